@@ -0,0 +1,229 @@
+// Badge codes travel in the Mumble user comment, so both the command layer and the
+// voice worker normalize them through here.
+const HARMONY_BADGES_COMMENT_PREFIX: &str = "harmony_badges:v1:";
+const HARMONY_META_COMMENT_PREFIX: &str = "harmony_meta:v1:";
+
+pub const MAX_BADGE_CODE_LEN: usize = 32;
+pub const DEFAULT_MAX_BADGE_CODES_PER_USER: usize = 5;
+// Keeps the comment well under the server's default message length limit.
+pub const MAX_BADGE_CODES_PER_USER_LIMIT: usize = 50;
+pub const MAX_AVATAR_SEED_LEN: usize = 32;
+
+// Optional display metadata carried on a second comment line next to the badges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserMeta {
+    pub color: Option<String>,
+    pub avatar_seed: Option<String>,
+}
+
+impl UserMeta {
+    pub fn normalized(color: Option<&str>, avatar_seed: Option<&str>) -> Self {
+        Self {
+            color: color.and_then(normalize_color),
+            avatar_seed: avatar_seed.and_then(normalize_avatar_seed),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.color.is_none() && self.avatar_seed.is_none()
+    }
+}
+
+// Six hex digits, with or without a leading '#'; stored lowercase without it.
+pub fn normalize_color(raw: &str) -> Option<String> {
+    let hex = raw.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    (hex.len() == 6 && hex.bytes().all(|value| value.is_ascii_hexdigit()))
+        .then(|| hex.to_ascii_lowercase())
+}
+
+pub fn normalize_avatar_seed(raw: &str) -> Option<String> {
+    let seed = raw.trim();
+    (!seed.is_empty()
+        && seed.len() <= MAX_AVATAR_SEED_LEN
+        && seed.bytes().all(|value| value.is_ascii_alphanumeric()))
+    .then(|| seed.to_string())
+}
+
+pub fn normalize_badge_codes(raw_codes: Vec<String>, max_codes: usize) -> Vec<String> {
+    let max_codes = max_codes.min(MAX_BADGE_CODES_PER_USER_LIMIT);
+    let mut normalized = Vec::new();
+
+    for raw in raw_codes {
+        if normalized.len() >= max_codes {
+            break;
+        }
+        let code = raw.trim().to_ascii_lowercase();
+        if code.is_empty() || code.len() > MAX_BADGE_CODE_LEN {
+            continue;
+        }
+        if !code.bytes().all(|value| {
+            value.is_ascii_lowercase() || value.is_ascii_digit() || value == b'-' || value == b'_'
+        }) {
+            continue;
+        }
+        if normalized.contains(&code) {
+            continue;
+        }
+        normalized.push(code);
+    }
+
+    normalized
+}
+
+pub fn encode_badge_comment(badge_codes: &[String], max_codes: usize, meta: &UserMeta) -> String {
+    let normalized = normalize_badge_codes(badge_codes.to_vec(), max_codes);
+    let mut comment = format!("{}{}", HARMONY_BADGES_COMMENT_PREFIX, normalized.join(","));
+    if meta.is_empty() {
+        return comment;
+    }
+
+    // The trailing comma keeps older clients, which split the whole comment on ',', from
+    // gluing the meta line onto the last badge; every meta field then fails their validation.
+    comment.push_str(",\n");
+    comment.push_str(HARMONY_META_COMMENT_PREFIX);
+    let fields = [("color", &meta.color), ("avatar", &meta.avatar_seed)]
+        .into_iter()
+        .filter_map(|(key, value)| value.as_ref().map(|value| format!("{key}={value}")))
+        .collect::<Vec<_>>();
+    comment.push_str(&fields.join(","));
+    comment
+}
+
+pub fn parse_badge_comment(comment: &str, max_codes: usize) -> Option<Vec<String>> {
+    let payload = comment
+        .lines()
+        .find_map(|line| line.strip_prefix(HARMONY_BADGES_COMMENT_PREFIX))?;
+    let codes = payload
+        .split(',')
+        .map(str::trim)
+        .filter(|code| !code.is_empty())
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    Some(normalize_badge_codes(codes, max_codes))
+}
+
+pub fn parse_meta_comment(comment: &str) -> UserMeta {
+    let Some(payload) = comment
+        .lines()
+        .find_map(|line| line.strip_prefix(HARMONY_META_COMMENT_PREFIX))
+    else {
+        return UserMeta::default();
+    };
+
+    let mut meta = UserMeta::default();
+    for field in payload.split(',') {
+        match field.trim().split_once('=') {
+            Some(("color", value)) => meta.color = normalize_color(value),
+            Some(("avatar", value)) => meta.avatar_seed = normalize_avatar_seed(value),
+            _ => {}
+        }
+    }
+    meta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn badge_comment_round_trip_encodes_and_decodes() {
+        let input = vec!["rainbow-core".to_string(), "party-parrot".to_string()];
+        let encoded = encode_badge_comment(
+            &input,
+            DEFAULT_MAX_BADGE_CODES_PER_USER,
+            &UserMeta::default(),
+        );
+        let decoded = parse_badge_comment(&encoded, DEFAULT_MAX_BADGE_CODES_PER_USER)
+            .expect("should parse encoded payload");
+        assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn badge_comment_round_trip_respects_a_raised_limit() {
+        let input = (0..12)
+            .map(|index| format!("badge-{index}"))
+            .collect::<Vec<_>>();
+        let encoded = encode_badge_comment(&input, 10, &UserMeta::default());
+        let decoded = parse_badge_comment(&encoded, 10).expect("should parse encoded payload");
+        assert_eq!(decoded, input[..10].to_vec());
+        assert_eq!(
+            parse_badge_comment(&encoded, 3).unwrap(),
+            input[..3].to_vec()
+        );
+    }
+
+    #[test]
+    fn meta_line_round_trips_alongside_badges() {
+        let input = vec!["rainbow-core".to_string()];
+        let meta = UserMeta::normalized(Some("#1E90FF"), Some("mason42"));
+        let encoded = encode_badge_comment(&input, DEFAULT_MAX_BADGE_CODES_PER_USER, &meta);
+
+        assert_eq!(
+            parse_badge_comment(&encoded, DEFAULT_MAX_BADGE_CODES_PER_USER).unwrap(),
+            input
+        );
+        assert_eq!(
+            parse_meta_comment(&encoded),
+            UserMeta {
+                color: Some("1e90ff".to_string()),
+                avatar_seed: Some("mason42".to_string()),
+            }
+        );
+
+        // What a client that only knows the badge line recovers from the same comment.
+        let legacy = encoded
+            .strip_prefix(HARMONY_BADGES_COMMENT_PREFIX)
+            .unwrap()
+            .split(',')
+            .map(str::trim)
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            normalize_badge_codes(legacy, DEFAULT_MAX_BADGE_CODES_PER_USER),
+            input
+        );
+    }
+
+    #[test]
+    fn meta_rejects_invalid_color_and_seed() {
+        assert_eq!(
+            parse_meta_comment(
+                "harmony_badges:v1:a,\nharmony_meta:v1:color=12345g,avatar=no spaces"
+            ),
+            UserMeta::default()
+        );
+        assert_eq!(
+            parse_meta_comment("harmony_badges:v1:a,b"),
+            UserMeta::default()
+        );
+        assert!(UserMeta::normalized(Some("fff"), Some(&"a".repeat(33))).is_empty());
+    }
+
+    #[test]
+    fn parse_badge_comment_ignores_non_harmony_payload() {
+        assert_eq!(
+            parse_badge_comment("hello world", DEFAULT_MAX_BADGE_CODES_PER_USER),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_badge_comment_normalizes_dedupes_and_caps() {
+        let parsed = parse_badge_comment(
+            "harmony_badges:v1:RAINBOW-CORE,party-parrot,rainbow-core,invalid!,a,b,c,d,e",
+            DEFAULT_MAX_BADGE_CODES_PER_USER,
+        )
+        .expect("payload should parse");
+        assert_eq!(
+            parsed,
+            vec![
+                "rainbow-core".to_string(),
+                "party-parrot".to_string(),
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string(),
+            ]
+        );
+    }
+}
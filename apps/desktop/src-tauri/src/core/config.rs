@@ -1,23 +1,42 @@
 use std::collections::HashMap;
+use std::ffi::OsString;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use super::badges::DEFAULT_MAX_BADGE_CODES_PER_USER;
+use super::voice::{
+    OPUS_BITRATE_MAX_BPS, OPUS_BITRATE_MIN_BPS, OPUS_COMPLEXITY_MAX, OPUS_COMPLEXITY_MIN,
+    OPUS_PACKET_LOSS_MAX_PCT, RX_JITTER_MAX_MAX, RX_JITTER_MAX_MIN, RX_JITTER_TARGET_MAX,
+    RX_JITTER_TARGET_MIN,
+};
+
 const APP_CONFIG_DIR: &str = "Harmony";
 const APP_CONFIG_FILE: &str = "config.json";
 const DEV_CONFIG_FILE: &str = "dev-config.json";
 const DEV_CONFIG_ENV: &str = "HARMONY_DEV_CONFIG";
+const DATA_DIR_ENV: &str = "HARMONY_DATA_DIR";
 
 pub const DEFAULT_SERVER_HOST: &str = "ec2-3-133-108-176.us-east-2.compute.amazonaws.com";
-pub const DEFAULT_USER_PASSWORD: &str = "Hoez312!!!";
-pub const SUPERUSER_TRIGGER_NICKNAME: &str = "spaceKomo";
-pub const SUPERUSER_AUTH_USERNAME: &str = "SuperUser";
-pub const SUPERUSER_AUTH_PASSWORD: &str = "Discourse312Gb!!!";
+// Only used as defaults so configs written by older builds keep authenticating the same way.
+const DEFAULT_USER_PASSWORD: &str = "Hoez312!!!";
+const DEFAULT_ADMIN_TRIGGER_NICKNAME: &str = "spaceKomo";
+const DEFAULT_ADMIN_USERNAME: &str = "SuperUser";
+const DEFAULT_ADMIN_PASSWORD: &str = "Discourse312Gb!!!";
 const LEGACY_LOCALHOST_IP: &str = "127.0.0.1";
 const LEGACY_LOCALHOST_NAME: &str = "localhost";
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PttMode {
+    #[default]
+    Vad,
+    PushToTalk,
+    PushToMute,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ServerConfig {
     pub host: String,
@@ -25,36 +44,188 @@ pub struct ServerConfig {
     #[serde(default)]
     pub password: Option<String>,
     pub default_channel: String,
+    // Try the _mumble._tcp SRV record first (port 0 implies this), falling back to host:port.
+    #[serde(default)]
+    pub use_srv: bool,
+    // Off leaves the user wherever the server placed them on connect.
+    #[serde(default = "default_auto_join_default_channel")]
+    pub auto_join_default_channel: bool,
     #[serde(default)]
     pub allow_insecure_tls: bool,
+    #[serde(default = "default_reconnect_max_delay_secs")]
+    pub reconnect_max_delay_secs: u64,
+    #[serde(default)]
+    pub reconnect_max_attempts: u32,
+    #[serde(default = "default_auto_reconnect")]
+    pub auto_reconnect: bool,
+    // For networks that block UDP: never open the UDP socket and always tunnel voice over TCP.
+    #[serde(default)]
+    pub force_tcp_tunnel: bool,
+    #[serde(default)]
+    pub access_tokens: HashMap<String, String>,
+    // Server-wide ACL tokens; `access_tokens` holds per-channel passwords keyed by channel id.
+    #[serde(default)]
+    pub tokens: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+impl ServerConfig {
+    // Everything sent in Authenticate.tokens, without duplicates.
+    pub fn all_access_tokens(&self) -> Vec<String> {
+        let mut tokens = self.tokens.clone();
+        for token in self.access_tokens.values() {
+            if !tokens.contains(token) {
+                tokens.push(token.clone());
+            }
+        }
+        tokens
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AppConfig {
     pub nickname: String,
     #[serde(default)]
     pub badge_profiles: HashMap<String, Vec<String>>,
+    #[serde(default = "default_max_badge_codes_per_user")]
+    pub max_badge_codes_per_user: usize,
+    // Shared with other Harmony users through the comment, next to the badges.
+    #[serde(default)]
+    pub display_color: Option<String>,
+    #[serde(default)]
+    pub avatar_seed: Option<String>,
     #[serde(default = "default_remember_me")]
     pub remember_me: bool,
     #[serde(default)]
-    pub ptt_enabled: bool,
+    pub ptt_mode: PttMode,
+    // Pre-ptt_mode boolean; read once on load and folded into ptt_mode.
+    #[serde(default, skip_serializing)]
+    pub ptt_enabled: Option<bool>,
     #[serde(default = "default_ptt_hotkey")]
     pub ptt_hotkey: String,
     #[serde(default)]
+    pub mute_hotkey: Option<String>,
+    #[serde(default)]
+    pub deafen_hotkey: Option<String>,
+    #[serde(default)]
     pub input_device: Option<String>,
     #[serde(default)]
     pub output_device: Option<String>,
+    // cpal host name such as "JACK" or "ASIO"; unset uses the platform default.
+    #[serde(default)]
+    pub audio_host: Option<String>,
     #[serde(default = "default_output_volume")]
     pub output_volume: u8,
     #[serde(default = "default_auto_mute_on_deafen")]
     pub auto_mute_on_deafen: bool,
     #[serde(default)]
+    pub start_muted: bool,
+    #[serde(default)]
+    pub start_deafened: bool,
+    #[serde(default = "default_soundboard_storage_limit_mb")]
+    pub soundboard_storage_limit_mb: u32,
+    #[serde(default)]
     pub voice_quality: VoiceQualityConfig,
     #[serde(default)]
     pub server: ServerConfig,
+    #[serde(default)]
+    pub cues: CueConfig,
+    #[serde(default)]
+    pub soundboard: SoundboardConfig,
+    #[serde(default, skip_serializing_if = "AuthConfig::is_builtin")]
+    pub auth: AuthConfig,
+}
+
+// Built-in values stay in memory only; a field is written out once it differs from them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuthConfig {
+    // The admin pair is used when the nickname matches this; null turns the trigger off.
+    #[serde(
+        default = "default_admin_trigger_nickname",
+        skip_serializing_if = "is_default_admin_trigger_nickname"
+    )]
+    pub admin_trigger_nickname: Option<String>,
+    // Uses the admin pair for every nickname.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub use_admin_account: bool,
+    #[serde(
+        default = "default_admin_username",
+        skip_serializing_if = "is_default_admin_username"
+    )]
+    pub admin_username: Option<String>,
+    #[serde(
+        default = "default_admin_password",
+        skip_serializing_if = "is_default_admin_password"
+    )]
+    pub admin_password: Option<String>,
+    // Sent when the server entry has no password of its own.
+    #[serde(
+        default = "default_fallback_password",
+        skip_serializing_if = "is_default_fallback_password"
+    )]
+    pub fallback_password: Option<String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            admin_trigger_nickname: default_admin_trigger_nickname(),
+            use_admin_account: false,
+            admin_username: default_admin_username(),
+            admin_password: default_admin_password(),
+            fallback_password: default_fallback_password(),
+        }
+    }
+}
+
+impl AuthConfig {
+    fn is_builtin(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
+pub const CUE_NONE: &str = "none";
+
+// How clips are mixed into the outgoing voice frame.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct SoundboardConfig {
+    #[serde(default = "default_soundboard_mix_gain")]
+    pub mix_gain: f32,
+    // Attenuate the mic while a clip plays so the clip stays audible.
+    #[serde(default)]
+    pub duck_voice: bool,
+}
+
+impl Default for SoundboardConfig {
+    fn default() -> Self {
+        Self {
+            mix_gain: default_soundboard_mix_gain(),
+            duck_voice: false,
+        }
+    }
+}
+
+// Each cue names a soundboard clip id, or "none" to stay silent.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CueConfig {
+    #[serde(default = "default_join_cue")]
+    pub join: String,
+    #[serde(default = "default_leave_cue")]
+    pub leave: String,
+    #[serde(default = "default_mute_cue")]
+    pub mute: String,
+}
+
+impl Default for CueConfig {
+    fn default() -> Self {
+        Self {
+            join: default_join_cue(),
+            leave: default_leave_cue(),
+            mute: default_mute_cue(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct VoiceQualityConfig {
     #[serde(default = "default_opus_bitrate_bps")]
     pub opus_bitrate_bps: i32,
@@ -64,8 +235,41 @@ pub struct VoiceQualityConfig {
     pub jitter_target_frames: usize,
     #[serde(default = "default_jitter_max_frames")]
     pub jitter_max_frames: usize,
+    // Frames a stream buffers before it starts playing; unset waits for the jitter target.
+    #[serde(default)]
+    pub initial_prefill_frames: Option<usize>,
     #[serde(default = "default_inband_fec")]
     pub inband_fec: bool,
+    #[serde(default = "default_adaptive_enabled")]
+    pub adaptive_enabled: bool,
+    #[serde(default = "default_opus_complexity")]
+    pub opus_complexity: i32,
+    #[serde(default = "default_vad_threshold")]
+    pub vad_threshold: f32,
+    #[serde(default)]
+    pub vad_off_threshold: Option<f32>,
+    #[serde(default = "default_vad_hold_frames")]
+    pub vad_hold_frames: u32,
+    #[serde(default = "default_vad_attack_frames")]
+    pub vad_attack_frames: u32,
+    #[serde(default)]
+    pub vad_smoothing: f32,
+    #[serde(default)]
+    pub stereo_spread: bool,
+    // Gain on everyone else while a priority speaker talks; 1.0 turns ducking off.
+    #[serde(default = "default_priority_duck_gain")]
+    pub priority_duck_gain: f32,
+    #[serde(default = "default_output_buffer_ms")]
+    pub output_buffer_ms: u32,
+    #[serde(default = "default_output_prefill_ms")]
+    pub output_prefill_ms: u32,
+    #[serde(default)]
+    pub hq_resampling: bool,
+    // Open devices at 48 kHz when they support it instead of at their default rate.
+    #[serde(default = "default_prefer_48k")]
+    pub prefer_48k: bool,
+    #[serde(default = "default_tx_limiter")]
+    pub tx_limiter: bool,
 }
 
 impl Default for VoiceQualityConfig {
@@ -75,7 +279,22 @@ impl Default for VoiceQualityConfig {
             packet_loss_perc: default_packet_loss_perc(),
             jitter_target_frames: default_jitter_target_frames(),
             jitter_max_frames: default_jitter_max_frames(),
+            initial_prefill_frames: None,
             inband_fec: default_inband_fec(),
+            adaptive_enabled: default_adaptive_enabled(),
+            opus_complexity: default_opus_complexity(),
+            vad_threshold: default_vad_threshold(),
+            vad_off_threshold: None,
+            vad_hold_frames: default_vad_hold_frames(),
+            vad_attack_frames: default_vad_attack_frames(),
+            vad_smoothing: 0.0,
+            stereo_spread: false,
+            priority_duck_gain: default_priority_duck_gain(),
+            output_buffer_ms: default_output_buffer_ms(),
+            output_prefill_ms: default_output_prefill_ms(),
+            hq_resampling: false,
+            prefer_48k: default_prefer_48k(),
+            tx_limiter: default_tx_limiter(),
         }
     }
 }
@@ -87,7 +306,15 @@ impl Default for ServerConfig {
             port: 64738,
             password: Some(DEFAULT_USER_PASSWORD.to_string()),
             default_channel: "Game Night".to_string(),
+            use_srv: false,
+            auto_join_default_channel: default_auto_join_default_channel(),
             allow_insecure_tls: true,
+            reconnect_max_delay_secs: default_reconnect_max_delay_secs(),
+            reconnect_max_attempts: 0,
+            auto_reconnect: default_auto_reconnect(),
+            force_tcp_tunnel: false,
+            access_tokens: HashMap::new(),
+            tokens: Vec::new(),
         }
     }
 }
@@ -97,15 +324,28 @@ impl Default for AppConfig {
         Self {
             nickname: String::new(),
             badge_profiles: HashMap::new(),
+            max_badge_codes_per_user: default_max_badge_codes_per_user(),
+            display_color: None,
+            avatar_seed: None,
             remember_me: default_remember_me(),
-            ptt_enabled: false,
+            ptt_mode: PttMode::default(),
+            ptt_enabled: None,
             ptt_hotkey: default_ptt_hotkey(),
+            mute_hotkey: None,
+            deafen_hotkey: None,
             input_device: None,
             output_device: None,
+            audio_host: None,
             output_volume: default_output_volume(),
             auto_mute_on_deafen: default_auto_mute_on_deafen(),
+            start_muted: false,
+            start_deafened: false,
+            soundboard_storage_limit_mb: default_soundboard_storage_limit_mb(),
             voice_quality: VoiceQualityConfig::default(),
             server: ServerConfig::default(),
+            cues: CueConfig::default(),
+            soundboard: SoundboardConfig::default(),
+            auth: AuthConfig::default(),
         }
     }
 }
@@ -138,6 +378,8 @@ pub enum ConfigError {
     },
     #[error("failed to serialize config: {0}")]
     Serialize(#[from] serde_json::Error),
+    #[error("invalid config: {0}")]
+    InvalidImport(serde_json::Error),
     #[error("failed to write config file {path}: {source}")]
     WriteFile {
         path: String,
@@ -147,7 +389,9 @@ pub enum ConfigError {
 
 pub fn load_config() -> Result<LoadedConfig, ConfigError> {
     if let Some(dev_path) = find_dev_config() {
-        let config = read_config(&dev_path)?;
+        let mut config = read_config(&dev_path)?;
+        apply_ptt_mode_migration(&mut config);
+        clamp_voice_quality(&mut config.voice_quality);
         return Ok(LoadedConfig {
             config,
             path: dev_path,
@@ -158,7 +402,9 @@ pub fn load_config() -> Result<LoadedConfig, ConfigError> {
     let path = persistent_config_path()?;
     if path.exists() {
         let mut config = read_config(&path)?;
-        if apply_legacy_server_migration(&mut config) {
+        let ptt_migrated = apply_ptt_mode_migration(&mut config);
+        let quality_clamped = clamp_voice_quality(&mut config.voice_quality);
+        if apply_legacy_server_migration(&mut config) || ptt_migrated || quality_clamped {
             save_config_to_path(&path, &config)?;
         }
         return Ok(LoadedConfig {
@@ -178,6 +424,14 @@ pub fn load_config() -> Result<LoadedConfig, ConfigError> {
     })
 }
 
+// Reads an edited config file; unlike load_config it never writes back.
+pub fn reload_config(path: &Path) -> Result<AppConfig, ConfigError> {
+    let mut config = read_config(path)?;
+    apply_ptt_mode_migration(&mut config);
+    clamp_voice_quality(&mut config.voice_quality);
+    Ok(config)
+}
+
 pub fn save_config_to_path(path: &Path, config: &AppConfig) -> Result<(), ConfigError> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|source| ConfigError::CreateDir {
@@ -193,11 +447,73 @@ pub fn save_config_to_path(path: &Path, config: &AppConfig) -> Result<(), Config
     })
 }
 
+pub fn export_config_json(config: &AppConfig, redact_secrets: bool) -> Result<String, ConfigError> {
+    let mut exported = config.clone();
+    if redact_secrets {
+        exported.server.password = None;
+        exported.server.access_tokens.clear();
+        exported.server.tokens.clear();
+        exported.auth.admin_password = None;
+        exported.auth.fallback_password = None;
+    }
+    Ok(serde_json::to_string_pretty(&exported)?)
+}
+
+// A redacted export blanks these; importing one should not wipe the secrets already saved here.
+pub fn keep_redacted_secrets(imported: &mut AppConfig, current: &AppConfig) {
+    let (server, auth) = (&mut imported.server, &mut imported.auth);
+    if server.password.is_none() {
+        server.password.clone_from(&current.server.password);
+    }
+    if server.access_tokens.is_empty() {
+        server
+            .access_tokens
+            .clone_from(&current.server.access_tokens);
+    }
+    if server.tokens.is_empty() {
+        server.tokens.clone_from(&current.server.tokens);
+    }
+    if auth.admin_password.is_none() {
+        auth.admin_password.clone_from(&current.auth.admin_password);
+    }
+    if auth.fallback_password.is_none() {
+        auth.fallback_password
+            .clone_from(&current.auth.fallback_password);
+    }
+}
+
+pub fn parse_config_json(raw: &str) -> Result<AppConfig, ConfigError> {
+    let mut config: AppConfig = serde_json::from_str(raw).map_err(ConfigError::InvalidImport)?;
+    apply_ptt_mode_migration(&mut config);
+    clamp_voice_quality(&mut config.voice_quality);
+    Ok(config)
+}
+
 pub fn persistent_config_path() -> Result<PathBuf, ConfigError> {
+    if let Some(data_dir) = data_dir_override()? {
+        return Ok(data_dir.join(APP_CONFIG_FILE));
+    }
     let base_dir = dirs::config_dir().ok_or(ConfigError::NoConfigDirectory)?;
     Ok(base_dir.join(APP_CONFIG_DIR).join(APP_CONFIG_FILE))
 }
 
+// Portable installs set HARMONY_DATA_DIR to keep config and soundboard under one root.
+pub fn data_dir_override() -> Result<Option<PathBuf>, ConfigError> {
+    prepare_data_dir(std::env::var_os(DATA_DIR_ENV))
+}
+
+fn prepare_data_dir(raw: Option<OsString>) -> Result<Option<PathBuf>, ConfigError> {
+    let Some(raw) = raw.filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    let data_dir = PathBuf::from(raw);
+    fs::create_dir_all(&data_dir).map_err(|source| ConfigError::CreateDir {
+        path: data_dir.display().to_string(),
+        source,
+    })?;
+    Ok(Some(data_dir))
+}
+
 fn read_config(path: &Path) -> Result<AppConfig, ConfigError> {
     let raw = fs::read_to_string(path).map_err(|source| ConfigError::ReadFile {
         path: path.display().to_string(),
@@ -245,6 +561,52 @@ fn apply_legacy_server_migration(config: &mut AppConfig) -> bool {
     false
 }
 
+fn apply_ptt_mode_migration(config: &mut AppConfig) -> bool {
+    let Some(enabled) = config.ptt_enabled.take() else {
+        return false;
+    };
+    if enabled && config.ptt_mode == PttMode::Vad {
+        config.ptt_mode = PttMode::PushToTalk;
+    }
+    true
+}
+
+// Hand-edited values are pulled back into the range the voice runtime supports.
+fn clamp_voice_quality(quality: &mut VoiceQualityConfig) -> bool {
+    let before = quality.clone();
+    quality.opus_bitrate_bps = quality
+        .opus_bitrate_bps
+        .clamp(OPUS_BITRATE_MIN_BPS, OPUS_BITRATE_MAX_BPS);
+    quality.packet_loss_perc = quality.packet_loss_perc.clamp(0, OPUS_PACKET_LOSS_MAX_PCT);
+    quality.opus_complexity = quality
+        .opus_complexity
+        .clamp(OPUS_COMPLEXITY_MIN, OPUS_COMPLEXITY_MAX);
+    quality.jitter_target_frames = quality
+        .jitter_target_frames
+        .clamp(RX_JITTER_TARGET_MIN, RX_JITTER_TARGET_MAX);
+    quality.jitter_max_frames = quality
+        .jitter_max_frames
+        .clamp(RX_JITTER_MAX_MIN, RX_JITTER_MAX_MAX);
+    // Matches the worker's floor so the saved max is the one that actually runs.
+    if quality.jitter_max_frames <= quality.jitter_target_frames {
+        quality.jitter_max_frames = (quality.jitter_target_frames + 2).min(RX_JITTER_MAX_MAX);
+    }
+    quality.initial_prefill_frames = quality
+        .initial_prefill_frames
+        .map(|frames| frames.clamp(1, quality.jitter_max_frames));
+    quality.priority_duck_gain = if quality.priority_duck_gain.is_finite() {
+        quality.priority_duck_gain.clamp(0.0, 1.0)
+    } else {
+        default_priority_duck_gain()
+    };
+    *quality != before
+}
+
+// Roughly -9 dB: others stay audible under the priority speaker.
+const fn default_priority_duck_gain() -> f32 {
+    0.35
+}
+
 const fn default_remember_me() -> bool {
     true
 }
@@ -253,10 +615,70 @@ fn default_ptt_hotkey() -> String {
     "AltLeft".to_string()
 }
 
+fn default_join_cue() -> String {
+    "default-chime".to_string()
+}
+
+fn default_leave_cue() -> String {
+    "default-pop".to_string()
+}
+
+fn default_mute_cue() -> String {
+    "default-rim".to_string()
+}
+
 const fn default_output_volume() -> u8 {
     80
 }
 
+fn default_admin_trigger_nickname() -> Option<String> {
+    Some(DEFAULT_ADMIN_TRIGGER_NICKNAME.to_string())
+}
+
+fn default_admin_username() -> Option<String> {
+    Some(DEFAULT_ADMIN_USERNAME.to_string())
+}
+
+fn default_admin_password() -> Option<String> {
+    Some(DEFAULT_ADMIN_PASSWORD.to_string())
+}
+
+fn default_fallback_password() -> Option<String> {
+    Some(DEFAULT_USER_PASSWORD.to_string())
+}
+
+fn is_default_admin_trigger_nickname(value: &Option<String>) -> bool {
+    value.as_deref() == Some(DEFAULT_ADMIN_TRIGGER_NICKNAME)
+}
+
+fn is_default_admin_username(value: &Option<String>) -> bool {
+    value.as_deref() == Some(DEFAULT_ADMIN_USERNAME)
+}
+
+fn is_default_admin_password(value: &Option<String>) -> bool {
+    value.as_deref() == Some(DEFAULT_ADMIN_PASSWORD)
+}
+
+fn is_default_fallback_password(value: &Option<String>) -> bool {
+    value.as_deref() == Some(DEFAULT_USER_PASSWORD)
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+const fn default_max_badge_codes_per_user() -> usize {
+    DEFAULT_MAX_BADGE_CODES_PER_USER
+}
+
+const fn default_soundboard_mix_gain() -> f32 {
+    0.55
+}
+
+const fn default_soundboard_storage_limit_mb() -> u32 {
+    100
+}
+
 const fn default_auto_mute_on_deafen() -> bool {
     true
 }
@@ -281,10 +703,119 @@ const fn default_inband_fec() -> bool {
     true
 }
 
+const fn default_adaptive_enabled() -> bool {
+    true
+}
+
+const fn default_tx_limiter() -> bool {
+    true
+}
+
+const fn default_prefer_48k() -> bool {
+    true
+}
+
+const fn default_opus_complexity() -> i32 {
+    8
+}
+
+#[cfg(target_os = "macos")]
+const fn default_vad_threshold() -> f32 {
+    0.010
+}
+
+#[cfg(not(target_os = "macos"))]
+const fn default_vad_threshold() -> f32 {
+    0.015
+}
+
+const fn default_vad_hold_frames() -> u32 {
+    3
+}
+
+const fn default_vad_attack_frames() -> u32 {
+    1
+}
+
+const fn default_output_buffer_ms() -> u32 {
+    1_200
+}
+
+const fn default_output_prefill_ms() -> u32 {
+    45
+}
+
+const fn default_reconnect_max_delay_secs() -> u64 {
+    32
+}
+
+const fn default_auto_reconnect() -> bool {
+    true
+}
+
+const fn default_auto_join_default_channel() -> bool {
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn data_dir_override_is_created_and_ignored_when_empty() {
+        assert!(prepare_data_dir(None).unwrap().is_none());
+        assert!(prepare_data_dir(Some(OsString::new())).unwrap().is_none());
+
+        let root = std::env::temp_dir().join(format!("harmony-data-dir-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let nested = root.join("portable");
+        let resolved = prepare_data_dir(Some(nested.clone().into_os_string()))
+            .unwrap()
+            .expect("override should resolve");
+        assert_eq!(resolved, nested);
+        assert!(nested.is_dir());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn default_voice_quality_is_left_untouched_by_clamping() {
+        let mut quality = VoiceQualityConfig::default();
+        assert!(!clamp_voice_quality(&mut quality));
+        assert_eq!(quality, VoiceQualityConfig::default());
+    }
+
+    #[test]
+    fn out_of_range_voice_quality_is_clamped_on_parse() {
+        let mut raw = serde_json::to_value(AppConfig::default()).unwrap();
+        raw["voice_quality"] = serde_json::json!({
+            "opus_bitrate_bps": 5000,
+            "packet_loss_perc": -3,
+            "opus_complexity": 40,
+            "jitter_target_frames": 7,
+            "jitter_max_frames": 5,
+            "initial_prefill_frames": 12,
+        });
+        let config = parse_config_json(&raw.to_string()).unwrap();
+        let quality = &config.voice_quality;
+        assert_eq!(quality.opus_bitrate_bps, OPUS_BITRATE_MIN_BPS);
+        assert_eq!(quality.packet_loss_perc, 0);
+        assert_eq!(quality.opus_complexity, OPUS_COMPLEXITY_MAX);
+        assert_eq!(quality.jitter_target_frames, 7);
+        assert_eq!(quality.jitter_max_frames, 9);
+        assert_eq!(quality.initial_prefill_frames, Some(9));
+
+        let mut oversized = VoiceQualityConfig {
+            jitter_target_frames: 0,
+            jitter_max_frames: 400,
+            initial_prefill_frames: Some(0),
+            ..VoiceQualityConfig::default()
+        };
+        assert!(clamp_voice_quality(&mut oversized));
+        assert_eq!(oversized.jitter_target_frames, RX_JITTER_TARGET_MIN);
+        assert_eq!(oversized.jitter_max_frames, RX_JITTER_MAX_MAX);
+        assert_eq!(oversized.initial_prefill_frames, Some(1));
+    }
+
     #[test]
     fn default_config_round_trip_serializes() {
         let config = AppConfig::default();
@@ -293,6 +824,22 @@ mod tests {
         assert_eq!(back, config);
     }
 
+    #[test]
+    fn built_in_auth_defaults_are_not_written_out() {
+        let serialized = serde_json::to_string(&AppConfig::default()).unwrap();
+        assert!(!serialized.contains(DEFAULT_ADMIN_PASSWORD));
+        assert!(!serialized.contains("\"auth\""));
+
+        let mut config = AppConfig::default();
+        config.auth.admin_trigger_nickname = None;
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(serialized.contains("\"admin_trigger_nickname\":null"));
+        assert!(!serialized.contains(DEFAULT_ADMIN_PASSWORD));
+        let back: AppConfig = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(back.auth.admin_trigger_nickname, None);
+        assert_eq!(back.auth, config.auth);
+    }
+
     #[test]
     fn migration_updates_legacy_localhost_config() {
         let mut config = AppConfig {
@@ -302,6 +849,7 @@ mod tests {
                 password: None,
                 default_channel: "Game Night".to_string(),
                 allow_insecure_tls: true,
+                ..ServerConfig::default()
             },
             ..AppConfig::default()
         };
@@ -324,6 +872,7 @@ mod tests {
                 password: None,
                 default_channel: "Game Night".to_string(),
                 allow_insecure_tls: true,
+                ..ServerConfig::default()
             },
             ..AppConfig::default()
         };
@@ -333,4 +882,90 @@ mod tests {
         assert_eq!(config.server.host, "voice.example.com");
         assert_eq!(config.server.password, None);
     }
+
+    #[test]
+    fn export_config_redacts_server_password_when_requested() {
+        let mut config = AppConfig::default();
+        config
+            .server
+            .access_tokens
+            .insert("12".to_string(), "secret".to_string());
+        config.server.tokens.push("guild".to_string());
+
+        let redacted = parse_config_json(&export_config_json(&config, true).expect("exports"))
+            .expect("redacted export parses");
+        assert_eq!(redacted.server.password, None);
+        assert!(redacted.server.access_tokens.is_empty());
+        assert!(redacted.server.tokens.is_empty());
+        assert_eq!(redacted.server.host, config.server.host);
+
+        let full = parse_config_json(&export_config_json(&config, false).expect("exports"))
+            .expect("full export parses");
+        assert_eq!(full, config);
+    }
+
+    #[test]
+    fn importing_a_redacted_export_keeps_saved_secrets() {
+        let mut current = AppConfig::default();
+        current.server.password = Some("hunter2".to_string());
+        current.server.tokens.push("guild".to_string());
+        current
+            .server
+            .access_tokens
+            .insert("12".to_string(), "secret".to_string());
+        current.auth.fallback_password = Some("fallback".to_string());
+
+        let mut imported = parse_config_json(&export_config_json(&current, true).expect("exports"))
+            .expect("redacted export parses");
+        imported.nickname = "Imported".to_string();
+        keep_redacted_secrets(&mut imported, &current);
+
+        assert_eq!(imported.nickname, "Imported");
+        assert_eq!(imported.server, current.server);
+        assert_eq!(imported.auth, current.auth);
+    }
+
+    #[test]
+    fn all_access_tokens_merges_channel_passwords_without_duplicates() {
+        let mut server = ServerConfig {
+            tokens: vec!["guild".to_string(), "raid".to_string()],
+            ..ServerConfig::default()
+        };
+        server
+            .access_tokens
+            .insert("4".to_string(), "raid".to_string());
+        server
+            .access_tokens
+            .insert("9".to_string(), "lobby".to_string());
+
+        assert_eq!(server.all_access_tokens(), ["guild", "raid", "lobby"]);
+    }
+
+    #[test]
+    fn legacy_ptt_enabled_maps_to_push_to_talk() {
+        let config = parse_config_json("{\"nickname\": \"mason\", \"ptt_enabled\": true}")
+            .expect("parses legacy config");
+        assert_eq!(config.ptt_mode, PttMode::PushToTalk);
+        assert_eq!(config.ptt_enabled, None);
+
+        let config = parse_config_json("{\"nickname\": \"mason\", \"ptt_enabled\": false}")
+            .expect("parses legacy config");
+        assert_eq!(config.ptt_mode, PttMode::Vad);
+
+        let serialized = serde_json::to_string(&config).expect("serializes config");
+        assert!(!serialized.contains("ptt_enabled"));
+        assert!(serialized.contains("\"ptt_mode\":\"vad\""));
+    }
+
+    #[test]
+    fn parse_config_json_rejects_malformed_input() {
+        assert!(matches!(
+            parse_config_json("{\"nickname\": 42}"),
+            Err(ConfigError::InvalidImport(_))
+        ));
+        assert!(matches!(
+            parse_config_json("not json"),
+            Err(ConfigError::InvalidImport(_))
+        ));
+    }
 }
@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use tauri::{AppHandle, Manager};
+use tokio::time::sleep;
+
+use super::AppCore;
+
+const CONFIG_POLL_INTERVAL_MS: u64 = 1_000;
+
+// Editors often write a file in several steps, so a change only counts once the mtime has held
+// still for a full poll.
+#[derive(Debug)]
+struct ChangeDebouncer {
+    last_seen: Option<SystemTime>,
+    pending: bool,
+}
+
+impl ChangeDebouncer {
+    fn new(initial: Option<SystemTime>) -> Self {
+        Self {
+            last_seen: initial,
+            pending: false,
+        }
+    }
+
+    fn observe(&mut self, modified: Option<SystemTime>) -> bool {
+        if modified != self.last_seen {
+            self.last_seen = modified;
+            self.pending = true;
+            return false;
+        }
+        std::mem::take(&mut self.pending)
+    }
+}
+
+pub async fn watch(app: AppHandle, path: PathBuf) {
+    log::info!("watching config file: path=\"{}\"", path.display());
+    let mut debouncer = ChangeDebouncer::new(modified_at(&path));
+    loop {
+        sleep(Duration::from_millis(CONFIG_POLL_INTERVAL_MS)).await;
+        if !debouncer.observe(modified_at(&path)) {
+            continue;
+        }
+        let state = app.state::<AppCore>();
+        if let Err(err) = state.reload_config_from_disk(&app).await {
+            log::warn!("config reload failed; keeping current config: {err}");
+        }
+    }
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debouncer_waits_for_mtime_to_settle() {
+        let start = SystemTime::UNIX_EPOCH;
+        let first_write = start + Duration::from_secs(1);
+        let second_write = start + Duration::from_secs(2);
+
+        let mut debouncer = ChangeDebouncer::new(Some(start));
+        assert!(!debouncer.observe(Some(start)));
+        assert!(!debouncer.observe(Some(first_write)));
+        assert!(!debouncer.observe(Some(second_write)));
+        assert!(debouncer.observe(Some(second_write)));
+        assert!(!debouncer.observe(Some(second_write)));
+    }
+}
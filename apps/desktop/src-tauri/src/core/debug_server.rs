@@ -0,0 +1,141 @@
+use std::sync::{Arc, RwLock as StdRwLock};
+
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+
+use super::events::{ConnectionEvent, RosterEvent};
+use super::voice::AudioQualityMetrics;
+
+pub const DEBUG_PORT_ENV: &str = "HARMONY_DEBUG_PORT";
+const MAX_REQUEST_HEAD_BYTES: usize = 8 * 1024;
+
+// Handles onto the live state owned by AppCore and the voice worker; nothing is copied.
+#[derive(Clone)]
+pub struct DebugSources {
+    pub connection: Arc<RwLock<ConnectionEvent>>,
+    pub roster: Arc<RwLock<RosterEvent>>,
+    pub quality_metrics: Arc<StdRwLock<AudioQualityMetrics>>,
+}
+
+#[derive(Serialize)]
+struct DebugSnapshot {
+    connection: ConnectionEvent,
+    roster: RosterEvent,
+    metrics: AudioQualityMetrics,
+}
+
+pub fn debug_port_from_env() -> Option<u16> {
+    let value = std::env::var(DEBUG_PORT_ENV).ok()?;
+    match parse_debug_port(&value) {
+        Some(port) => Some(port),
+        None => {
+            log::warn!("ignoring invalid {DEBUG_PORT_ENV} value: {value:?}");
+            None
+        }
+    }
+}
+
+fn parse_debug_port(value: &str) -> Option<u16> {
+    value.trim().parse::<u16>().ok().filter(|port| *port != 0)
+}
+
+pub async fn serve(port: u16, sources: DebugSources) {
+    let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            log::warn!("failed to bind debug server on 127.0.0.1:{port}: {err}");
+            return;
+        }
+    };
+    log::info!("debug server listening on http://127.0.0.1:{port}/");
+
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                log::debug!("debug server accept failed: {err}");
+                continue;
+            }
+        };
+        let sources = sources.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = handle_connection(stream, &sources).await {
+                log::debug!("debug server request failed: {err}");
+            }
+        });
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, sources: &DebugSources) -> Result<(), String> {
+    let mut head = Vec::with_capacity(1024);
+    let mut buf = [0_u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buf).await.map_err(|err| err.to_string())?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..read]);
+        if head.len() > MAX_REQUEST_HEAD_BYTES {
+            break;
+        }
+    }
+
+    let response = if is_get_request(&head) {
+        let body = snapshot_json(sources).await?;
+        http_response("200 OK", &body)
+    } else {
+        http_response(
+            "405 Method Not Allowed",
+            "{\"error\":\"only GET is supported\"}",
+        )
+    };
+    stream
+        .write_all(response.as_bytes())
+        .await
+        .map_err(|err| err.to_string())?;
+    stream.shutdown().await.map_err(|err| err.to_string())
+}
+
+async fn snapshot_json(sources: &DebugSources) -> Result<String, String> {
+    let snapshot = DebugSnapshot {
+        connection: sources.connection.read().await.clone(),
+        roster: sources.roster.read().await.clone(),
+        metrics: sources
+            .quality_metrics
+            .read()
+            .map(|metrics| metrics.clone())
+            .unwrap_or_default(),
+    };
+    serde_json::to_string_pretty(&snapshot).map_err(|err| err.to_string())
+}
+
+fn is_get_request(head: &[u8]) -> bool {
+    head.split(|byte| *byte == b' ')
+        .next()
+        .is_some_and(|method| method == b"GET")
+}
+
+fn http_response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_debug_port_and_get_requests() {
+        assert_eq!(parse_debug_port(" 9331 "), Some(9331));
+        assert_eq!(parse_debug_port("0"), None);
+        assert_eq!(parse_debug_port("not-a-port"), None);
+
+        assert!(is_get_request(b"GET / HTTP/1.1\r\n\r\n"));
+        assert!(!is_get_request(b"POST / HTTP/1.1\r\n\r\n"));
+        assert!(!is_get_request(b""));
+    }
+}
@@ -1,12 +1,25 @@
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, Runtime};
 
+use super::config::{AppConfig, PttMode};
+
 pub const EVENT_CONNECTION: &str = "core/connection";
 pub const EVENT_ROSTER: &str = "core/roster";
 pub const EVENT_SPEAKING: &str = "core/speaking";
 pub const EVENT_DEVICES: &str = "core/devices";
 pub const EVENT_SELF: &str = "core/self";
 pub const EVENT_MESSAGE: &str = "core/message";
+pub const EVENT_INPUT_LEVEL: &str = "core/input-level";
+pub const EVENT_PERMISSION_DENIED: &str = "core/permission-denied";
+pub const EVENT_RECORDING: &str = "core/recording";
+pub const EVENT_NETWORK: &str = "core/network";
+pub const EVENT_MIC_LEVEL: &str = "core/mic-level";
+pub const EVENT_OUTPUT_LEVEL: &str = "core/output-level";
+pub const EVENT_AUDIO_WARNING: &str = "core/audio-warning";
+pub const EVENT_USER_PRESENCE: &str = "core/user-presence";
+pub const EVENT_TALKING_WHILE_MUTED: &str = "core/talking-while-muted";
+pub const EVENT_CONFIG: &str = "core/config";
+pub const EVENT_SELF_ENFORCED: &str = "core/self-enforced";
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -17,11 +30,26 @@ pub enum ConnectionState {
     Reconnecting,
 }
 
+// Handshake step reported while connecting, ending at Synced once ServerSync arrives.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionPhase {
+    Resolving,
+    Tcp,
+    Tls,
+    Version,
+    Authenticating,
+    AwaitingSync,
+    Synced,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ConnectionEvent {
     pub state: ConnectionState,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<ConnectionPhase>,
 }
 
 impl Default for ConnectionEvent {
@@ -29,10 +57,24 @@ impl Default for ConnectionEvent {
         Self {
             state: ConnectionState::Disconnected,
             reason: None,
+            phase: None,
         }
     }
 }
 
+// Filled from the server's Version and ServerSync packets; empty until they arrive.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct ServerInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub welcome_text: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ChannelInfo {
     pub id: String,
@@ -44,9 +86,21 @@ pub struct RosterUser {
     pub id: String,
     pub name: String,
     pub badge_codes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar_seed: Option<String>,
     pub muted: bool,
     pub deafened: bool,
     pub speaking: bool,
+    // Silenced on our side only; the server and other listeners still hear them.
+    #[serde(default)]
+    pub locally_muted: bool,
+    #[serde(default)]
+    pub priority_speaker: bool,
+    // Channels this user hears without being in them (Mumble 1.4 channel listeners).
+    #[serde(default)]
+    pub listening_channel_ids: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -73,12 +127,27 @@ pub struct SpeakingEvent {
     pub speaking: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub level: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<[f32; 3]>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UserPresenceEvent {
+    pub user_id: String,
+    pub name: String,
+    pub joined: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct DeviceInfo {
     pub id: String,
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_sample_rate: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channels: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_format: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -87,23 +156,36 @@ pub struct DevicesEvent {
     pub outputs: Vec<DeviceInfo>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct SelfEvent {
     pub muted: bool,
     pub deafened: bool,
     pub ptt_enabled: bool,
+    #[serde(default)]
+    pub ptt_mode: PttMode,
     pub transmitting: bool,
+    // Connected without a microphone; stays muted for the whole session.
+    #[serde(default)]
+    pub listen_only: bool,
+    // Incoming voice from everyone else is dropped locally; cues still play.
+    #[serde(default)]
+    pub all_users_muted: bool,
 }
 
-impl Default for SelfEvent {
-    fn default() -> Self {
-        Self {
-            muted: false,
-            deafened: false,
-            ptt_enabled: false,
-            transmitting: false,
-        }
-    }
+// Set while the server mutes or deafens us against what we asked for; all false once it lifts.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct SelfEnforcedEvent {
+    pub muted: bool,
+    pub deafened: bool,
+}
+
+// Server notices (welcome text, admin broadcasts) are styled apart from peer chat.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageKind {
+    #[default]
+    Chat,
+    System,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -114,9 +196,91 @@ pub struct MessageEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub channel_id: Option<String>,
     pub message: String,
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default)]
+    pub kind: MessageKind,
     pub timestamp_ms: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InputLevelEvent {
+    pub level: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PermissionDeniedEvent {
+    pub denial_type: String,
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+    pub timestamp_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecordingEvent {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NetworkEvent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rtt_ms: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub udp_rtt_ms: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp_rtt_ms: Option<f32>,
+    pub packet_loss_percent: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MicLevelEvent {
+    pub level: f32,
+    pub on_threshold: f32,
+    pub off_threshold: f32,
+    pub muted: bool,
+}
+
+// Level of the mixed playback, peak being the loudest sample since the last event.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OutputLevelEvent {
+    pub level: f32,
+    pub peak: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TalkingWhileMutedEvent {
+    pub level: f32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioWarningKind {
+    NoInputDevice,
+    NoOutputDevice,
+    SavedInputUnavailable,
+    SavedOutputUnavailable,
+    SavedHostUnavailable,
+    ResamplerFailed,
+    OneWayAudio,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AudioWarning {
+    pub kind: AudioWarningKind,
+    pub detail: String,
+}
+
+// An empty warning list means every audio device is up again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct AudioWarningEvent {
+    pub warnings: Vec<AudioWarning>,
+}
+
 fn emit<R: Runtime, T: Serialize>(
     app: &AppHandle<R>,
     event_name: &str,
@@ -143,6 +307,13 @@ pub fn emit_speaking<R: Runtime>(
     emit(app, EVENT_SPEAKING, payload)
 }
 
+pub fn emit_user_presence<R: Runtime>(
+    app: &AppHandle<R>,
+    payload: &UserPresenceEvent,
+) -> Result<(), String> {
+    emit(app, EVENT_USER_PRESENCE, payload)
+}
+
 pub fn emit_devices<R: Runtime>(app: &AppHandle<R>, payload: &DevicesEvent) -> Result<(), String> {
     emit(app, EVENT_DEVICES, payload)
 }
@@ -154,3 +325,67 @@ pub fn emit_self<R: Runtime>(app: &AppHandle<R>, payload: &SelfEvent) -> Result<
 pub fn emit_message<R: Runtime>(app: &AppHandle<R>, payload: &MessageEvent) -> Result<(), String> {
     emit(app, EVENT_MESSAGE, payload)
 }
+
+pub fn emit_input_level<R: Runtime>(
+    app: &AppHandle<R>,
+    payload: &InputLevelEvent,
+) -> Result<(), String> {
+    emit(app, EVENT_INPUT_LEVEL, payload)
+}
+
+pub fn emit_permission_denied<R: Runtime>(
+    app: &AppHandle<R>,
+    payload: &PermissionDeniedEvent,
+) -> Result<(), String> {
+    emit(app, EVENT_PERMISSION_DENIED, payload)
+}
+
+pub fn emit_recording<R: Runtime>(
+    app: &AppHandle<R>,
+    payload: &RecordingEvent,
+) -> Result<(), String> {
+    emit(app, EVENT_RECORDING, payload)
+}
+
+pub fn emit_network<R: Runtime>(app: &AppHandle<R>, payload: &NetworkEvent) -> Result<(), String> {
+    emit(app, EVENT_NETWORK, payload)
+}
+
+pub fn emit_audio_warning<R: Runtime>(
+    app: &AppHandle<R>,
+    payload: &AudioWarningEvent,
+) -> Result<(), String> {
+    emit(app, EVENT_AUDIO_WARNING, payload)
+}
+
+pub fn emit_talking_while_muted<R: Runtime>(
+    app: &AppHandle<R>,
+    payload: &TalkingWhileMutedEvent,
+) -> Result<(), String> {
+    emit(app, EVENT_TALKING_WHILE_MUTED, payload)
+}
+
+pub fn emit_self_enforced<R: Runtime>(
+    app: &AppHandle<R>,
+    payload: &SelfEnforcedEvent,
+) -> Result<(), String> {
+    emit(app, EVENT_SELF_ENFORCED, payload)
+}
+
+pub fn emit_config<R: Runtime>(app: &AppHandle<R>, payload: &AppConfig) -> Result<(), String> {
+    emit(app, EVENT_CONFIG, payload)
+}
+
+pub fn emit_mic_level<R: Runtime>(
+    app: &AppHandle<R>,
+    payload: &MicLevelEvent,
+) -> Result<(), String> {
+    emit(app, EVENT_MIC_LEVEL, payload)
+}
+
+pub fn emit_output_level<R: Runtime>(
+    app: &AppHandle<R>,
+    payload: &OutputLevelEvent,
+) -> Result<(), String> {
+    emit(app, EVENT_OUTPUT_LEVEL, payload)
+}
@@ -1,25 +1,47 @@
+pub mod badges;
 pub mod config;
+pub mod config_watcher;
+pub mod debug_server;
 pub mod events;
 pub mod soundboard;
 pub mod voice;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, VecDeque};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, State};
 use tokio::sync::{Mutex, RwLock};
 
-use config::{load_config, save_config_to_path, AppConfig};
+use badges::normalize_badge_codes;
+use config::{
+    export_config_json, keep_redacted_secrets, load_config, parse_config_json, reload_config,
+    save_config_to_path, AppConfig, CueConfig, PttMode, SoundboardConfig, VoiceQualityConfig,
+    CUE_NONE,
+};
 use events::{
-    emit_connection, emit_devices, emit_roster, emit_self, ConnectionEvent, ConnectionState,
-    DevicesEvent, SelfEvent,
+    emit_config, emit_connection, emit_devices, emit_recording, emit_roster, emit_self,
+    ConnectionEvent, ConnectionState, DevicesEvent, MessageEvent, RecordingEvent, SelfEvent,
+    ServerInfo,
 };
-use soundboard::{SoundboardClip, SoundboardStore};
+use soundboard::{SoundboardClip, SoundboardStore, SoundboardUsage};
+use voice::audio_out::{OUTPUT_BUFFER_MIN_MS, OUTPUT_PREFILL_MIN_MS};
+use voice::codec::CodecCapabilities;
 use voice::hotkeys::Hotkey;
+use voice::loss_injection::{LossInjection, LOSS_INJECTION_MAX_PERCENT};
+use voice::recorder::MicSampleInfo;
+use voice::vad::{
+    VadSettings, VAD_ATTACK_FRAMES_MAX, VAD_HOLD_FRAMES_MAX, VAD_SMOOTHING_MAX, VAD_THRESHOLD_MAX,
+    VAD_THRESHOLD_MIN,
+};
 use voice::{
-    list_input_devices, list_output_devices, AudioQualityMetrics, VoiceService, VoiceSharedState,
+    canonical_audio_host_id, list_input_devices, list_output_devices, AudioDevice, AudioHost,
+    AudioQualityMetrics, CueClips, InputMonitor, SpeakerStats, VoiceService, VoiceSharedState,
+    VoiceTransport, OPUS_BITRATE_MAX_BPS, OPUS_BITRATE_MIN_BPS, OPUS_COMPLEXITY_MAX,
+    OPUS_COMPLEXITY_MIN, OPUS_PACKET_LOSS_MAX_PCT, RX_JITTER_MAX_MAX, RX_JITTER_MAX_MIN,
+    RX_JITTER_TARGET_MAX, RX_JITTER_TARGET_MIN, SOUNDBOARD_MIX_GAIN_MAX,
 };
 
 #[derive(Debug, Clone, Serialize)]
@@ -29,6 +51,19 @@ pub struct BootstrapState {
     pub roster: events::RosterEvent,
     pub devices: DevicesEvent,
     pub self_state: SelfEvent,
+    pub message_history: Vec<MessageEvent>,
+}
+
+// Network fields stay empty unless a connection is live.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionSummary {
+    pub state: ConnectionState,
+    pub server_host: String,
+    pub channel_name: String,
+    pub user_count: usize,
+    pub rtt_ms: Option<f32>,
+    pub transport: Option<VoiceTransport>,
+    pub packet_loss_percent: f32,
 }
 
 pub struct AppCore {
@@ -39,14 +74,17 @@ pub struct AppCore {
     pub roster: Arc<RwLock<events::RosterEvent>>,
     pub devices: Arc<RwLock<DevicesEvent>>,
     pub self_state: Arc<RwLock<SelfEvent>>,
+    pub message_history: Arc<RwLock<VecDeque<MessageEvent>>>,
+    pub server_info: Arc<RwLock<ServerInfo>>,
     pub voice: Mutex<VoiceService>,
+    pub input_monitor: Mutex<InputMonitor>,
     pub soundboard: Mutex<SoundboardStore>,
 }
 
 impl AppCore {
     pub fn new() -> Result<Self, String> {
         let loaded = load_config().map_err(|err| err.to_string())?;
-        let devices = read_devices_event();
+        let devices = read_devices_event(loaded.config.audio_host.as_deref());
         let roster = events::RosterEvent {
             channel: events::ChannelInfo {
                 id: "0".to_string(),
@@ -58,8 +96,11 @@ impl AppCore {
         let self_state = SelfEvent {
             muted: false,
             deafened: false,
-            ptt_enabled: loaded.config.ptt_enabled,
+            ptt_enabled: loaded.config.ptt_mode == PttMode::PushToTalk,
+            ptt_mode: loaded.config.ptt_mode,
             transmitting: false,
+            listen_only: false,
+            all_users_muted: false,
         };
 
         Ok(Self {
@@ -70,7 +111,10 @@ impl AppCore {
             roster: Arc::new(RwLock::new(roster)),
             devices: Arc::new(RwLock::new(devices)),
             self_state: Arc::new(RwLock::new(self_state)),
+            message_history: Arc::new(RwLock::new(VecDeque::new())),
+            server_info: Arc::new(RwLock::new(ServerInfo::default())),
             voice: Mutex::new(VoiceService::new()),
+            input_monitor: Mutex::new(InputMonitor::new()),
             soundboard: Mutex::new(SoundboardStore::load()?),
         })
     }
@@ -82,9 +126,22 @@ impl AppCore {
             roster: self.roster.read().await.clone(),
             devices: self.devices.read().await.clone(),
             self_state: self.self_state.read().await.clone(),
+            message_history: self.message_history().await,
+        }
+    }
+
+    pub async fn debug_sources(&self) -> debug_server::DebugSources {
+        debug_server::DebugSources {
+            connection: Arc::clone(&self.connection),
+            roster: Arc::clone(&self.roster),
+            quality_metrics: self.voice.lock().await.quality_metrics_handle(),
         }
     }
 
+    pub async fn message_history(&self) -> Vec<MessageEvent> {
+        self.message_history.read().await.iter().cloned().collect()
+    }
+
     pub async fn emit_initial_events(&self, app: &AppHandle) -> Result<(), String> {
         let connection = self.connection.read().await.clone();
         let roster = self.roster.read().await.clone();
@@ -98,6 +155,17 @@ impl AppCore {
         Ok(())
     }
 
+    async fn soundboard_limit_bytes(&self) -> u64 {
+        u64::from(self.config.read().await.soundboard_storage_limit_mb) * 1024 * 1024
+    }
+
+    // Usage is bookkeeping, so a failed write is logged rather than surfaced.
+    pub async fn flush_soundboard_usage(&self) {
+        if let Err(err) = self.soundboard.lock().await.flush_usage() {
+            log::warn!("failed to persist soundboard usage: {err}");
+        }
+    }
+
     async fn persist_config(&self) -> Result<(), String> {
         if self.config_is_dev_override {
             return Ok(());
@@ -106,40 +174,216 @@ impl AppCore {
         save_config_to_path(&self.config_path, &snapshot).map_err(|err| err.to_string())
     }
 
+    // Dev override files are shared fixtures, so only the persistent config is watched.
+    pub fn config_watch_path(&self) -> Option<PathBuf> {
+        (!self.config_is_dev_override).then(|| self.config_path.clone())
+    }
+
+    pub async fn reload_config_from_disk(&self, app: &AppHandle) -> Result<(), String> {
+        let mut next = reload_config(&self.config_path).map_err(|err| err.to_string())?;
+        let current = self.config.read().await.clone();
+        // Our own saves land here too; they match what is already in memory.
+        if next == current {
+            return Ok(());
+        }
+        log::info!("config file changed on disk; applying");
+
+        match normalize_voice_quality(next.voice_quality.clone()) {
+            Ok(quality) => next.voice_quality = quality,
+            Err(err) => {
+                log::warn!("ignoring edited voice quality: {err}");
+                next.voice_quality = current.voice_quality.clone();
+            }
+        }
+        let connected = self.connection.read().await.state != ConnectionState::Disconnected;
+        let server_changed = next.server.host != current.server.host
+            || next.server.port != current.server.port
+            || next.server.password != current.server.password;
+        if connected && server_changed {
+            log::info!("server endpoint changed in config; it will apply on the next connect");
+        }
+
+        *self.config.write().await = next.clone();
+        self.apply_config_change(app, &current, &next).await?;
+
+        emit_config(app, &next)?;
+        self.emit_initial_events(app).await
+    }
+
+    // Pushes whatever differs between two configs into the running monitor and voice worker.
+    async fn apply_config_change(
+        &self,
+        app: &AppHandle,
+        current: &AppConfig,
+        next: &AppConfig,
+    ) -> Result<(), String> {
+        if next.ptt_mode != current.ptt_mode {
+            let mut self_state = self.self_state.write().await;
+            self_state.ptt_enabled = next.ptt_mode == PttMode::PushToTalk;
+            self_state.ptt_mode = next.ptt_mode;
+        }
+        if next.input_device != current.input_device || next.audio_host != current.audio_host {
+            let mut monitor = self.input_monitor.lock().await;
+            if monitor.is_running() {
+                monitor
+                    .start(
+                        app.clone(),
+                        next.input_device.clone(),
+                        next.voice_quality.prefer_48k,
+                        next.audio_host.clone(),
+                    )
+                    .await?;
+            }
+        }
+        {
+            let voice = self.voice.lock().await;
+            if next.ptt_mode != current.ptt_mode {
+                voice.set_ptt_mode(next.ptt_mode);
+            }
+            if next.audio_host != current.audio_host {
+                voice.set_audio_host(next.audio_host.clone());
+            }
+            if let Some(device_id) = next
+                .input_device
+                .clone()
+                .filter(|_| next.input_device != current.input_device)
+            {
+                voice.set_input_device(device_id);
+            }
+            if let Some(device_id) = next
+                .output_device
+                .clone()
+                .filter(|_| next.output_device != current.output_device)
+            {
+                voice.set_output_device(device_id);
+            }
+            if next.voice_quality != current.voice_quality {
+                voice.apply_voice_quality(next.voice_quality.clone());
+            }
+            if next.soundboard != current.soundboard {
+                voice.set_soundboard_mix(next.soundboard);
+            }
+        }
+        Ok(())
+    }
+
     fn voice_shared_state(&self) -> VoiceSharedState {
         VoiceSharedState {
             connection: Arc::clone(&self.connection),
             roster: Arc::clone(&self.roster),
             self_state: Arc::clone(&self.self_state),
+            message_history: Arc::clone(&self.message_history),
+            server_info: Arc::clone(&self.server_info),
         }
     }
 
     async fn refresh_devices(&self, app: &AppHandle) -> Result<DevicesEvent, String> {
-        let refreshed = read_devices_event();
+        let audio_host = self.config.read().await.audio_host.clone();
+        let refreshed = read_devices_event(audio_host.as_deref());
         {
             let mut devices = self.devices.write().await;
             *devices = refreshed.clone();
         }
         emit_devices(app, &refreshed)?;
+        // A newly plugged-in device may fill in for one that failed at connect time.
+        self.voice.lock().await.retry_audio();
         Ok(refreshed)
     }
+
+    async fn roster_session(&self, user_id: &str) -> Result<u32, String> {
+        let session = user_id
+            .parse::<u32>()
+            .map_err(|_| "invalid user id".to_string())?;
+        let known_user = self
+            .roster
+            .read()
+            .await
+            .users
+            .iter()
+            .any(|user| user.id == session.to_string());
+        if !known_user {
+            return Err("user is not in the roster".to_string());
+        }
+        Ok(session)
+    }
+
+    async fn apply_mute(&self, app: &AppHandle, muted: bool) -> Result<(), String> {
+        let next = {
+            let mut self_state = self.self_state.write().await;
+            if self_state.listen_only && !muted {
+                return Err("cannot unmute a listen-only connection".to_string());
+            }
+            self_state.muted = muted;
+            self_state.clone()
+        };
+        emit_self(app, &next)?;
+
+        let voice = self.voice.lock().await;
+        voice.set_mute(muted);
+        Ok(())
+    }
+
+    async fn apply_ptt_mode(&self, app: &AppHandle, mode: PttMode) -> Result<(), String> {
+        {
+            let mut config = self.config.write().await;
+            config.ptt_mode = mode;
+        }
+        self.persist_config().await?;
+
+        let next = {
+            let mut self_state = self.self_state.write().await;
+            self_state.ptt_enabled = mode == PttMode::PushToTalk;
+            self_state.ptt_mode = mode;
+            self_state.clone()
+        };
+        emit_self(app, &next)?;
+
+        let voice = self.voice.lock().await;
+        voice.set_ptt_mode(mode);
+        Ok(())
+    }
+
+    async fn apply_deafen(&self, app: &AppHandle, deafened: bool) -> Result<(), String> {
+        let auto_mute = self.config.read().await.auto_mute_on_deafen;
+
+        let next = {
+            let mut self_state = self.self_state.write().await;
+            self_state.deafened = deafened;
+            if auto_mute && deafened {
+                self_state.muted = true;
+            }
+            self_state.clone()
+        };
+        emit_self(app, &next)?;
+
+        let voice = self.voice.lock().await;
+        voice.set_deafen(deafened);
+        if auto_mute && deafened {
+            voice.set_mute(true);
+        }
+        Ok(())
+    }
+}
+
+fn device_info(device: AudioDevice) -> events::DeviceInfo {
+    events::DeviceInfo {
+        id: device.id,
+        name: device.name,
+        default_sample_rate: device.default_sample_rate,
+        channels: device.channels,
+        sample_format: device.sample_format,
+    }
 }
 
-fn read_devices_event() -> DevicesEvent {
+fn read_devices_event(audio_host: Option<&str>) -> DevicesEvent {
     DevicesEvent {
-        inputs: list_input_devices()
+        inputs: list_input_devices(audio_host)
             .into_iter()
-            .map(|device| events::DeviceInfo {
-                id: device.id,
-                name: device.name,
-            })
+            .map(device_info)
             .collect(),
-        outputs: list_output_devices()
+        outputs: list_output_devices(audio_host)
             .into_iter()
-            .map(|device| events::DeviceInfo {
-                id: device.id,
-                name: device.name,
-            })
+            .map(device_info)
             .collect(),
     }
 }
@@ -149,6 +393,15 @@ pub struct ConnectArgs {
     nickname: String,
     #[serde(default)]
     badge_codes: Vec<String>,
+    #[serde(default)]
+    listen_only: bool,
+    // False joins under this nickname once without saving it or its badges.
+    #[serde(default = "default_remember_nickname")]
+    remember: bool,
+}
+
+const fn default_remember_nickname() -> bool {
+    true
 }
 
 #[derive(Debug, Deserialize)]
@@ -161,16 +414,98 @@ pub struct SetDeafenArgs {
     deafened: bool,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetAllUsersMutedArgs {
+    muted: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SetPttArgs {
     enabled: bool,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetPttModeArgs {
+    mode: PttMode,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetPttKeyHeldArgs {
+    held: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SetPttHotkeyArgs {
     hotkey: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetToggleHotkeyArgs {
+    #[serde(default)]
+    hotkey: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HotkeyPressedArgs {
+    hotkey: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetJitterBufferArgs {
+    target_frames: usize,
+    max_frames: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetAdaptiveEnabledArgs {
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetOpusComplexityArgs {
+    complexity: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetLossInjectionArgs {
+    loss_percent: f32,
+    #[serde(default)]
+    reorder_percent: f32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetSoundboardMixArgs {
+    #[serde(default)]
+    mix_gain: Option<f32>,
+    #[serde(default)]
+    duck_voice: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetVadArgs {
+    threshold: f32,
+    #[serde(default)]
+    off_threshold: Option<f32>,
+    hold_frames: u32,
+    // Omitted fields keep the saved attack and smoothing.
+    #[serde(default)]
+    attack_frames: Option<u32>,
+    #[serde(default)]
+    smoothing: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetVoiceQualityArgs {
+    voice_quality: VoiceQualityConfig,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetSelfPositionArgs {
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SetInputDeviceArgs {
     device_id: String,
@@ -181,15 +516,86 @@ pub struct SetOutputDeviceArgs {
     device_id: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetAudioHostArgs {
+    // None returns to the platform default host.
+    #[serde(default)]
+    host_id: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SetServerEndpointArgs {
     host: String,
     port: u16,
+    #[serde(default)]
+    use_srv: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct SendMessageArgs {
     message: String,
+    #[serde(default)]
+    channel_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SendPrivateMessageArgs {
+    user_id: String,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RemoveUserArgs {
+    user_id: String,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartRecordingArgs {
+    #[serde(default)]
+    include_self: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecordMicSampleArgs {
+    seconds: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddAccessTokenArgs {
+    token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JoinChannelWithPasswordArgs {
+    channel_id: String,
+    password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelListenerArgs {
+    channel_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateChannelArgs {
+    name: String,
+    #[serde(default)]
+    parent_id: Option<String>,
+    #[serde(default)]
+    temporary: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportConfigArgs {
+    #[serde(default)]
+    redact_secrets: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportConfigArgs {
+    json: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -204,6 +610,12 @@ pub struct DeleteSoundboardClipArgs {
     clip_id: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetSoundboardClipCategoryArgs {
+    clip_id: String,
+    category: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PlaySoundboardClipArgs {
     clip_id: String,
@@ -224,54 +636,69 @@ pub async fn connect(
     if nickname.is_empty() {
         return Err("nickname is required".to_string());
     }
-    let badge_codes = normalize_badge_codes(args.badge_codes);
+    let max_badge_codes = state.config.read().await.max_badge_codes_per_user;
+    let badge_codes = normalize_badge_codes(args.badge_codes, max_badge_codes);
+
+    let config_snapshot = if args.remember {
+        {
+            let mut config = state.config.write().await;
+            config.nickname = nickname.clone();
+            config.badge_profiles.insert(nickname, badge_codes);
+        }
+        state.persist_config().await?;
+        state.config.read().await.clone()
+    } else {
+        let mut snapshot = state.config.read().await.clone();
+        snapshot.nickname = nickname.clone();
+        snapshot.badge_profiles.insert(nickname, badge_codes);
+        snapshot
+    };
+    state.input_monitor.lock().await.stop().await;
 
+    // The worker reads self state when it builds the media runtime, so set it before connecting.
     {
-        let mut config = state.config.write().await;
-        config.nickname = nickname.clone();
-        config.badge_profiles.insert(nickname, badge_codes);
+        let mut self_state = state.self_state.write().await;
+        self_state.listen_only = args.listen_only;
+        self_state.deafened = config_snapshot.start_deafened;
+        self_state.muted = args.listen_only
+            || config_snapshot.start_muted
+            || (config_snapshot.start_deafened && config_snapshot.auto_mute_on_deafen);
     }
-    state.persist_config().await?;
-
-    let config_snapshot = state.config.read().await.clone();
     let shared = state.voice_shared_state();
+    let cues = {
+        let soundboard = state.soundboard.lock().await;
+        resolve_cue_clips(&soundboard, &config_snapshot.cues)
+    };
     {
         let mut voice = state.voice.lock().await;
-        voice.connect(app.clone(), config_snapshot, shared).await?;
+        voice
+            .connect(app.clone(), config_snapshot, shared, cues)
+            .await?;
     }
     state.emit_initial_events(&app).await?;
     Ok(())
 }
 
-const MAX_BADGE_CODES_PER_USER: usize = 5;
-const MAX_BADGE_CODE_LEN: usize = 32;
-
-fn normalize_badge_codes(raw_codes: Vec<String>) -> Vec<String> {
-    let mut normalized = Vec::new();
-    let mut seen = HashSet::new();
-
-    for raw in raw_codes {
-        let code = raw.trim().to_ascii_lowercase();
-        if code.is_empty() || code.len() > MAX_BADGE_CODE_LEN {
-            continue;
-        }
-        if !code.bytes().all(|value| {
-            value.is_ascii_lowercase() || value.is_ascii_digit() || value == b'-' || value == b'_'
-        }) {
-            continue;
+fn resolve_cue_clips(soundboard: &SoundboardStore, cues: &CueConfig) -> CueClips {
+    let resolve = |clip_id: &str| {
+        if clip_id == CUE_NONE {
+            return None;
         }
-        if !seen.insert(code.clone()) {
-            continue;
-        }
-        normalized.push(code);
-        if normalized.len() >= MAX_BADGE_CODES_PER_USER {
-            break;
+        let samples = soundboard.samples_for_clip(clip_id);
+        if samples.is_none() {
+            log::warn!("cue clip not found: {clip_id}");
         }
+        samples.map(Arc::from)
+    };
+    CueClips {
+        join: resolve(&cues.join),
+        leave: resolve(&cues.leave),
+        mute: resolve(&cues.mute),
     }
-
-    normalized
 }
 
+const MAX_CHANNEL_NAME_LEN: usize = 64;
+
 #[tauri::command]
 pub async fn disconnect(app: AppHandle, state: State<'_, AppCore>) -> Result<(), String> {
     {
@@ -282,6 +709,7 @@ pub async fn disconnect(app: AppHandle, state: State<'_, AppCore>) -> Result<(),
     let disconnected = ConnectionEvent {
         state: ConnectionState::Disconnected,
         reason: None,
+        phase: None,
     };
     {
         let mut connection = state.connection.write().await;
@@ -297,16 +725,7 @@ pub async fn set_mute(
     state: State<'_, AppCore>,
     args: SetMuteArgs,
 ) -> Result<(), String> {
-    let next = {
-        let mut self_state = state.self_state.write().await;
-        self_state.muted = args.muted;
-        self_state.clone()
-    };
-    emit_self(&app, &next)?;
-
-    let voice = state.voice.lock().await;
-    voice.set_mute(args.muted);
-    Ok(())
+    state.apply_mute(&app, args.muted).await
 }
 
 #[tauri::command]
@@ -315,23 +734,25 @@ pub async fn set_deafen(
     state: State<'_, AppCore>,
     args: SetDeafenArgs,
 ) -> Result<(), String> {
-    let auto_mute = state.config.read().await.auto_mute_on_deafen;
+    state.apply_deafen(&app, args.deafened).await
+}
 
+// Local only: mutes every other roster user, including anyone who joins while it is on.
+#[tauri::command]
+pub async fn set_all_users_muted(
+    app: AppHandle,
+    state: State<'_, AppCore>,
+    args: SetAllUsersMutedArgs,
+) -> Result<(), String> {
     let next = {
         let mut self_state = state.self_state.write().await;
-        self_state.deafened = args.deafened;
-        if auto_mute && args.deafened {
-            self_state.muted = true;
-        }
+        self_state.all_users_muted = args.muted;
         self_state.clone()
     };
     emit_self(&app, &next)?;
 
     let voice = state.voice.lock().await;
-    voice.set_deafen(args.deafened);
-    if auto_mute && args.deafened {
-        voice.set_mute(true);
-    }
+    voice.set_all_users_muted(args.muted);
     Ok(())
 }
 
@@ -341,21 +762,31 @@ pub async fn set_ptt(
     state: State<'_, AppCore>,
     args: SetPttArgs,
 ) -> Result<(), String> {
-    {
-        let mut config = state.config.write().await;
-        config.ptt_enabled = args.enabled;
-    }
-    state.persist_config().await?;
-
-    let next = {
-        let mut self_state = state.self_state.write().await;
-        self_state.ptt_enabled = args.enabled;
-        self_state.clone()
+    let mode = if args.enabled {
+        PttMode::PushToTalk
+    } else {
+        PttMode::Vad
     };
-    emit_self(&app, &next)?;
+    state.apply_ptt_mode(&app, mode).await
+}
+
+#[tauri::command]
+pub async fn set_ptt_mode(
+    app: AppHandle,
+    state: State<'_, AppCore>,
+    args: SetPttModeArgs,
+) -> Result<(), String> {
+    state.apply_ptt_mode(&app, args.mode).await
+}
 
+// Reported by the hotkey listener while the PTT key is pressed; only gates push-to-mute.
+#[tauri::command]
+pub async fn set_ptt_key_held(
+    state: State<'_, AppCore>,
+    args: SetPttKeyHeldArgs,
+) -> Result<(), String> {
     let voice = state.voice.lock().await;
-    voice.set_ptt(args.enabled);
+    voice.set_ptt_key_held(args.held);
     Ok(())
 }
 
@@ -371,6 +802,17 @@ pub async fn set_ptt_hotkey(
 
     {
         let mut config = state.config.write().await;
+        let toggles = [
+            config.mute_hotkey.as_deref(),
+            config.deafen_hotkey.as_deref(),
+        ];
+        if toggles
+            .into_iter()
+            .flatten()
+            .any(|toggle| hotkeys_collide(toggle, &parsed_hotkey.0))
+        {
+            return Err("push-to-talk hotkey is already bound to a toggle".to_string());
+        }
         config.ptt_hotkey = parsed_hotkey.0.clone();
     }
     state.persist_config().await?;
@@ -381,63 +823,475 @@ pub async fn set_ptt_hotkey(
 }
 
 #[tauri::command]
-pub async fn set_input_device(
-    _app: AppHandle,
+pub async fn set_mute_hotkey(
     state: State<'_, AppCore>,
-    args: SetInputDeviceArgs,
+    args: SetToggleHotkeyArgs,
 ) -> Result<(), String> {
+    let parsed_hotkey = parse_toggle_hotkey(args.hotkey.as_deref())?;
+
     {
         let mut config = state.config.write().await;
-        config.input_device = Some(args.device_id.clone());
+        if let Some(hotkey) = &parsed_hotkey {
+            validate_toggle_hotkey(
+                &hotkey.0,
+                &config.ptt_hotkey,
+                config.deafen_hotkey.as_deref(),
+            )?;
+        }
+        config.mute_hotkey = parsed_hotkey.map(|hotkey| hotkey.0);
     }
-    state.persist_config().await?;
-
-    let voice = state.voice.lock().await;
-    voice.set_input_device(args.device_id);
-    Ok(())
+    state.persist_config().await
 }
 
 #[tauri::command]
-pub async fn set_output_device(
-    _app: AppHandle,
+pub async fn set_deafen_hotkey(
     state: State<'_, AppCore>,
-    args: SetOutputDeviceArgs,
+    args: SetToggleHotkeyArgs,
 ) -> Result<(), String> {
+    let parsed_hotkey = parse_toggle_hotkey(args.hotkey.as_deref())?;
+
     {
         let mut config = state.config.write().await;
-        config.output_device = Some(args.device_id.clone());
+        if let Some(hotkey) = &parsed_hotkey {
+            validate_toggle_hotkey(&hotkey.0, &config.ptt_hotkey, config.mute_hotkey.as_deref())?;
+        }
+        config.deafen_hotkey = parsed_hotkey.map(|hotkey| hotkey.0);
     }
-    state.persist_config().await?;
-
-    let voice = state.voice.lock().await;
-    voice.set_output_device(args.device_id);
-    Ok(())
+    state.persist_config().await
 }
 
+// Called by the frontend key listener; toggles mute/deafen when the key is bound.
 #[tauri::command]
-pub async fn set_server_endpoint(
-    _app: AppHandle,
+pub async fn hotkey_pressed(
+    app: AppHandle,
     state: State<'_, AppCore>,
-    args: SetServerEndpointArgs,
+    args: HotkeyPressedArgs,
 ) -> Result<(), String> {
-    let host = args.host.trim().to_string();
-    if host.is_empty() {
-        return Err("server host cannot be empty".to_string());
-    }
-    if args.port == 0 {
-        return Err("server port must be greater than 0".to_string());
-    }
+    let Some(pressed) = Hotkey::parse(&args.hotkey) else {
+        return Ok(());
+    };
 
-    {
-        let mut config = state.config.write().await;
-        config.server.host = host;
-        config.server.port = args.port;
+    let (mute_hotkey, deafen_hotkey) = {
+        let config = state.config.read().await;
+        (config.mute_hotkey.clone(), config.deafen_hotkey.clone())
+    };
+    let current = state.self_state.read().await.clone();
+
+    if mute_hotkey.is_some_and(|hotkey| hotkeys_collide(&hotkey, &pressed.0)) {
+        return state.apply_mute(&app, !current.muted).await;
+    }
+    if deafen_hotkey.is_some_and(|hotkey| hotkeys_collide(&hotkey, &pressed.0)) {
+        return state.apply_deafen(&app, !current.deafened).await;
     }
-    state.persist_config().await?;
     Ok(())
 }
 
-#[tauri::command]
+fn hotkeys_collide(left: &str, right: &str) -> bool {
+    left.eq_ignore_ascii_case(right)
+}
+
+// None clears the binding; a string that does not parse is an error rather than a clear.
+fn parse_toggle_hotkey(raw: Option<&str>) -> Result<Option<Hotkey>, String> {
+    raw.map(|raw| Hotkey::parse(raw).ok_or_else(|| "hotkey cannot be empty".to_string()))
+        .transpose()
+}
+
+fn validate_toggle_hotkey(
+    hotkey: &str,
+    ptt_hotkey: &str,
+    other_toggle: Option<&str>,
+) -> Result<(), String> {
+    if hotkeys_collide(hotkey, ptt_hotkey) {
+        return Err("toggle hotkey cannot match the push-to-talk hotkey".to_string());
+    }
+    if other_toggle.is_some_and(|other| hotkeys_collide(hotkey, other)) {
+        return Err("mute and deafen hotkeys must be different".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_jitter_buffer(
+    state: State<'_, AppCore>,
+    args: SetJitterBufferArgs,
+) -> Result<(), String> {
+    validate_jitter_frames(args.target_frames, args.max_frames)?;
+
+    {
+        let mut config = state.config.write().await;
+        config.voice_quality.jitter_target_frames = args.target_frames;
+        config.voice_quality.jitter_max_frames = args.max_frames;
+        // A lower max would otherwise leave a saved prefill that normalize rejects on import.
+        let quality = &mut config.voice_quality;
+        quality.initial_prefill_frames = quality
+            .initial_prefill_frames
+            .map(|frames| frames.min(args.max_frames));
+    }
+    state.persist_config().await?;
+
+    let voice = state.voice.lock().await;
+    voice.set_jitter_buffer(args.target_frames, args.max_frames);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_adaptive_enabled(
+    state: State<'_, AppCore>,
+    args: SetAdaptiveEnabledArgs,
+) -> Result<(), String> {
+    {
+        let mut config = state.config.write().await;
+        config.voice_quality.adaptive_enabled = args.enabled;
+    }
+    state.persist_config().await?;
+
+    let voice = state.voice.lock().await;
+    voice.set_adaptive_enabled(args.enabled);
+    Ok(())
+}
+
+// Dev-only: needs a debug build running on a dev config. Zero for both rates turns it off.
+#[tauri::command]
+pub async fn set_loss_injection(
+    state: State<'_, AppCore>,
+    args: SetLossInjectionArgs,
+) -> Result<(), String> {
+    if !cfg!(debug_assertions) || !state.config_is_dev_override {
+        return Err("loss injection is only available in dev builds with a dev config".to_string());
+    }
+    if !args.loss_percent.is_finite() || !args.reorder_percent.is_finite() {
+        return Err("loss injection rates must be finite numbers".to_string());
+    }
+    let injection = LossInjection {
+        loss_percent: args.loss_percent.clamp(0.0, LOSS_INJECTION_MAX_PERCENT),
+        reorder_percent: args.reorder_percent.clamp(0.0, LOSS_INJECTION_MAX_PERCENT),
+    };
+
+    let voice = state.voice.lock().await;
+    voice.set_loss_injection(Some(injection));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_codec_capabilities() -> Result<CodecCapabilities, String> {
+    Ok(CodecCapabilities::default())
+}
+
+#[tauri::command]
+pub async fn get_voice_quality(state: State<'_, AppCore>) -> Result<VoiceQualityConfig, String> {
+    Ok(state.config.read().await.voice_quality.clone())
+}
+
+#[tauri::command]
+pub async fn set_voice_quality(
+    state: State<'_, AppCore>,
+    args: SetVoiceQualityArgs,
+) -> Result<VoiceQualityConfig, String> {
+    let quality = normalize_voice_quality(args.voice_quality)?;
+    {
+        let mut config = state.config.write().await;
+        config.voice_quality = quality.clone();
+    }
+    state.persist_config().await?;
+
+    let voice = state.voice.lock().await;
+    voice.apply_voice_quality(quality.clone());
+    Ok(quality)
+}
+
+// Same bounds the worker and config load clamp to, so out-of-range sizes are rejected here
+// instead of being saved and then quietly clamped.
+fn validate_jitter_frames(target_frames: usize, max_frames: usize) -> Result<(), String> {
+    if !(RX_JITTER_TARGET_MIN..=RX_JITTER_TARGET_MAX).contains(&target_frames) {
+        return Err(format!(
+            "jitter target frames must be between {RX_JITTER_TARGET_MIN} and {RX_JITTER_TARGET_MAX}"
+        ));
+    }
+    if !(RX_JITTER_MAX_MIN..=RX_JITTER_MAX_MAX).contains(&max_frames) {
+        return Err(format!(
+            "jitter max frames must be between {RX_JITTER_MAX_MIN} and {RX_JITTER_MAX_MAX}"
+        ));
+    }
+    // The worker bumps max to target + 2 when it is not above the target, so equality would
+    // be saved as one size and run as another.
+    if max_frames <= target_frames {
+        return Err("jitter max frames must be above the target".to_string());
+    }
+    Ok(())
+}
+
+// Rejects values the individual setters would reject; VAD values are clamped like set_vad.
+fn normalize_voice_quality(mut quality: VoiceQualityConfig) -> Result<VoiceQualityConfig, String> {
+    if !(OPUS_BITRATE_MIN_BPS..=OPUS_BITRATE_MAX_BPS).contains(&quality.opus_bitrate_bps) {
+        return Err(format!(
+            "opus bitrate must be between {OPUS_BITRATE_MIN_BPS} and {OPUS_BITRATE_MAX_BPS} bps"
+        ));
+    }
+    if !(0..=OPUS_PACKET_LOSS_MAX_PCT).contains(&quality.packet_loss_perc) {
+        return Err(format!(
+            "packet loss percent must be between 0 and {OPUS_PACKET_LOSS_MAX_PCT}"
+        ));
+    }
+    validate_jitter_frames(quality.jitter_target_frames, quality.jitter_max_frames)?;
+    if quality
+        .initial_prefill_frames
+        .is_some_and(|frames| frames == 0 || frames > quality.jitter_max_frames)
+    {
+        return Err(
+            "initial prefill frames must be between 1 and the jitter max frames".to_string(),
+        );
+    }
+    if !(OPUS_COMPLEXITY_MIN..=OPUS_COMPLEXITY_MAX).contains(&quality.opus_complexity) {
+        return Err(format!(
+            "opus complexity must be between {OPUS_COMPLEXITY_MIN} and {OPUS_COMPLEXITY_MAX}"
+        ));
+    }
+    if quality.output_buffer_ms < OUTPUT_BUFFER_MIN_MS {
+        return Err(format!(
+            "output buffer must be at least {OUTPUT_BUFFER_MIN_MS} ms"
+        ));
+    }
+    if quality.output_prefill_ms < OUTPUT_PREFILL_MIN_MS
+        || quality.output_prefill_ms > quality.output_buffer_ms / 2
+    {
+        return Err(format!(
+            "output prefill must be between {OUTPUT_PREFILL_MIN_MS} ms and half the output buffer"
+        ));
+    }
+    if !quality.vad_threshold.is_finite()
+        || quality
+            .vad_off_threshold
+            .is_some_and(|value| !value.is_finite())
+    {
+        return Err("vad threshold must be a finite number".to_string());
+    }
+    if !quality.vad_smoothing.is_finite() {
+        return Err("vad smoothing must be a finite number".to_string());
+    }
+    if !quality.priority_duck_gain.is_finite() {
+        return Err("priority duck gain must be a finite number".to_string());
+    }
+
+    quality.vad_threshold = quality
+        .vad_threshold
+        .clamp(VAD_THRESHOLD_MIN, VAD_THRESHOLD_MAX);
+    quality.vad_off_threshold = quality
+        .vad_off_threshold
+        .map(|value| value.clamp(VAD_THRESHOLD_MIN, quality.vad_threshold));
+    quality.vad_hold_frames = quality.vad_hold_frames.min(VAD_HOLD_FRAMES_MAX);
+    quality.vad_attack_frames = quality.vad_attack_frames.clamp(1, VAD_ATTACK_FRAMES_MAX);
+    quality.vad_smoothing = quality.vad_smoothing.clamp(0.0, VAD_SMOOTHING_MAX);
+    quality.priority_duck_gain = quality.priority_duck_gain.clamp(0.0, 1.0);
+    Ok(quality)
+}
+
+#[tauri::command]
+pub async fn set_self_position(
+    state: State<'_, AppCore>,
+    args: SetSelfPositionArgs,
+) -> Result<(), String> {
+    let position = [args.x, args.y, args.z];
+    if !position.iter().all(|value| value.is_finite()) {
+        return Err("position must be finite numbers".to_string());
+    }
+    let voice = state.voice.lock().await;
+    voice.set_self_position(position);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_opus_complexity(
+    state: State<'_, AppCore>,
+    args: SetOpusComplexityArgs,
+) -> Result<(), String> {
+    if !(OPUS_COMPLEXITY_MIN..=OPUS_COMPLEXITY_MAX).contains(&args.complexity) {
+        return Err(format!(
+            "opus complexity must be between {OPUS_COMPLEXITY_MIN} and {OPUS_COMPLEXITY_MAX}"
+        ));
+    }
+
+    {
+        let mut config = state.config.write().await;
+        config.voice_quality.opus_complexity = args.complexity;
+    }
+    state.persist_config().await?;
+
+    let voice = state.voice.lock().await;
+    voice.set_complexity(args.complexity);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_vad(state: State<'_, AppCore>, args: SetVadArgs) -> Result<(), String> {
+    if !args.threshold.is_finite() || args.off_threshold.is_some_and(|value| !value.is_finite()) {
+        return Err("vad threshold must be a finite number".to_string());
+    }
+    if args.smoothing.is_some_and(|value| !value.is_finite()) {
+        return Err("vad smoothing must be a finite number".to_string());
+    }
+    let threshold = args.threshold.clamp(VAD_THRESHOLD_MIN, VAD_THRESHOLD_MAX);
+    let off_threshold = args
+        .off_threshold
+        .map(|value| value.clamp(VAD_THRESHOLD_MIN, threshold));
+    let hold_frames = args.hold_frames.min(VAD_HOLD_FRAMES_MAX);
+
+    let settings = {
+        let mut config = state.config.write().await;
+        let quality = &mut config.voice_quality;
+        quality.vad_threshold = threshold;
+        quality.vad_off_threshold = off_threshold;
+        quality.vad_hold_frames = hold_frames;
+        if let Some(attack_frames) = args.attack_frames {
+            quality.vad_attack_frames = attack_frames.clamp(1, VAD_ATTACK_FRAMES_MAX);
+        }
+        if let Some(smoothing) = args.smoothing {
+            quality.vad_smoothing = smoothing.clamp(0.0, VAD_SMOOTHING_MAX);
+        }
+        VadSettings::new(quality)
+    };
+    state.persist_config().await?;
+
+    let voice = state.voice.lock().await;
+    voice.set_vad(settings);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_soundboard_mix(
+    state: State<'_, AppCore>,
+    args: SetSoundboardMixArgs,
+) -> Result<SoundboardConfig, String> {
+    if args.mix_gain.is_some_and(|value| !value.is_finite()) {
+        return Err("soundboard mix gain must be a finite number".to_string());
+    }
+
+    let mix = {
+        let mut config = state.config.write().await;
+        if let Some(mix_gain) = args.mix_gain {
+            config.soundboard.mix_gain = mix_gain.clamp(0.0, SOUNDBOARD_MIX_GAIN_MAX);
+        }
+        if let Some(duck_voice) = args.duck_voice {
+            config.soundboard.duck_voice = duck_voice;
+        }
+        config.soundboard
+    };
+    state.persist_config().await?;
+
+    let voice = state.voice.lock().await;
+    voice.set_soundboard_mix(mix);
+    Ok(mix)
+}
+
+#[tauri::command]
+pub async fn set_input_device(
+    app: AppHandle,
+    state: State<'_, AppCore>,
+    args: SetInputDeviceArgs,
+) -> Result<(), String> {
+    let (prefer_48k, audio_host) = {
+        let mut config = state.config.write().await;
+        config.input_device = Some(args.device_id.clone());
+        (config.voice_quality.prefer_48k, config.audio_host.clone())
+    };
+    state.persist_config().await?;
+
+    {
+        let mut monitor = state.input_monitor.lock().await;
+        if monitor.is_running() {
+            monitor
+                .start(app, Some(args.device_id.clone()), prefer_48k, audio_host)
+                .await?;
+        }
+    }
+
+    let voice = state.voice.lock().await;
+    voice.set_input_device(args.device_id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_output_device(
+    _app: AppHandle,
+    state: State<'_, AppCore>,
+    args: SetOutputDeviceArgs,
+) -> Result<(), String> {
+    {
+        let mut config = state.config.write().await;
+        config.output_device = Some(args.device_id.clone());
+    }
+    state.persist_config().await?;
+
+    let voice = state.voice.lock().await;
+    voice.set_output_device(args.device_id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_audio_hosts() -> Result<Vec<AudioHost>, String> {
+    Ok(voice::list_audio_hosts())
+}
+
+#[tauri::command]
+pub async fn set_audio_host(
+    app: AppHandle,
+    state: State<'_, AppCore>,
+    args: SetAudioHostArgs,
+) -> Result<DevicesEvent, String> {
+    let audio_host = match args.host_id.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(raw) => Some(
+            canonical_audio_host_id(raw)
+                .ok_or_else(|| format!("audio host \"{raw}\" is not available"))?,
+        ),
+    };
+
+    let (input_device, prefer_48k) = {
+        let mut config = state.config.write().await;
+        config.audio_host = audio_host.clone();
+        (config.input_device.clone(), config.voice_quality.prefer_48k)
+    };
+    state.persist_config().await?;
+
+    {
+        let mut monitor = state.input_monitor.lock().await;
+        if monitor.is_running() {
+            monitor
+                .start(app.clone(), input_device, prefer_48k, audio_host.clone())
+                .await?;
+        }
+    }
+
+    state.voice.lock().await.set_audio_host(audio_host);
+    state.refresh_devices(&app).await
+}
+
+#[tauri::command]
+pub async fn set_server_endpoint(
+    _app: AppHandle,
+    state: State<'_, AppCore>,
+    args: SetServerEndpointArgs,
+) -> Result<(), String> {
+    let host = args.host.trim().to_string();
+    if host.is_empty() {
+        return Err("server host cannot be empty".to_string());
+    }
+    if args.port == 0 {
+        return Err("server port must be greater than 0".to_string());
+    }
+
+    {
+        let mut config = state.config.write().await;
+        config.server.host = host;
+        config.server.port = args.port;
+        if let Some(use_srv) = args.use_srv {
+            config.server.use_srv = use_srv;
+        }
+    }
+    state.persist_config().await?;
+    Ok(())
+}
+
+#[tauri::command]
 pub async fn refresh_devices(
     app: AppHandle,
     state: State<'_, AppCore>,
@@ -453,6 +1307,78 @@ pub async fn get_audio_quality_metrics(
     Ok(voice.audio_quality_metrics())
 }
 
+// Session id to receive counters for everyone currently sending us voice.
+#[tauri::command]
+pub async fn get_per_speaker_stats(
+    state: State<'_, AppCore>,
+) -> Result<BTreeMap<u32, SpeakerStats>, String> {
+    let voice = state.voice.lock().await;
+    Ok(voice.per_speaker_stats())
+}
+
+#[tauri::command]
+pub async fn get_connection_summary(
+    state: State<'_, AppCore>,
+) -> Result<ConnectionSummary, String> {
+    let connection_state = state.connection.read().await.state;
+    let server_host = state.config.read().await.server.host.clone();
+    let (channel_name, user_count) = {
+        let roster = state.roster.read().await;
+        (roster.channel.name.clone(), roster.users.len())
+    };
+    let metrics = state.voice.lock().await.audio_quality_metrics();
+    let live = connection_state == ConnectionState::Connected && metrics.connected;
+
+    Ok(ConnectionSummary {
+        state: connection_state,
+        server_host,
+        channel_name,
+        user_count,
+        rtt_ms: metrics.rtt_ms.filter(|_| live),
+        transport: live.then_some(metrics.voice_transport),
+        packet_loss_percent: if live {
+            metrics.packet_loss_percent
+        } else {
+            0.0
+        },
+    })
+}
+
+#[tauri::command]
+pub async fn start_input_monitor(app: AppHandle, state: State<'_, AppCore>) -> Result<(), String> {
+    if state.connection.read().await.state != ConnectionState::Disconnected {
+        return Err("input monitor is only available while disconnected".to_string());
+    }
+
+    let (device_id, prefer_48k, audio_host) = {
+        let config = state.config.read().await;
+        (
+            config.input_device.clone(),
+            config.voice_quality.prefer_48k,
+            config.audio_host.clone(),
+        )
+    };
+    let mut monitor = state.input_monitor.lock().await;
+    monitor.start(app, device_id, prefer_48k, audio_host).await
+}
+
+#[tauri::command]
+pub async fn stop_input_monitor(state: State<'_, AppCore>) -> Result<(), String> {
+    let mut monitor = state.input_monitor.lock().await;
+    monitor.stop().await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_server_info(state: State<'_, AppCore>) -> Result<ServerInfo, String> {
+    Ok(state.server_info.read().await.clone())
+}
+
+#[tauri::command]
+pub async fn get_message_history(state: State<'_, AppCore>) -> Result<Vec<MessageEvent>, String> {
+    Ok(state.message_history().await)
+}
+
 #[tauri::command]
 pub async fn send_message(
     _app: AppHandle,
@@ -463,9 +1389,243 @@ pub async fn send_message(
     if message.is_empty() {
         return Err("message cannot be empty".to_string());
     }
+    let channel_id = args
+        .channel_id
+        .as_deref()
+        .map(|raw| {
+            raw.parse::<u32>()
+                .map_err(|_| "invalid channel id".to_string())
+        })
+        .transpose()?;
+
+    let mut voice = state.voice.lock().await;
+    voice.send_message(message, channel_id)
+}
+
+#[tauri::command]
+pub async fn send_private_message(
+    state: State<'_, AppCore>,
+    args: SendPrivateMessageArgs,
+) -> Result<(), String> {
+    let message = args.message.trim().to_string();
+    if message.is_empty() {
+        return Err("message cannot be empty".to_string());
+    }
+    let session = state.roster_session(&args.user_id).await?;
+
+    let mut voice = state.voice.lock().await;
+    voice.send_private_message(session, message)
+}
+
+#[tauri::command]
+pub async fn kick_user(state: State<'_, AppCore>, args: RemoveUserArgs) -> Result<(), String> {
+    let session = state.roster_session(&args.user_id).await?;
+    let voice = state.voice.lock().await;
+    voice.remove_user(session, normalize_reason(args.reason), false)
+}
+
+#[tauri::command]
+pub async fn ban_user(state: State<'_, AppCore>, args: RemoveUserArgs) -> Result<(), String> {
+    let session = state.roster_session(&args.user_id).await?;
+    let voice = state.voice.lock().await;
+    voice.remove_user(session, normalize_reason(args.reason), true)
+}
+
+fn normalize_reason(reason: Option<String>) -> Option<String> {
+    reason
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[tauri::command]
+pub async fn start_recording(
+    app: AppHandle,
+    state: State<'_, AppCore>,
+    args: StartRecordingArgs,
+) -> Result<String, String> {
+    let path = {
+        let voice = state.voice.lock().await;
+        voice.start_recording(app.clone(), args.include_self)?
+    };
+    emit_recording(
+        &app,
+        &RecordingEvent {
+            active: true,
+            path: Some(path.clone()),
+            reason: None,
+        },
+    )?;
+    Ok(path)
+}
+
+// Writes the raw microphone capture to a WAV for support; stops on its own.
+#[tauri::command]
+pub async fn record_mic_sample(
+    app: AppHandle,
+    state: State<'_, AppCore>,
+    args: RecordMicSampleArgs,
+) -> Result<MicSampleInfo, String> {
+    let voice = state.voice.lock().await;
+    voice.record_mic_sample(app, args.seconds)
+}
+
+#[tauri::command]
+pub async fn reconnect_now(state: State<'_, AppCore>) -> Result<(), String> {
+    if state.connection.read().await.state != ConnectionState::Reconnecting {
+        return Err("reconnect now is only available while reconnecting".to_string());
+    }
+    state.voice.lock().await.reconnect_now()
+}
+
+#[tauri::command]
+pub async fn stop_recording(state: State<'_, AppCore>) -> Result<(), String> {
+    let voice = state.voice.lock().await;
+    voice.stop_recording()
+}
+
+#[tauri::command]
+pub async fn join_channel_with_password(
+    state: State<'_, AppCore>,
+    args: JoinChannelWithPasswordArgs,
+) -> Result<(), String> {
+    let channel_id = args
+        .channel_id
+        .parse::<u32>()
+        .map_err(|_| "invalid channel id".to_string())?;
+    let password = args.password.trim().to_string();
+    if password.is_empty() {
+        return Err("channel password cannot be empty".to_string());
+    }
+
+    {
+        let mut config = state.config.write().await;
+        config
+            .server
+            .access_tokens
+            .insert(channel_id.to_string(), password.clone());
+    }
+    state.persist_config().await?;
+
+    let voice = state.voice.lock().await;
+    voice.join_channel(channel_id, Some(password))
+}
+
+// Hear a channel without leaving the current one; needs a Mumble 1.4+ server.
+#[tauri::command]
+pub async fn listen_to_channel(
+    state: State<'_, AppCore>,
+    args: ChannelListenerArgs,
+) -> Result<(), String> {
+    let channel_id = args
+        .channel_id
+        .parse::<u32>()
+        .map_err(|_| "invalid channel id".to_string())?;
+    let voice = state.voice.lock().await;
+    voice.set_channel_listener(channel_id, true)
+}
+
+#[tauri::command]
+pub async fn stop_listening_to_channel(
+    state: State<'_, AppCore>,
+    args: ChannelListenerArgs,
+) -> Result<(), String> {
+    let channel_id = args
+        .channel_id
+        .parse::<u32>()
+        .map_err(|_| "invalid channel id".to_string())?;
+    let voice = state.voice.lock().await;
+    voice.set_channel_listener(channel_id, false)
+}
+
+#[tauri::command]
+pub async fn add_access_token(
+    state: State<'_, AppCore>,
+    args: AddAccessTokenArgs,
+) -> Result<(), String> {
+    let token = args.token.trim().to_string();
+    if token.is_empty() {
+        return Err("access token cannot be empty".to_string());
+    }
+
+    {
+        let mut config = state.config.write().await;
+        if config.server.tokens.contains(&token) {
+            return Ok(());
+        }
+        config.server.tokens.push(token.clone());
+    }
+    state.persist_config().await?;
+
+    // A live connection resends Authenticate; otherwise the token goes out on the next connect.
+    let voice = state.voice.lock().await;
+    voice.add_access_token(token);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn create_channel(
+    state: State<'_, AppCore>,
+    args: CreateChannelArgs,
+) -> Result<(), String> {
+    let name = args.name.trim().to_string();
+    if name.is_empty() {
+        return Err("channel name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_CHANNEL_NAME_LEN {
+        return Err(format!(
+            "channel name cannot exceed {MAX_CHANNEL_NAME_LEN} characters"
+        ));
+    }
+    let parent_id = match args.parent_id.as_deref() {
+        Some(raw) => raw
+            .parse::<u32>()
+            .map_err(|_| "invalid parent channel id".to_string())?,
+        None => 0,
+    };
 
     let voice = state.voice.lock().await;
-    voice.send_message(message)
+    voice.create_channel(name, parent_id, args.temporary)
+}
+
+#[tauri::command]
+pub async fn export_config(
+    state: State<'_, AppCore>,
+    args: ExportConfigArgs,
+) -> Result<String, String> {
+    let snapshot = state.config.read().await.clone();
+    export_config_json(&snapshot, args.redact_secrets).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn import_config(
+    app: AppHandle,
+    state: State<'_, AppCore>,
+    args: ImportConfigArgs,
+) -> Result<AppConfig, String> {
+    let mut imported = parse_config_json(&args.json).map_err(|err| err.to_string())?;
+    imported.voice_quality = normalize_voice_quality(imported.voice_quality)?;
+    if let Some(hotkey) = imported.mute_hotkey.as_deref() {
+        validate_toggle_hotkey(
+            hotkey,
+            &imported.ptt_hotkey,
+            imported.deafen_hotkey.as_deref(),
+        )?;
+    }
+    if let Some(hotkey) = imported.deafen_hotkey.as_deref() {
+        validate_toggle_hotkey(hotkey, &imported.ptt_hotkey, None)?;
+    }
+
+    let current = {
+        let mut config = state.config.write().await;
+        keep_redacted_secrets(&mut imported, &config);
+        std::mem::replace(&mut *config, imported.clone())
+    };
+    state.persist_config().await?;
+
+    state.apply_config_change(&app, &current, &imported).await?;
+    emit_config(&app, &imported)?;
+    state.emit_initial_events(&app).await?;
+    Ok(imported)
 }
 
 #[tauri::command]
@@ -481,8 +1641,16 @@ pub async fn import_soundboard_clip(
     state: State<'_, AppCore>,
     args: ImportSoundboardClipArgs,
 ) -> Result<SoundboardClip, String> {
+    let limit_bytes = state.soundboard_limit_bytes().await;
     let mut soundboard = state.soundboard.lock().await;
-    soundboard.import_custom_clip(&args.label, &args.file_name, &args.bytes)
+    soundboard.import_custom_clip(&args.label, &args.file_name, &args.bytes, limit_bytes)
+}
+
+#[tauri::command]
+pub async fn soundboard_usage(state: State<'_, AppCore>) -> Result<SoundboardUsage, String> {
+    let limit_bytes = state.soundboard_limit_bytes().await;
+    let soundboard = state.soundboard.lock().await;
+    Ok(soundboard.usage(limit_bytes))
 }
 
 #[tauri::command]
@@ -494,8 +1662,24 @@ pub async fn delete_soundboard_clip(
     soundboard.delete_custom_clip(&args.clip_id)
 }
 
+#[tauri::command]
+pub async fn set_soundboard_clip_category(
+    state: State<'_, AppCore>,
+    args: SetSoundboardClipCategoryArgs,
+) -> Result<SoundboardClip, String> {
+    let mut soundboard = state.soundboard.lock().await;
+    soundboard.set_custom_clip_category(&args.clip_id, args.category.as_deref())
+}
+
+#[tauri::command]
+pub async fn list_soundboard_categories(state: State<'_, AppCore>) -> Result<Vec<String>, String> {
+    let soundboard = state.soundboard.lock().await;
+    Ok(soundboard.list_categories())
+}
+
 #[tauri::command]
 pub async fn play_soundboard_clip(
+    app: AppHandle,
     state: State<'_, AppCore>,
     args: PlaySoundboardClipArgs,
 ) -> Result<(), String> {
@@ -505,6 +1689,22 @@ pub async fn play_soundboard_clip(
             .samples_for_clip(&args.clip_id)
             .ok_or_else(|| "clip not found".to_string())?
     };
-    let voice = state.voice.lock().await;
-    voice.queue_soundboard_samples(samples_48k)
+    state
+        .voice
+        .lock()
+        .await
+        .queue_soundboard_samples(samples_48k)?;
+
+    let flush_delay = state
+        .soundboard
+        .lock()
+        .await
+        .record_play(&args.clip_id, Instant::now());
+    if let Some(delay) = flush_delay {
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(delay).await;
+            app.state::<AppCore>().flush_soundboard_usage().await;
+        });
+    }
+    Ok(())
 }
@@ -1,10 +1,11 @@
-use std::collections::HashMap;
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::f32::consts::PI;
 use std::fs;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 use symphonia::core::audio::SampleBuffer;
@@ -16,16 +17,25 @@ use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 use symphonia::default::{get_codecs, get_probe};
 
+use super::config::data_dir_override;
+
 const APP_DIR: &str = "Harmony";
 const SOUNDBOARD_DIR: &str = "soundboard";
 const CUSTOM_CLIPS_DIR: &str = "clips";
 const MANIFEST_FILE: &str = "manifest.json";
+// User-supplied .sb descriptors, synthesized like the built-in defaults.
+const DISK_DEFAULTS_DIR: &str = "defaults";
+const DESCRIPTOR_EXTENSION: &str = "sb";
+const MAX_DESCRIPTOR_BYTES: u64 = 4 * 1024;
 const OUTPUT_SAMPLE_RATE: u32 = 48_000;
 const MAX_IMPORT_BYTES: usize = 6 * 1024 * 1024;
 const MAX_CLIP_DURATION_MS: u32 = 8_000;
 const MAX_CLIP_SAMPLES: usize =
     ((OUTPUT_SAMPLE_RATE as u64 * MAX_CLIP_DURATION_MS as u64) / 1000) as usize;
 const MAX_LABEL_CHARS: usize = 36;
+const MAX_CATEGORY_CHARS: usize = 24;
+// Play counts are batched into one manifest write per interval instead of one per play.
+const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
 
 static CUSTOM_CLIP_COUNTER: AtomicU64 = AtomicU64::new(1);
 
@@ -36,12 +46,25 @@ pub enum SoundboardClipSource {
     Custom,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SoundboardUsage {
+    pub total_bytes: u64,
+    pub clip_count: usize,
+    pub limit_bytes: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SoundboardClip {
     pub id: String,
     pub label: String,
     pub source: SoundboardClipSource,
     pub duration_ms: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub play_count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_played_at_ms: Option<u64>,
 }
 
 struct StoredClip {
@@ -54,6 +77,16 @@ struct StoredClip {
 struct SoundboardManifest {
     #[serde(default)]
     custom_clips: Vec<ManifestCustomClip>,
+    // Keyed by clip id, so default clips keep their counts too.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    clip_usage: BTreeMap<String, ManifestClipUsage>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+struct ManifestClipUsage {
+    play_count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_played_at_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,6 +94,14 @@ struct ManifestCustomClip {
     id: String,
     label: String,
     file_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+}
+
+struct DiskDefault {
+    id: String,
+    label: String,
+    samples_48k: Vec<f32>,
 }
 
 struct DefaultAsset {
@@ -102,11 +143,17 @@ pub struct SoundboardStore {
     custom_dir: PathBuf,
     manifest_path: PathBuf,
     clips: HashMap<String, StoredClip>,
+    usage_dirty: bool,
+    usage_flush_scheduled: bool,
+    last_usage_flush: Instant,
 }
 
 impl SoundboardStore {
     pub fn load() -> Result<Self, String> {
-        let root_dir = resolve_soundboard_root()?;
+        Self::open(&resolve_soundboard_root()?)
+    }
+
+    fn open(root_dir: &Path) -> Result<Self, String> {
         let custom_dir = root_dir.join(CUSTOM_CLIPS_DIR);
         let manifest_path = root_dir.join(MANIFEST_FILE);
         fs::create_dir_all(&custom_dir)
@@ -116,9 +163,13 @@ impl SoundboardStore {
             custom_dir,
             manifest_path,
             clips: HashMap::new(),
+            usage_dirty: false,
+            usage_flush_scheduled: false,
+            last_usage_flush: Instant::now(),
         };
 
         store.load_default_clips()?;
+        store.load_disk_default_clips(&root_dir.join(DISK_DEFAULTS_DIR));
         store.load_custom_clips()?;
         Ok(store)
     }
@@ -129,23 +180,62 @@ impl SoundboardStore {
             .values()
             .map(|entry| entry.clip.clone())
             .collect::<Vec<_>>();
-        clips.sort_by(|left, right| match (&left.source, &right.source) {
-            (SoundboardClipSource::Default, SoundboardClipSource::Custom) => {
-                std::cmp::Ordering::Less
-            }
-            (SoundboardClipSource::Custom, SoundboardClipSource::Default) => {
-                std::cmp::Ordering::Greater
-            }
-            _ => left.label.to_lowercase().cmp(&right.label.to_lowercase()),
-        });
+        clips.sort_by(compare_clips);
         clips
     }
 
+    pub fn list_categories(&self) -> Vec<String> {
+        self.clips
+            .values()
+            .filter_map(|entry| entry.clip.category.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    pub fn set_custom_clip_category(
+        &mut self,
+        clip_id: &str,
+        category: Option<&str>,
+    ) -> Result<SoundboardClip, String> {
+        let Some(existing) = self.clips.get_mut(clip_id) else {
+            return Err("clip not found".to_string());
+        };
+        if existing.clip.source != SoundboardClipSource::Custom {
+            return Err("default clips cannot be categorized".to_string());
+        }
+        existing.clip.category = normalize_category(category);
+        let clip = existing.clip.clone();
+        self.persist_manifest()?;
+        Ok(clip)
+    }
+
+    // Usage counts the custom clip files on disk; default clips are synthesized in memory.
+    pub fn usage(&self, limit_bytes: u64) -> SoundboardUsage {
+        let files = self
+            .clips
+            .values()
+            .filter(|entry| entry.clip.source == SoundboardClipSource::Custom)
+            .filter_map(|entry| entry.file_path.as_ref())
+            .collect::<Vec<_>>();
+        let total_bytes = files
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        SoundboardUsage {
+            total_bytes,
+            clip_count: files.len(),
+            limit_bytes,
+        }
+    }
+
     pub fn import_custom_clip(
         &mut self,
         label: &str,
         file_name: &str,
         bytes: &[u8],
+        limit_bytes: u64,
     ) -> Result<SoundboardClip, String> {
         if bytes.is_empty() {
             return Err("sound file is empty".to_string());
@@ -156,6 +246,8 @@ impl SoundboardStore {
                 MAX_IMPORT_BYTES / (1024 * 1024)
             ));
         }
+        let usage = self.usage(limit_bytes);
+        ensure_storage_available(usage.total_bytes, bytes.len() as u64, limit_bytes)?;
 
         let ext = normalize_extension(file_name)
             .ok_or_else(|| "unsupported file type. use .mp3, .wav, or .ogg".to_string())?;
@@ -177,6 +269,9 @@ impl SoundboardStore {
             label: normalized_label,
             source: SoundboardClipSource::Custom,
             duration_ms: duration_ms_for_samples(decoded.len()),
+            category: None,
+            play_count: 0,
+            last_played_at_ms: None,
         };
 
         self.clips.insert(
@@ -218,6 +313,28 @@ impl SoundboardStore {
         Ok(())
     }
 
+    // Counts only in memory. Returns how long the caller should wait before calling
+    // flush_usage when this play is the first one since the last scheduled flush.
+    pub fn record_play(&mut self, clip_id: &str, now: Instant) -> Option<Duration> {
+        let entry = self.clips.get_mut(clip_id)?;
+        entry.clip.play_count = entry.clip.play_count.saturating_add(1);
+        entry.clip.last_played_at_ms = Some(unix_time_ms());
+        self.usage_dirty = true;
+        if self.usage_flush_scheduled {
+            return None;
+        }
+        self.usage_flush_scheduled = true;
+        Some(usage_flush_delay(self.last_usage_flush, now))
+    }
+
+    pub fn flush_usage(&mut self) -> Result<(), String> {
+        self.usage_flush_scheduled = false;
+        if !self.usage_dirty {
+            return Ok(());
+        }
+        self.persist_manifest()
+    }
+
     pub fn samples_for_clip(&self, clip_id: &str) -> Option<Vec<f32>> {
         self.clips
             .get(clip_id)
@@ -231,24 +348,49 @@ impl SoundboardStore {
             if samples.is_empty() {
                 continue;
             }
-            let clip = SoundboardClip {
-                id: asset.id.to_string(),
-                label: asset.label.to_string(),
-                source: SoundboardClipSource::Default,
-                duration_ms: duration_ms_for_samples(samples.len()),
-            };
-            self.clips.insert(
-                clip.id.clone(),
-                StoredClip {
-                    clip,
-                    samples_48k: samples,
-                    file_path: None,
-                },
-            );
+            self.insert_default_clip(asset.id.to_string(), asset.label.to_string(), samples);
         }
         Ok(())
     }
 
+    // Runs after the built-ins so a descriptor can't shadow clips that cues refer to.
+    fn load_disk_default_clips(&mut self, dir: &Path) {
+        for disk_default in read_disk_defaults(dir) {
+            if self.clips.contains_key(&disk_default.id) {
+                log::warn!(
+                    "skipping default clip descriptor {}: id is already in use",
+                    disk_default.id
+                );
+                continue;
+            }
+            self.insert_default_clip(
+                disk_default.id,
+                disk_default.label,
+                disk_default.samples_48k,
+            );
+        }
+    }
+
+    fn insert_default_clip(&mut self, id: String, label: String, samples_48k: Vec<f32>) {
+        let clip = SoundboardClip {
+            id,
+            label,
+            source: SoundboardClipSource::Default,
+            duration_ms: duration_ms_for_samples(samples_48k.len()),
+            category: None,
+            play_count: 0,
+            last_played_at_ms: None,
+        };
+        self.clips.insert(
+            clip.id.clone(),
+            StoredClip {
+                clip,
+                samples_48k,
+                file_path: None,
+            },
+        );
+    }
+
     fn load_custom_clips(&mut self) -> Result<(), String> {
         let manifest = self.read_manifest()?;
         let mut loaded_entries = Vec::new();
@@ -292,6 +434,9 @@ impl SoundboardStore {
                 label: normalize_label(&item.label, &item.file_name),
                 source: SoundboardClipSource::Custom,
                 duration_ms: duration_ms_for_samples(decoded.len()),
+                category: normalize_category(item.category.as_deref()),
+                play_count: 0,
+                last_played_at_ms: None,
             };
             self.clips.insert(
                 clip.id.clone(),
@@ -304,8 +449,20 @@ impl SoundboardStore {
             loaded_entries.push(item);
         }
 
+        // Usage for clips that no longer exist is dropped along with their entries.
+        let mut clip_usage = manifest.clip_usage;
+        clip_usage.retain(|clip_id, usage| match self.clips.get_mut(clip_id) {
+            Some(entry) => {
+                entry.clip.play_count = usage.play_count;
+                entry.clip.last_played_at_ms = usage.last_played_at_ms;
+                true
+            }
+            None => false,
+        });
+
         self.write_manifest(&SoundboardManifest {
             custom_clips: loaded_entries,
+            clip_usage,
         })?;
         Ok(())
     }
@@ -320,7 +477,7 @@ impl SoundboardStore {
             .map_err(|err| format!("failed to parse soundboard manifest: {err}"))
     }
 
-    fn persist_manifest(&self) -> Result<(), String> {
+    fn persist_manifest(&mut self) -> Result<(), String> {
         let mut custom_clips = self
             .clips
             .values()
@@ -332,23 +489,47 @@ impl SoundboardStore {
                     id: entry.clip.id.clone(),
                     label: entry.clip.label.clone(),
                     file_name,
+                    category: entry.clip.category.clone(),
                 })
             })
             .collect::<Vec<_>>();
-        custom_clips
-            .sort_by(|left, right| left.label.to_lowercase().cmp(&right.label.to_lowercase()));
-        self.write_manifest(&SoundboardManifest { custom_clips })
+        custom_clips.sort_by_key(|entry| entry.label.to_lowercase());
+        let clip_usage = self
+            .clips
+            .values()
+            .filter(|entry| entry.clip.play_count > 0)
+            .map(|entry| {
+                (
+                    entry.clip.id.clone(),
+                    ManifestClipUsage {
+                        play_count: entry.clip.play_count,
+                        last_played_at_ms: entry.clip.last_played_at_ms,
+                    },
+                )
+            })
+            .collect();
+        self.write_manifest(&SoundboardManifest {
+            custom_clips,
+            clip_usage,
+        })
     }
 
-    fn write_manifest(&self, manifest: &SoundboardManifest) -> Result<(), String> {
+    // Every manifest write carries the current counts, so any of them settles pending usage.
+    fn write_manifest(&mut self, manifest: &SoundboardManifest) -> Result<(), String> {
         let content = serde_json::to_string_pretty(manifest)
             .map_err(|err| format!("failed to serialize soundboard manifest: {err}"))?;
         fs::write(&self.manifest_path, content)
-            .map_err(|err| format!("failed to write soundboard manifest: {err}"))
+            .map_err(|err| format!("failed to write soundboard manifest: {err}"))?;
+        self.usage_dirty = false;
+        self.last_usage_flush = Instant::now();
+        Ok(())
     }
 }
 
 fn resolve_soundboard_root() -> Result<PathBuf, String> {
+    if let Some(data_dir) = data_dir_override().map_err(|err| err.to_string())? {
+        return Ok(data_dir.join(SOUNDBOARD_DIR));
+    }
     let base = dirs::data_local_dir()
         .or_else(dirs::data_dir)
         .ok_or_else(|| "failed to resolve local data directory".to_string())?;
@@ -364,6 +545,17 @@ fn next_custom_clip_id() -> String {
     format!("custom-{timestamp_ms}-{counter}")
 }
 
+fn unix_time_ms() -> u64 {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+}
+
+fn usage_flush_delay(last_flush: Instant, now: Instant) -> Duration {
+    USAGE_FLUSH_INTERVAL.saturating_sub(now.saturating_duration_since(last_flush))
+}
+
 fn normalize_label(label: &str, file_name: &str) -> String {
     let trimmed = label.trim();
     if !trimmed.is_empty() {
@@ -381,6 +573,32 @@ fn normalize_label(label: &str, file_name: &str) -> String {
     }
 }
 
+fn normalize_category(category: Option<&str>) -> Option<String> {
+    let trimmed = category?.trim();
+    (!trimmed.is_empty()).then(|| trimmed.chars().take(MAX_CATEGORY_CHARS).collect())
+}
+
+// Defaults first, then custom clips grouped by category with uncategorized clips last.
+fn compare_clips(left: &SoundboardClip, right: &SoundboardClip) -> CmpOrdering {
+    let by_label = || left.label.to_lowercase().cmp(&right.label.to_lowercase());
+    match (&left.source, &right.source) {
+        (SoundboardClipSource::Default, SoundboardClipSource::Custom) => CmpOrdering::Less,
+        (SoundboardClipSource::Custom, SoundboardClipSource::Default) => CmpOrdering::Greater,
+        (SoundboardClipSource::Default, SoundboardClipSource::Default) => by_label(),
+        (SoundboardClipSource::Custom, SoundboardClipSource::Custom) => {
+            match (&left.category, &right.category) {
+                (Some(left_category), Some(right_category)) => left_category
+                    .to_lowercase()
+                    .cmp(&right_category.to_lowercase())
+                    .then_with(by_label),
+                (Some(_), None) => CmpOrdering::Less,
+                (None, Some(_)) => CmpOrdering::Greater,
+                (None, None) => by_label(),
+            }
+        }
+    }
+}
+
 fn normalize_extension(file_name: &str) -> Option<&'static str> {
     let ext = Path::new(file_name)
         .extension()?
@@ -398,6 +616,21 @@ fn duration_ms_for_samples(sample_count: usize) -> u32 {
     ((sample_count as u64 * 1000) / OUTPUT_SAMPLE_RATE as u64) as u32
 }
 
+fn ensure_storage_available(
+    used_bytes: u64,
+    incoming_bytes: u64,
+    limit_bytes: u64,
+) -> Result<(), String> {
+    if used_bytes.saturating_add(incoming_bytes) > limit_bytes {
+        return Err(format!(
+            "soundboard storage is full ({:.1} of {} MB used); delete clips to make room",
+            used_bytes as f64 / (1024.0 * 1024.0),
+            limit_bytes / (1024 * 1024)
+        ));
+    }
+    Ok(())
+}
+
 fn ensure_clip_length(sample_count: usize) -> Result<(), String> {
     if sample_count > MAX_CLIP_SAMPLES {
         return Err(format!(
@@ -554,6 +787,79 @@ fn default_assets() -> [DefaultAsset; 3] {
     ]
 }
 
+// A bad descriptor only loses that clip; the rest of the soundboard still loads.
+fn read_disk_defaults(dir: &Path) -> Vec<DiskDefault> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Vec::new(),
+        Err(err) => {
+            log::warn!(
+                "failed to read default clip descriptors in {}: {err}",
+                dir.display()
+            );
+            return Vec::new();
+        }
+    };
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(DESCRIPTOR_EXTENSION))
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| match read_disk_default(&path) {
+            Ok(disk_default) => Some(disk_default),
+            Err(err) => {
+                log::warn!("skipping default clip descriptor {}: {err}", path.display());
+                None
+            }
+        })
+        .collect()
+}
+
+fn read_disk_default(path: &Path) -> Result<DiskDefault, String> {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| "file name is not valid utf8".to_string())?;
+    let id = disk_default_clip_id(file_name).ok_or_else(|| "file name has no stem".to_string())?;
+    let size = fs::metadata(path)
+        .map_err(|err| format!("failed to read descriptor: {err}"))?
+        .len();
+    if size > MAX_DESCRIPTOR_BYTES {
+        return Err(format!(
+            "descriptor is too large (max {MAX_DESCRIPTOR_BYTES} bytes)"
+        ));
+    }
+    let raw = fs::read(path).map_err(|err| format!("failed to read descriptor: {err}"))?;
+    let samples_48k = synthesize_default_clip(parse_default_spec(&raw)?);
+    if samples_48k.is_empty() {
+        return Err("descriptor produced no audio".to_string());
+    }
+    Ok(DiskDefault {
+        id,
+        label: normalize_label("", file_name),
+        samples_48k,
+    })
+}
+
+// "Air Horn.sb" becomes "default-air-horn", matching the built-in id style.
+fn disk_default_clip_id(file_name: &str) -> Option<String> {
+    let stem = Path::new(file_name).file_stem()?.to_str()?.trim();
+    let slug = stem
+        .split(|ch: char| !ch.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+    (!slug.is_empty()).then(|| format!("default-{slug}"))
+}
+
 fn parse_default_spec(raw_descriptor: &[u8]) -> Result<DefaultSpec, String> {
     let text = std::str::from_utf8(raw_descriptor)
         .map_err(|err| format!("default sound descriptor must be utf8: {err}"))?;
@@ -667,6 +973,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn disk_defaults_load_valid_descriptors_and_skip_bad_ones() {
+        let dir =
+            std::env::temp_dir().join(format!("harmony-disk-defaults-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Air Horn.sb"), "wave=square\nfreq_hz=420\n").unwrap();
+        fs::write(dir.join("broken.sb"), "wave=sawtooth\n").unwrap();
+        fs::write(dir.join("notes.txt"), "wave=sine\n").unwrap();
+
+        let loaded = read_disk_defaults(&dir);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, "default-air-horn");
+        assert_eq!(loaded[0].label, "Air Horn");
+        assert!(!loaded[0].samples_48k.is_empty());
+
+        assert!(read_disk_defaults(&dir).is_empty());
+        assert_eq!(disk_default_clip_id("--.sb"), None);
+    }
+
     #[test]
     fn resample_linear_downsamples() {
         let input = vec![0.0_f32; 48_000];
@@ -682,6 +1009,100 @@ mod tests {
         assert_eq!(normalize_extension("clip.flac"), None);
     }
 
+    #[test]
+    fn compare_clips_groups_custom_clips_by_category() {
+        let clip =
+            |id: &str, source: SoundboardClipSource, category: Option<&str>| SoundboardClip {
+                id: id.to_string(),
+                label: id.to_string(),
+                source,
+                duration_ms: 100,
+                category: category.map(str::to_string),
+                play_count: 0,
+                last_played_at_ms: None,
+            };
+        let mut clips = [
+            clip("zap", SoundboardClipSource::Custom, None),
+            clip("boo", SoundboardClipSource::Custom, Some("memes")),
+            clip("air", SoundboardClipSource::Custom, Some("Alerts")),
+            clip("pop", SoundboardClipSource::Default, None),
+            clip("ahh", SoundboardClipSource::Custom, Some("memes")),
+        ];
+        clips.sort_by(compare_clips);
+        let ids = clips
+            .iter()
+            .map(|clip| clip.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["pop", "air", "ahh", "boo", "zap"]);
+    }
+
+    #[test]
+    fn normalize_category_trims_and_drops_empty_values() {
+        assert_eq!(
+            normalize_category(Some("  memes ")),
+            Some("memes".to_string())
+        );
+        assert_eq!(normalize_category(Some("   ")), None);
+        assert_eq!(normalize_category(None), None);
+    }
+
+    #[test]
+    fn ensure_storage_available_rejects_imports_past_the_limit() {
+        assert!(ensure_storage_available(900, 100, 1_000).is_ok());
+        assert!(ensure_storage_available(901, 100, 1_000).is_err());
+    }
+
+    #[test]
+    fn usage_flush_waits_out_the_rest_of_the_batch_interval() {
+        let last_flush = Instant::now();
+        assert_eq!(
+            usage_flush_delay(last_flush, last_flush),
+            USAGE_FLUSH_INTERVAL
+        );
+        assert_eq!(
+            usage_flush_delay(last_flush, last_flush + Duration::from_secs(10)),
+            USAGE_FLUSH_INTERVAL - Duration::from_secs(10)
+        );
+        assert_eq!(
+            usage_flush_delay(last_flush, last_flush + USAGE_FLUSH_INTERVAL * 2),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn a_single_play_is_persisted_by_the_scheduled_flush() {
+        let root =
+            std::env::temp_dir().join(format!("harmony-soundboard-usage-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let mut store = SoundboardStore::open(&root).unwrap();
+
+        let now = Instant::now();
+        assert!(store.record_play("default-chime", now).is_some());
+        // Later plays ride on the flush that is already scheduled.
+        assert!(store.record_play("default-chime", now).is_none());
+        store.flush_usage().unwrap();
+
+        let reopened = SoundboardStore::open(&root).unwrap();
+        let _ = fs::remove_dir_all(&root);
+        let chime = reopened
+            .list_clips()
+            .into_iter()
+            .find(|clip| clip.id == "default-chime")
+            .unwrap();
+        assert_eq!(chime.play_count, 2);
+        assert!(chime.last_played_at_ms.is_some());
+    }
+
+    #[test]
+    fn manifest_without_usage_still_parses() {
+        let manifest = serde_json::from_str::<SoundboardManifest>(
+            r#"{"custom_clips":[{"id":"custom-1","label":"Boo","file_name":"custom-1.wav"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.custom_clips.len(), 1);
+        assert!(manifest.clip_usage.is_empty());
+    }
+
     #[test]
     fn ensure_clip_length_enforces_duration_limit() {
         assert!(ensure_clip_length(MAX_CLIP_SAMPLES).is_ok());
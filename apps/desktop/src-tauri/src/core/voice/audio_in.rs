@@ -4,7 +4,9 @@ use std::sync::{mpsc, Arc};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{FromSample, Sample, SampleFormat, StreamConfig};
 
-use super::AudioDevice;
+use super::{
+    match_device, resolve_host, select_stream_config, AudioDevice, DeviceIdAllocator, DeviceMatch,
+};
 
 const CLIP_THRESHOLD: f32 = 0.995;
 
@@ -34,6 +36,7 @@ impl InputStatsAtomic {
 
 pub struct InputCapture {
     _stream: cpal::Stream,
+    missing_device_id: Option<String>,
     sample_rate: u32,
     device_name: String,
     stats: Arc<InputStatsAtomic>,
@@ -41,6 +44,11 @@ pub struct InputCapture {
 }
 
 impl InputCapture {
+    // The saved device id that could not be found, when the default was used instead.
+    pub fn missing_device_id(&self) -> Option<&str> {
+        self.missing_device_id.as_deref()
+    }
+
     pub fn sample_rate(&self) -> u32 {
         self.sample_rate
     }
@@ -60,37 +68,47 @@ impl InputCapture {
     }
 }
 
-pub fn list_input_devices() -> Vec<AudioDevice> {
-    let host = cpal::default_host();
+pub fn list_input_devices(host_id: Option<&str>) -> Vec<AudioDevice> {
+    let host = resolve_host(host_id);
 
+    let mut ids = DeviceIdAllocator::default();
     host.input_devices()
         .ok()
         .map(|devices| {
             devices
                 .enumerate()
                 .map(|(idx, device)| {
-                    let name = device
-                        .name()
-                        .unwrap_or_else(|_| format!("Input Device {}", idx + 1));
-                    AudioDevice {
-                        id: name.clone(),
-                        name,
-                    }
+                    let name = input_device_name(&device, idx);
+                    let id = ids.next_id(&name);
+                    AudioDevice::new(id, name, device.default_input_config().ok())
                 })
                 .collect::<Vec<_>>()
         })
         .unwrap_or_default()
 }
 
-pub fn start_input_capture(selected_device_id: Option<&str>) -> Result<InputCapture, String> {
-    let host = cpal::default_host();
-    let device = resolve_input_device(&host, selected_device_id)?;
+pub fn start_input_capture(
+    selected_device_id: Option<&str>,
+    prefer_48k: bool,
+    host_id: Option<&str>,
+) -> Result<InputCapture, String> {
+    let host = resolve_host(host_id);
+    let (device, saved_missing) = resolve_input_device(&host, selected_device_id)?;
+    let missing_device_id = selected_device_id
+        .filter(|_| saved_missing)
+        .map(str::to_string);
     let device_name = device
         .name()
         .unwrap_or_else(|_| "Unknown Input".to_string());
-    let supported = device
+    let default_config = device
         .default_input_config()
         .map_err(|err| format!("failed to query default input config: {err}"))?;
+    let supported = select_stream_config(
+        "input",
+        default_config,
+        device.supported_input_configs().into_iter().flatten(),
+        prefer_48k,
+    );
     let sample_rate = supported.sample_rate().0;
     let sample_format = supported.sample_format();
     let stream_config: StreamConfig = supported.into();
@@ -186,6 +204,7 @@ pub fn start_input_capture(selected_device_id: Option<&str>) -> Result<InputCapt
 
     Ok(InputCapture {
         _stream: stream,
+        missing_device_id,
         sample_rate,
         device_name,
         stats,
@@ -193,26 +212,39 @@ pub fn start_input_capture(selected_device_id: Option<&str>) -> Result<InputCapt
     })
 }
 
+fn input_device_name(device: &cpal::Device, idx: usize) -> String {
+    device
+        .name()
+        .unwrap_or_else(|_| format!("Input Device {}", idx + 1))
+}
+
+// Returns the device and whether a saved selection was missing and replaced by the default.
 fn resolve_input_device(
     host: &cpal::Host,
     selected_device_id: Option<&str>,
-) -> Result<cpal::Device, String> {
-    if let Some(target_id) = selected_device_id {
-        let devices = host
-            .input_devices()
-            .map_err(|err| format!("failed to enumerate input devices: {err}"))?;
-        for device in devices {
-            let Ok(name) = device.name() else {
-                continue;
-            };
-            if name == target_id {
-                return Ok(device);
-            }
-        }
-    }
+) -> Result<(cpal::Device, bool), String> {
+    let mut devices = if selected_device_id.is_some() {
+        host.input_devices()
+            .map_err(|err| format!("failed to enumerate input devices: {err}"))?
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+    let names = devices
+        .iter()
+        .enumerate()
+        .map(|(idx, device)| input_device_name(device, idx))
+        .collect::<Vec<_>>();
+    let saved_missing = match match_device(&names, selected_device_id) {
+        DeviceMatch::Found(idx) => return Ok((devices.swap_remove(idx), false)),
+        DeviceMatch::Default => false,
+        DeviceMatch::SavedMissing => true,
+    };
 
-    host.default_input_device()
-        .ok_or_else(|| "no input device available".to_string())
+    let device = host
+        .default_input_device()
+        .ok_or_else(|| "no input device available".to_string())?;
+    Ok((device, saved_missing))
 }
 
 fn build_input_stream<T>(
@@ -6,14 +6,34 @@ use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{FromSample, Sample, SampleFormat, StreamConfig};
 use crossbeam_queue::ArrayQueue;
 
-use super::resampler::MonoResampler;
-use super::AudioDevice;
-
-const OUTPUT_QUEUE_SECONDS: f32 = 1.2;
-const OUTPUT_QUEUE_MIN_CAPACITY: usize = 9_600;
+use super::resampler::{MonoResampler, ResamplerQuality};
+use super::{
+    match_device, resolve_host, select_stream_config, AudioDevice, DeviceIdAllocator, DeviceMatch,
+};
+
+pub const OUTPUT_BUFFER_MIN_MS: u32 = 100;
+pub const OUTPUT_BUFFER_MAX_MS: u32 = 5_000;
+pub const OUTPUT_PREFILL_MIN_MS: u32 = 10;
 const CLIP_THRESHOLD: f32 = 0.995;
 const QUEUE_LOG_WINDOW_PUSHES: u32 = 120;
-const PLAYOUT_PREFILL_MS: usize = 45;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputBufferConfig {
+    pub buffer_ms: u32,
+    pub prefill_ms: u32,
+}
+
+impl OutputBufferConfig {
+    // Prefill never exceeds half the queue, or playout could not start before overflow drops.
+    pub fn new(buffer_ms: u32, prefill_ms: u32) -> Self {
+        let buffer_ms = buffer_ms.clamp(OUTPUT_BUFFER_MIN_MS, OUTPUT_BUFFER_MAX_MS);
+        let prefill_ms = prefill_ms.clamp(OUTPUT_PREFILL_MIN_MS, buffer_ms / 2);
+        Self {
+            buffer_ms,
+            prefill_ms,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct OutputPlaybackStats {
@@ -89,15 +109,22 @@ struct PushWindowState {
 
 pub struct OutputPlayback {
     _stream: cpal::Stream,
+    missing_device_id: Option<String>,
     device_name: String,
     sample_rate: u32,
-    queue: Arc<ArrayQueue<f32>>,
-    resampler: Mutex<MonoResampler>,
+    queue: Arc<ArrayQueue<[f32; 2]>>,
+    resampler: Mutex<StereoResampler>,
+    buffer_config: OutputBufferConfig,
     stats: Arc<PlaybackStatsAtomic>,
     push_window: Mutex<PushWindowState>,
 }
 
 impl OutputPlayback {
+    // The saved device id that could not be found, when the default was used instead.
+    pub fn missing_device_id(&self) -> Option<&str> {
+        self.missing_device_id.as_deref()
+    }
+
     pub fn device_name(&self) -> &str {
         &self.device_name
     }
@@ -106,45 +133,73 @@ impl OutputPlayback {
         self.sample_rate
     }
 
+    pub fn buffer_config(&self) -> OutputBufferConfig {
+        self.buffer_config
+    }
+
     pub fn stats_snapshot(&self) -> OutputPlaybackStats {
         self.stats.snapshot(self.queue.len())
     }
 
+    pub fn clear(&self) {
+        while self.queue.pop().is_some() {}
+    }
+
     pub fn push_mono_48k(&self, samples: &[f32]) {
         if samples.is_empty() {
             return;
         }
 
-        let mut converted = Vec::with_capacity(samples.len() + samples.len() / 4 + 8);
-        if let Ok(mut resampler) = self.resampler.lock() {
-            if let Err(err) = resampler.process(samples, &mut converted) {
-                log::warn!("output resampler failed; dropping frame chunk: {err}");
-                return;
-            }
-        } else {
+        self.push_resampled(samples, samples);
+    }
+
+    pub fn push_stereo_48k(&self, left: &[f32], right: &[f32]) {
+        if left.is_empty() || right.is_empty() {
             return;
         }
+        let len = left.len().min(right.len());
+        self.push_resampled(&left[..len], &right[..len]);
+    }
 
-        if converted.is_empty() {
+    fn push_resampled(&self, left: &[f32], right: &[f32]) {
+        let capacity = left.len() + left.len() / 4 + 8;
+        let mut converted_left = Vec::with_capacity(capacity);
+        let mut converted_right = Vec::with_capacity(capacity);
+        let Ok(mut resampler) = self.resampler.lock() else {
+            return;
+        };
+        if let Err(err) = resampler.process(left, right, &mut converted_left, &mut converted_right)
+        {
+            log::warn!("output resampler failed; dropping frame chunk: {err}");
             return;
         }
+        drop(resampler);
 
-        for sample in converted {
-            let clipped = sample.clamp(-1.0, 1.0);
+        for (&l, &r) in converted_left.iter().zip(&converted_right) {
+            self.push_frame([l, r]);
+        }
+        self.observe_push_depth();
+    }
+
+    fn push_frame(&self, frame: [f32; 2]) {
+        let clipped = frame.map(|sample| {
             if sample.abs() >= CLIP_THRESHOLD {
                 self.stats.clipped_samples.fetch_add(1, Ordering::Relaxed);
             }
-
-            if self.queue.push(clipped).is_err() {
-                let _ = self.queue.pop();
-                if self.queue.push(clipped).is_ok() {
-                    self.stats
-                        .overflow_dropped_samples
-                        .fetch_add(1, Ordering::Relaxed);
-                }
+            sample.clamp(-1.0, 1.0)
+        });
+
+        if self.queue.push(clipped).is_err() {
+            let _ = self.queue.pop();
+            if self.queue.push(clipped).is_ok() {
+                self.stats
+                    .overflow_dropped_samples
+                    .fetch_add(1, Ordering::Relaxed);
             }
         }
+    }
 
+    fn observe_push_depth(&self) {
         let depth = self.queue.len();
         self.stats.observe_peak_depth(depth);
 
@@ -173,47 +228,58 @@ impl OutputPlayback {
     }
 }
 
-pub fn list_output_devices() -> Vec<AudioDevice> {
-    let host = cpal::default_host();
+pub fn list_output_devices(host_id: Option<&str>) -> Vec<AudioDevice> {
+    let host = resolve_host(host_id);
 
+    let mut ids = DeviceIdAllocator::default();
     host.output_devices()
         .ok()
         .map(|devices| {
             devices
                 .enumerate()
                 .map(|(idx, device)| {
-                    let name = device
-                        .name()
-                        .unwrap_or_else(|_| format!("Output Device {}", idx + 1));
-                    AudioDevice {
-                        id: name.clone(),
-                        name,
-                    }
+                    let name = output_device_name(&device, idx);
+                    let id = ids.next_id(&name);
+                    AudioDevice::new(id, name, device.default_output_config().ok())
                 })
                 .collect::<Vec<_>>()
         })
         .unwrap_or_default()
 }
 
-pub fn start_output_playback(selected_device_id: Option<&str>) -> Result<OutputPlayback, String> {
-    let host = cpal::default_host();
-    let device = resolve_output_device(&host, selected_device_id)?;
+pub fn start_output_playback(
+    selected_device_id: Option<&str>,
+    buffer_config: OutputBufferConfig,
+    resampler_quality: ResamplerQuality,
+    prefer_48k: bool,
+    host_id: Option<&str>,
+) -> Result<OutputPlayback, String> {
+    let host = resolve_host(host_id);
+    let (device, saved_missing) = resolve_output_device(&host, selected_device_id)?;
+    let missing_device_id = selected_device_id
+        .filter(|_| saved_missing)
+        .map(str::to_string);
     let device_name = device
         .name()
         .unwrap_or_else(|_| "Unknown Output".to_string());
-    let supported = device
+    let default_config = device
         .default_output_config()
         .map_err(|err| format!("failed to query default output config: {err}"))?;
+    let supported = select_stream_config(
+        "output",
+        default_config,
+        device.supported_output_configs().into_iter().flatten(),
+        prefer_48k,
+    );
 
     let sample_rate = supported.sample_rate().0;
     let sample_format = supported.sample_format();
     let config: StreamConfig = supported.into();
     let channels = usize::from(config.channels.max(1));
 
-    let queue_capacity = ((sample_rate as f32 * OUTPUT_QUEUE_SECONDS) as usize)
-        .max(OUTPUT_QUEUE_MIN_CAPACITY)
-        .max(channels * 256);
-    let queue = Arc::new(ArrayQueue::<f32>::new(queue_capacity));
+    let queue_capacity = ms_to_frames(sample_rate, buffer_config.buffer_ms).max(channels * 256);
+    let prefill_frames = ms_to_frames(sample_rate, buffer_config.prefill_ms);
+    let queue = Arc::new(ArrayQueue::<[f32; 2]>::new(queue_capacity));
     let stats = Arc::new(PlaybackStatsAtomic::default());
     let queue_for_callback = Arc::clone(&queue);
     let stats_for_callback = Arc::clone(&stats);
@@ -225,8 +291,8 @@ pub fn start_output_playback(selected_device_id: Option<&str>) -> Result<OutputP
         SampleFormat::I8 => build_output_stream::<i8>(
             &device,
             &config,
-            channels,
             sample_rate,
+            prefill_frames,
             queue_for_callback,
             stats_for_callback,
             err_fn,
@@ -234,8 +300,8 @@ pub fn start_output_playback(selected_device_id: Option<&str>) -> Result<OutputP
         SampleFormat::I16 => build_output_stream::<i16>(
             &device,
             &config,
-            channels,
             sample_rate,
+            prefill_frames,
             queue_for_callback,
             stats_for_callback,
             err_fn,
@@ -243,8 +309,8 @@ pub fn start_output_playback(selected_device_id: Option<&str>) -> Result<OutputP
         SampleFormat::I32 => build_output_stream::<i32>(
             &device,
             &config,
-            channels,
             sample_rate,
+            prefill_frames,
             queue_for_callback,
             stats_for_callback,
             err_fn,
@@ -252,8 +318,8 @@ pub fn start_output_playback(selected_device_id: Option<&str>) -> Result<OutputP
         SampleFormat::U8 => build_output_stream::<u8>(
             &device,
             &config,
-            channels,
             sample_rate,
+            prefill_frames,
             queue_for_callback,
             stats_for_callback,
             err_fn,
@@ -261,8 +327,8 @@ pub fn start_output_playback(selected_device_id: Option<&str>) -> Result<OutputP
         SampleFormat::U16 => build_output_stream::<u16>(
             &device,
             &config,
-            channels,
             sample_rate,
+            prefill_frames,
             queue_for_callback,
             stats_for_callback,
             err_fn,
@@ -270,8 +336,8 @@ pub fn start_output_playback(selected_device_id: Option<&str>) -> Result<OutputP
         SampleFormat::U32 => build_output_stream::<u32>(
             &device,
             &config,
-            channels,
             sample_rate,
+            prefill_frames,
             queue_for_callback,
             stats_for_callback,
             err_fn,
@@ -279,8 +345,8 @@ pub fn start_output_playback(selected_device_id: Option<&str>) -> Result<OutputP
         SampleFormat::F32 => build_output_stream::<f32>(
             &device,
             &config,
-            channels,
             sample_rate,
+            prefill_frames,
             queue_for_callback,
             stats_for_callback,
             err_fn,
@@ -288,8 +354,8 @@ pub fn start_output_playback(selected_device_id: Option<&str>) -> Result<OutputP
         SampleFormat::F64 => build_output_stream::<f64>(
             &device,
             &config,
-            channels,
             sample_rate,
+            prefill_frames,
             queue_for_callback,
             stats_for_callback,
             err_fn,
@@ -301,65 +367,133 @@ pub fn start_output_playback(selected_device_id: Option<&str>) -> Result<OutputP
         .play()
         .map_err(|err| format!("failed to start output stream: {err}"))?;
 
-    let resampler = MonoResampler::new(48_000, sample_rate)?;
+    let resampler = StereoResampler::new(sample_rate, resampler_quality)?;
     log::info!(
-        "output stream started: device=\"{}\" sample_rate={} channels={} format={:?} queue_capacity={}",
+        "output stream started: device=\"{}\" sample_rate={} channels={} format={:?} queue_capacity={} prefill={}",
         device_name,
         sample_rate,
         config.channels,
         sample_format,
-        queue_capacity
+        queue_capacity,
+        prefill_frames
     );
 
     Ok(OutputPlayback {
         _stream: stream,
+        missing_device_id,
         device_name,
         sample_rate,
         queue,
         resampler: Mutex::new(resampler),
+        buffer_config,
         stats,
         push_window: Mutex::new(PushWindowState::default()),
     })
 }
 
+fn output_device_name(device: &cpal::Device, idx: usize) -> String {
+    device
+        .name()
+        .unwrap_or_else(|_| format!("Output Device {}", idx + 1))
+}
+
+// Returns the device and whether a saved selection was missing and replaced by the default.
 fn resolve_output_device(
     host: &cpal::Host,
     selected_device_id: Option<&str>,
-) -> Result<cpal::Device, String> {
-    if let Some(target_id) = selected_device_id {
-        let devices = host
-            .output_devices()
-            .map_err(|err| format!("failed to enumerate output devices: {err}"))?;
-        for device in devices {
-            let Ok(name) = device.name() else {
-                continue;
-            };
-            if name == target_id {
-                return Ok(device);
-            }
+) -> Result<(cpal::Device, bool), String> {
+    let mut devices = if selected_device_id.is_some() {
+        host.output_devices()
+            .map_err(|err| format!("failed to enumerate output devices: {err}"))?
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+    let names = devices
+        .iter()
+        .enumerate()
+        .map(|(idx, device)| output_device_name(device, idx))
+        .collect::<Vec<_>>();
+    let saved_missing = match match_device(&names, selected_device_id) {
+        DeviceMatch::Found(idx) => return Ok((devices.swap_remove(idx), false)),
+        DeviceMatch::Default => false,
+        DeviceMatch::SavedMissing => true,
+    };
+
+    let device = host
+        .default_output_device()
+        .ok_or_else(|| "no output device available".to_string())?;
+    Ok((device, saved_missing))
+}
+
+// Mono devices get the downmix; extra channels beyond the first two get the center mix.
+fn write_output_frame<T>(frame: &mut [T], left: f32, right: f32, stats: &PlaybackStatsAtomic)
+where
+    T: Sample + FromSample<f32>,
+{
+    let center = (left + right) * 0.5;
+    let mono_device = frame.len() == 1;
+    for (idx, sample) in frame.iter_mut().enumerate() {
+        let value = match idx {
+            0 if !mono_device => left,
+            1 => right,
+            _ => center,
+        };
+        if value.abs() >= CLIP_THRESHOLD {
+            stats.clipped_samples.fetch_add(1, Ordering::Relaxed);
         }
+        *sample = T::from_sample(value.clamp(-1.0, 1.0));
     }
+}
+
+fn ms_to_frames(sample_rate: u32, ms: u32) -> usize {
+    (u64::from(sample_rate) * u64::from(ms) / 1_000) as usize
+}
 
-    host.default_output_device()
-        .ok_or_else(|| "no output device available".to_string())
+// Each channel keeps its own filter state, but both are always fed the same number of input
+// samples. Mono pushes (cues, or stereo spread turned off) go through both, so a partial chunk
+// left pending in one channel is always matched in the other and L/R never drift apart.
+struct StereoResampler {
+    left: MonoResampler,
+    right: MonoResampler,
+}
+
+impl StereoResampler {
+    fn new(output_rate: u32, quality: ResamplerQuality) -> Result<Self, String> {
+        Ok(Self {
+            left: MonoResampler::new(48_000, output_rate, quality)?,
+            right: MonoResampler::new(48_000, output_rate, quality)?,
+        })
+    }
+
+    fn process(
+        &mut self,
+        left: &[f32],
+        right: &[f32],
+        out_left: &mut Vec<f32>,
+        out_right: &mut Vec<f32>,
+    ) -> Result<(), String> {
+        self.left.process(left, out_left)?;
+        self.right.process(right, out_right)
+    }
 }
 
 fn build_output_stream<T>(
     device: &cpal::Device,
     config: &StreamConfig,
-    channels: usize,
     sample_rate: u32,
-    queue: Arc<ArrayQueue<f32>>,
+    prefill_frames: usize,
+    queue: Arc<ArrayQueue<[f32; 2]>>,
     stats: Arc<PlaybackStatsAtomic>,
     err_fn: impl Fn(cpal::StreamError) + Send + 'static,
 ) -> Result<cpal::Stream, String>
 where
     T: Sample + cpal::SizedSample + FromSample<f32> + Send + 'static,
 {
-    let channels = channels.max(1);
+    let channels = usize::from(config.channels.max(1));
     let sample_rate = sample_rate.max(1);
     let frame_budget_us = 1_000_000_f64 / sample_rate as f64;
-    let prefill_samples = ((sample_rate as usize) * PLAYOUT_PREFILL_MS / 1_000).max(channels * 8);
+    let prefill_samples = prefill_frames.max(channels * 8);
     let mut primed = false;
     let mut underflowing = false;
 
@@ -370,8 +504,8 @@ where
                 let started = Instant::now();
 
                 for frame in data.chunks_mut(channels) {
-                    let mono = if !primed && queue.len() < prefill_samples {
-                        0.0
+                    let [left, right] = if !primed && queue.len() < prefill_samples {
+                        [0.0, 0.0]
                     } else if let Some(value) = queue.pop() {
                         if !primed {
                             primed = true;
@@ -387,17 +521,10 @@ where
                             stats.underflow_events.fetch_add(1, Ordering::Relaxed);
                             log::debug!("output stream underflow: queue depth={}", queue.len());
                         }
-                        0.0
+                        [0.0, 0.0]
                     };
 
-                    let clipped = mono.clamp(-1.0, 1.0);
-                    if mono.abs() >= CLIP_THRESHOLD {
-                        stats.clipped_samples.fetch_add(1, Ordering::Relaxed);
-                    }
-                    let converted = T::from_sample(clipped);
-                    for sample in frame {
-                        *sample = converted;
-                    }
+                    write_output_frame(frame, left, right, &stats);
                 }
 
                 let elapsed_us = started.elapsed().as_micros() as u64;
@@ -413,3 +540,49 @@ where
         )
         .map_err(|err| format!("failed to build output stream: {err}"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_buffer_config_enforces_minimums() {
+        assert_eq!(
+            OutputBufferConfig::new(1_200, 45),
+            OutputBufferConfig {
+                buffer_ms: 1_200,
+                prefill_ms: 45,
+            }
+        );
+        assert_eq!(
+            OutputBufferConfig::new(20, 1),
+            OutputBufferConfig {
+                buffer_ms: OUTPUT_BUFFER_MIN_MS,
+                prefill_ms: OUTPUT_PREFILL_MIN_MS,
+            }
+        );
+        assert_eq!(OutputBufferConfig::new(200, 500).prefill_ms, 100);
+    }
+
+    #[test]
+    fn stereo_resampler_keeps_channels_in_step_across_mono_pushes_at_44_1k() {
+        let mut resampler = StereoResampler::new(44_100, ResamplerQuality::Standard).unwrap();
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        // Odd-sized mono cues interleaved with stereo mix frames, as play_cue does mid-stream.
+        for chunk in [701_usize, 960, 333, 960, 960, 117, 960] {
+            let mono = vec![0.25_f32; chunk];
+            let quiet = vec![0.0_f32; chunk];
+            if chunk == 960 {
+                resampler
+                    .process(&mono, &quiet, &mut left, &mut right)
+                    .unwrap();
+            } else {
+                resampler
+                    .process(&mono, &mono, &mut left, &mut right)
+                    .unwrap();
+            }
+            assert_eq!(left.len(), right.len());
+        }
+        assert!(!left.is_empty());
+    }
+}
@@ -6,102 +6,188 @@ use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
 use std::sync::{Arc, RwLock as StdRwLock};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use bytes::BytesMut;
-use futures_util::stream::{SplitSink, SplitStream};
+use bytes::{Bytes, BytesMut};
+use futures_util::stream::{FuturesUnordered, SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
+use hickory_resolver::TokioAsyncResolver;
 use mumble_protocol::control::{msgs, ClientControlCodec, ControlPacket};
 use mumble_protocol::crypt::ClientCryptState;
-use mumble_protocol::voice::{Clientbound, VoicePacket, VoicePacketPayload};
+use mumble_protocol::voice::{Clientbound, VoicePacket, VoicePacketDst, VoicePacketPayload};
 use mumble_protocol::Serverbound;
 use native_tls::TlsConnector as NativeTlsConnector;
 use opus2::{Application, Bitrate, Channels, Decoder as OpusDecoder, Encoder as OpusEncoder};
+use protobuf::Message;
 use serde::Serialize;
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, RwLock};
-use tokio::time::{interval, sleep, MissedTickBehavior};
+use tokio::time::{interval, sleep, timeout, MissedTickBehavior};
 use tokio_native_tls::{TlsConnector, TlsStream};
 use tokio_util::codec::{Decoder, Framed};
 
 use tauri::AppHandle;
 
 use super::audio_in::{self, InputCapture, InputCaptureStats};
-use super::audio_out::{self, OutputPlayback, OutputPlaybackStats};
-use super::quality::{mix_mono_frames, should_conceal_gap, soft_limiter};
-use super::resampler::MonoResampler;
-use super::vad::VoiceActivityDetector;
-use crate::core::config::{
-    AppConfig, DEFAULT_USER_PASSWORD, SUPERUSER_AUTH_PASSWORD, SUPERUSER_AUTH_USERNAME,
-    SUPERUSER_TRIGGER_NICKNAME,
+use super::audio_out::{self, OutputBufferConfig, OutputPlayback, OutputPlaybackStats};
+use super::canonical_audio_host_id;
+use super::loss_injection::{LossInjection, LossInjector};
+use super::quality::{
+    mix_mono_frames, mix_stereo_frames, peak_level, rms_level, should_conceal_gap, soft_limiter,
+    stereo_pan_for_session,
 };
+use super::recorder::{MicSampleInfo, VoiceRecorder, MIC_SAMPLE_MAX_SECONDS};
+use super::resampler::{MonoResampler, ResamplerQuality};
+use super::vad::{VadSettings, VoiceActivityDetector};
+use crate::core::badges::{
+    encode_badge_comment, normalize_badge_codes, parse_badge_comment, parse_meta_comment, UserMeta,
+    DEFAULT_MAX_BADGE_CODES_PER_USER,
+};
+use crate::core::config::{AppConfig, PttMode, ServerConfig, SoundboardConfig, VoiceQualityConfig};
 use crate::core::events::{
-    self, ConnectionEvent, ConnectionState, MessageEvent, RosterEvent, SelfEvent, SpeakingEvent,
+    self, AudioWarning, AudioWarningEvent, AudioWarningKind, ConnectionEvent, ConnectionPhase,
+    ConnectionState, MessageEvent, MessageKind, MicLevelEvent, NetworkEvent, OutputLevelEvent,
+    PermissionDeniedEvent, RosterEvent, SelfEnforcedEvent, SelfEvent, ServerInfo, SpeakingEvent,
+    TalkingWhileMutedEvent, UserPresenceEvent,
 };
 
 type ControlFramed = Framed<TlsStream<TcpStream>, ClientControlCodec>;
 type ControlSink = SplitSink<ControlFramed, ControlPacket<Serverbound>>;
 type ControlStream = SplitStream<ControlFramed>;
 
-const OPUS_SAMPLE_RATE: u32 = 48_000;
-const OPUS_FRAME_SAMPLES: usize = 960;
+pub(crate) const OPUS_SAMPLE_RATE: u32 = 48_000;
+pub(crate) const OPUS_FRAME_SAMPLES: usize = 960;
 const OPUS_MAX_PACKET_SIZE: usize = 1024;
 const OPUS_MAX_DECODED_SAMPLES: usize = 5760;
+const DECODED_FRAME_POOL_LIMIT: usize = 4;
 // Mumble sequence numbers are frame-sequence counters, not PCM sample offsets.
 const OPUS_SEQ_STEP: u64 = 1;
 const DEFAULT_OPUS_BITRATE_BPS: i32 = 48_000;
-const OPUS_BITRATE_MIN_BPS: i32 = 32_000;
-const OPUS_BITRATE_MAX_BPS: i32 = 72_000;
-const OPUS_COMPLEXITY: i32 = 8;
+pub const OPUS_BITRATE_MIN_BPS: i32 = 32_000;
+pub const OPUS_BITRATE_MAX_BPS: i32 = 72_000;
+const OPUS_BITRATE_FLOOR_BPS: i32 = 8_000;
+// IPv4 + UDP + crypt tag + voice header, sequence and length bytes per 20 ms packet.
+const VOICE_PACKET_OVERHEAD_BYTES: i32 = 20 + 8 + 4 + 1 + 2 + 2;
+const VOICE_PACKETS_PER_SECOND: i32 = 1_000 / MEDIA_TICK_MS as i32;
+// Added to each datagram for the bandwidth estimate; the crypt tag is already in its length.
+const UDP_IP_HEADER_BYTES: usize = 20 + 8;
+const MUMBLE_DEFAULT_PORT: u16 = 64738;
+const MUMBLE_SRV_PREFIX: &str = "_mumble._tcp.";
+// Bounds one media tick's UDP reads; anything left waits in the socket for the next tick.
+const UDP_RX_MAX_PACKETS_PER_TICK: usize = 128;
+const UDP_RX_BUFFER_BYTES: usize = 2048;
+// Control frame prefix plus voice header, sequence and length bytes.
+const TUNNEL_VOICE_OVERHEAD_BYTES: usize = 6 + 1 + 2 + 2;
+pub const OPUS_COMPLEXITY_MIN: i32 = 0;
+pub const OPUS_COMPLEXITY_MAX: i32 = 10;
 const DEFAULT_OPUS_PACKET_LOSS_PCT: i32 = 10;
+pub const OPUS_PACKET_LOSS_MAX_PCT: i32 = 100;
+const OPUS_TUNED_PACKET_LOSS_MAX_PCT: i32 = 25;
 const MEDIA_TICK_MS: u64 = 20;
 const UDP_PING_INTERVAL_SECS: u64 = 5;
 const VOICE_HANGOVER_FRAMES: u32 = 4;
-const SOUNDBOARD_QUEUE_LIMIT_SAMPLES: usize = OPUS_SAMPLE_RATE as usize * 20;
-const SOUNDBOARD_MIX_GAIN: f32 = 0.55;
+const SOUNDBOARD_MAX_VOICES: usize = 4;
+const SOUNDBOARD_CROSSFADE_SAMPLES: usize = OPUS_SAMPLE_RATE as usize / 100;
+pub const SOUNDBOARD_MIX_GAIN_MAX: f32 = 1.0;
+const SOUNDBOARD_DUCK_GAIN: f32 = 0.35;
 const TX_HEADROOM_GAIN: f32 = 0.92;
 const TX_LIMITER_DRIVE: f32 = 1.25;
-#[cfg(target_os = "macos")]
-const VAD_THRESHOLD: f32 = 0.010;
-#[cfg(not(target_os = "macos"))]
-const VAD_THRESHOLD: f32 = 0.015;
-const VAD_OFF_THRESHOLD: f32 = VAD_THRESHOLD * 0.7;
 const UDP_DECRYPT_FAILURE_THRESHOLD: u32 = 12;
 const UDP_DEGRADED_WINDOW_MS: u64 = 10_000;
+const UDP_SEND_FAILURE_REBIND_THRESHOLD: u32 = 2;
+// A live capture stream delivers every few ms; a second of nothing means it died.
+const INPUT_STALL_TICKS: u32 = (1_000 / MEDIA_TICK_MS) as u32;
+// RFC 8305 recommends 150-250 ms between connection attempts.
+const HAPPY_EYEBALLS_STAGGER_MS: u64 = 250;
+// Reconnecting cannot fix this one, so the worker stops instead of retrying.
+const SERVER_OPUS_UNSUPPORTED_REASON: &str = "server does not support Opus";
 const DEFAULT_RX_JITTER_TARGET_FRAMES: usize = 4;
 const DEFAULT_RX_JITTER_MAX_FRAMES: usize = 10;
-const RX_JITTER_TARGET_MIN: usize = 2;
-const RX_JITTER_TARGET_MAX: usize = 8;
-const RX_JITTER_MAX_MIN: usize = 4;
-const RX_JITTER_MAX_MAX: usize = 16;
+pub const RX_JITTER_TARGET_MIN: usize = 2;
+pub const RX_JITTER_TARGET_MAX: usize = 8;
+pub const RX_JITTER_MAX_MIN: usize = 4;
+pub const RX_JITTER_MAX_MAX: usize = 16;
 const RX_GAP_PLC_TRIGGER_FRAMES: u64 = 2;
 const RX_MIX_HEADROOM_GAIN: f32 = 0.90;
 const RX_LIMITER_DRIVE: f32 = 1.35;
+const STEREO_PAN_SPREAD: f32 = 0.6;
+const POSITION_INFO_LEN: usize = 12;
+// Re-emit a speaker's position only once it has moved this far (game units, usually meters).
+const POSITION_EMIT_EPSILON: f32 = 0.25;
+const POSITIONAL_MIN_DISTANCE: f32 = 2.0;
+const POSITIONAL_MAX_DISTANCE: f32 = 30.0;
+const POSITIONAL_MIN_GAIN: f32 = 0.2;
 const INBOUND_STREAM_IDLE_TIMEOUT_MS: u64 = 8_000;
-const HARMONY_BADGES_COMMENT_PREFIX: &str = "harmony_badges:v1:";
-const MAX_BADGE_CODES_PER_USER: usize = 5;
-const MAX_BADGE_CODE_LEN: usize = 32;
+// Upper bound on the goodbye flush so a dead connection can't stall disconnect.
+const DISCONNECT_FLUSH_TIMEOUT_MS: u64 = 500;
 const MUMBLE_MIN_CHANNEL_LISTENER_MAJOR: u32 = 1;
 const MUMBLE_MIN_CHANNEL_LISTENER_MINOR: u32 = 4;
 const MUMBLE_MIN_CHANNEL_LISTENER_PATCH: u32 = 0;
+// UserState.listening_channel_add/remove, which mumble-protocol's older Mumble.proto lacks.
+const USER_STATE_LISTENING_CHANNEL_ADD: u32 = 21;
+const USER_STATE_LISTENING_CHANNEL_REMOVE: u32 = 22;
 const HARMONY_CLIENT_RELEASE_NAME: &str = "Harmony Desktop";
 const CODEC_ADAPT_INTERVAL_MS: u64 = 1_000;
+const MESSAGE_HISTORY_LIMIT: usize = 100;
+// Stays under the default Mumble flood limits.
+const TEXT_RATE_LIMIT_MESSAGES: u32 = 5;
+const TEXT_RATE_LIMIT_WINDOW_SECS: u64 = 10;
+const NETWORK_EVENT_INTERVAL_MS: u64 = 1_000;
+const RTT_SMOOTHING_FACTOR: f32 = 0.125;
+const MIC_LEVEL_EMIT_INTERVAL_MS: u64 = 200;
+const MIC_LEVEL_SMOOTHING_FACTOR: f32 = 0.3;
+const MUTED_SPEECH_WARN_FRAMES: u32 = (2_000 / MEDIA_TICK_MS) as u32;
+const MUTED_SPEECH_COOLDOWN_FRAMES: u32 = (30_000 / MEDIA_TICK_MS) as u32;
+// Counted in codec adapt intervals; spans at least two server ping replies.
+const ONE_WAY_AUDIO_WINDOWS: u32 = 25;
+// Output queue depth allowed above the jitter target before latency is trimmed back.
+const OUTPUT_LATENCY_HIGH_WATER_FRAMES: usize = 3;
+// Counted in mixed frames: a quiet frame is dropped after 0.5 s over the mark, any frame after 5 s.
+const OUTPUT_LATENCY_QUIET_DROP_MIXES: u32 = 25;
+const OUTPUT_LATENCY_FORCED_DROP_MIXES: u32 = 250;
+const OUTPUT_LATENCY_QUIET_PEAK: f32 = 0.01;
+// A device request is applied once no newer one has arrived for this long.
+const DEVICE_SWITCH_DEBOUNCE_MS: u64 = 250;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VoiceTransport {
+    Udp,
+    Tcp,
+}
 
 #[derive(Debug, Clone, Serialize)]
 pub struct AudioQualityMetrics {
     pub connected: bool,
     pub input_device_name: Option<String>,
     pub input_sample_rate: Option<u32>,
+    pub input_stream_restarts: u64,
     pub output_device_name: Option<String>,
     pub output_sample_rate: Option<u32>,
+    pub output_buffer_ms: u32,
+    pub output_prefill_ms: u32,
+    pub output_device_switches: u64,
     pub tx_frames_encoded: u64,
+    // Wall time per Opus call, for judging what opus_complexity costs on this machine.
+    pub encode_max_us: u64,
+    pub encode_avg_us: f32,
+    pub decode_max_us: u64,
+    pub decode_avg_us: f32,
+    // Encoded Opus sizes: <50, 50-99, 100-199 and 200+ bytes.
+    pub tx_packet_size_buckets: [u64; 4],
+    pub tx_packet_size_avg_bytes: f32,
     pub tx_packets_sent_udp: u64,
     pub tx_packets_sent_tcp: u64,
+    // Path the most recent voice packet went out on.
+    pub voice_transport: VoiceTransport,
+    pub udp_degraded: bool,
     pub tx_clip_samples: u64,
     pub tx_limiter_activations: u64,
     pub tx_bitrate_bps: i32,
     pub tx_packet_loss_percent: i32,
+    pub tx_bitrate_ceiling_bps: i32,
     pub rx_packets_received: u64,
     pub rx_frames_decoded: u64,
     pub rx_plc_frames: u64,
+    pub rx_fec_recovered: u64,
     pub rx_late_frames_dropped: u64,
     pub rx_gap_events: u64,
     pub rx_jitter_ms: f32,
@@ -110,6 +196,9 @@ pub struct AudioQualityMetrics {
     pub rx_buffered_peak_frames: usize,
     pub rx_mix_clip_samples: u64,
     pub rx_nan_samples: u64,
+    pub rx_decode_errors: u64,
+    // Media ticks that stopped reading at the per-tick UDP cap.
+    pub rx_packets_deferred: u64,
     pub output_underflow_events: u64,
     pub output_overflow_dropped_samples: u64,
     pub output_callback_overruns: u64,
@@ -117,12 +206,34 @@ pub struct AudioQualityMetrics {
     pub output_clipped_samples: u64,
     pub output_peak_queue_samples: usize,
     pub output_queued_samples: usize,
+    // Mixed frames dropped to pull a drifting output queue back toward the jitter target.
+    pub output_latency_corrections: u64,
     pub input_clipped_frames: u64,
     pub input_dropped_chunks: u64,
     pub input_delivered_chunks: u64,
     pub network_good_packets: u32,
     pub network_late_packets: u32,
     pub network_lost_packets: u32,
+    // Refreshed with each network event.
+    pub rtt_ms: Option<f32>,
+    pub packet_loss_percent: f32,
+    // Measured over the last codec adapt interval, including header overhead.
+    pub tx_kbps: f32,
+    pub rx_kbps: f32,
+    // Non-zero only while the dev loss injector is running.
+    pub rx_injected_loss_percent: f32,
+    pub rx_injected_reorder_percent: f32,
+    pub rx_injected_drops: u64,
+    // Keyed by session; a speaker drops out once their idle stream is cleaned up.
+    pub rx_per_speaker: BTreeMap<u32, SpeakerStats>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct SpeakerStats {
+    pub frames_decoded: u64,
+    pub plc_frames: u64,
+    pub fec_recovered: u64,
+    pub late_frames_dropped: u64,
 }
 
 impl Default for AudioQualityMetrics {
@@ -131,18 +242,32 @@ impl Default for AudioQualityMetrics {
             connected: false,
             input_device_name: None,
             input_sample_rate: None,
+            input_stream_restarts: 0,
             output_device_name: None,
             output_sample_rate: None,
+            output_buffer_ms: 0,
+            output_prefill_ms: 0,
+            output_device_switches: 0,
             tx_frames_encoded: 0,
+            encode_max_us: 0,
+            encode_avg_us: 0.0,
+            decode_max_us: 0,
+            decode_avg_us: 0.0,
+            tx_packet_size_buckets: [0; 4],
+            tx_packet_size_avg_bytes: 0.0,
             tx_packets_sent_udp: 0,
             tx_packets_sent_tcp: 0,
+            voice_transport: VoiceTransport::Tcp,
+            udp_degraded: false,
             tx_clip_samples: 0,
             tx_limiter_activations: 0,
             tx_bitrate_bps: DEFAULT_OPUS_BITRATE_BPS,
             tx_packet_loss_percent: DEFAULT_OPUS_PACKET_LOSS_PCT,
+            tx_bitrate_ceiling_bps: OPUS_BITRATE_MAX_BPS,
             rx_packets_received: 0,
             rx_frames_decoded: 0,
             rx_plc_frames: 0,
+            rx_fec_recovered: 0,
             rx_late_frames_dropped: 0,
             rx_gap_events: 0,
             rx_jitter_ms: 0.0,
@@ -151,6 +276,8 @@ impl Default for AudioQualityMetrics {
             rx_buffered_peak_frames: 0,
             rx_mix_clip_samples: 0,
             rx_nan_samples: 0,
+            rx_decode_errors: 0,
+            rx_packets_deferred: 0,
             output_underflow_events: 0,
             output_overflow_dropped_samples: 0,
             output_callback_overruns: 0,
@@ -158,12 +285,21 @@ impl Default for AudioQualityMetrics {
             output_clipped_samples: 0,
             output_peak_queue_samples: 0,
             output_queued_samples: 0,
+            output_latency_corrections: 0,
             input_clipped_frames: 0,
             input_dropped_chunks: 0,
             input_delivered_chunks: 0,
             network_good_packets: 0,
             network_late_packets: 0,
             network_lost_packets: 0,
+            rtt_ms: None,
+            packet_loss_percent: 0.0,
+            tx_kbps: 0.0,
+            rx_kbps: 0.0,
+            rx_injected_loss_percent: 0.0,
+            rx_injected_reorder_percent: 0.0,
+            rx_injected_drops: 0,
+            rx_per_speaker: BTreeMap::new(),
         }
     }
 }
@@ -175,21 +311,30 @@ struct CodecTuning {
     baseline_packet_loss_pct: i32,
     current_packet_loss_pct: i32,
     inband_fec: bool,
+    complexity: i32,
 }
 
 impl CodecTuning {
     fn new_from_config(config: &AppConfig) -> Self {
-        let voice = &config.voice_quality;
+        Self::new(&config.voice_quality)
+    }
+
+    fn new(voice: &VoiceQualityConfig) -> Self {
         let baseline_bitrate = voice
             .opus_bitrate_bps
             .clamp(OPUS_BITRATE_MIN_BPS, OPUS_BITRATE_MAX_BPS);
-        let baseline_loss = voice.packet_loss_perc.clamp(0, 25);
+        let baseline_loss = voice
+            .packet_loss_perc
+            .clamp(0, OPUS_TUNED_PACKET_LOSS_MAX_PCT);
         Self {
             baseline_bitrate_bps: baseline_bitrate,
             current_bitrate_bps: baseline_bitrate,
             baseline_packet_loss_pct: baseline_loss,
             current_packet_loss_pct: baseline_loss,
             inband_fec: voice.inband_fec,
+            complexity: voice
+                .opus_complexity
+                .clamp(OPUS_COMPLEXITY_MIN, OPUS_COMPLEXITY_MAX),
         }
     }
 }
@@ -200,19 +345,34 @@ struct JitterTuning {
     baseline_max_frames: usize,
     target_frames: usize,
     max_frames: usize,
+    prefill_frames: Option<usize>,
     gap_plc_trigger_frames: u64,
 }
 
 impl JitterTuning {
     fn new_from_config(config: &AppConfig) -> Self {
-        let target = config
-            .voice_quality
-            .jitter_target_frames
-            .clamp(RX_JITTER_TARGET_MIN, RX_JITTER_TARGET_MAX);
-        let mut max_frames = config
-            .voice_quality
-            .jitter_max_frames
-            .clamp(RX_JITTER_MAX_MIN, RX_JITTER_MAX_MAX);
+        Self::new(
+            config.voice_quality.jitter_target_frames,
+            config.voice_quality.jitter_max_frames,
+        )
+        .with_prefill(config.voice_quality.initial_prefill_frames)
+    }
+
+    fn with_prefill(mut self, prefill_frames: Option<usize>) -> Self {
+        self.prefill_frames = prefill_frames;
+        self
+    }
+
+    // Lets a stream start before the steady-state target; adaptation still grows the target.
+    fn start_frames(&self) -> usize {
+        self.prefill_frames
+            .unwrap_or(self.target_frames)
+            .clamp(1, self.max_frames)
+    }
+
+    fn new(target_frames: usize, max_frames: usize) -> Self {
+        let target = target_frames.clamp(RX_JITTER_TARGET_MIN, RX_JITTER_TARGET_MAX);
+        let mut max_frames = max_frames.clamp(RX_JITTER_MAX_MIN, RX_JITTER_MAX_MAX);
         if max_frames <= target {
             max_frames = (target + 2).clamp(RX_JITTER_MAX_MIN, RX_JITTER_MAX_MAX);
         }
@@ -221,6 +381,7 @@ impl JitterTuning {
             baseline_max_frames: max_frames,
             target_frames: target,
             max_frames,
+            prefill_frames: None,
             gap_plc_trigger_frames: RX_GAP_PLC_TRIGGER_FRAMES,
         }
     }
@@ -231,12 +392,15 @@ pub struct VoiceSharedState {
     pub connection: Arc<RwLock<ConnectionEvent>>,
     pub roster: Arc<RwLock<RosterEvent>>,
     pub self_state: Arc<RwLock<SelfEvent>>,
+    pub message_history: Arc<RwLock<VecDeque<MessageEvent>>>,
+    pub server_info: Arc<RwLock<ServerInfo>>,
 }
 
 pub struct VoiceService {
     worker: Option<tauri::async_runtime::JoinHandle<()>>,
     command_tx: Option<mpsc::UnboundedSender<VoiceCommand>>,
     quality_metrics: Arc<StdRwLock<AudioQualityMetrics>>,
+    text_limiter: TextRateLimiter,
 }
 
 impl VoiceService {
@@ -245,6 +409,7 @@ impl VoiceService {
             worker: None,
             command_tx: None,
             quality_metrics: Arc::new(StdRwLock::new(AudioQualityMetrics::default())),
+            text_limiter: TextRateLimiter::new(Instant::now()),
         }
     }
 
@@ -253,6 +418,7 @@ impl VoiceService {
         app: AppHandle,
         config: AppConfig,
         shared: VoiceSharedState,
+        cues: CueClips,
     ) -> Result<(), String> {
         self.disconnect().await;
 
@@ -267,7 +433,7 @@ impl VoiceService {
         let (command_tx, command_rx) = mpsc::unbounded_channel();
         let handle = tauri::async_runtime::spawn_blocking(move || {
             tauri::async_runtime::block_on(run_voice_worker(
-                app, config, shared, command_rx, metrics,
+                app, config, shared, command_rx, metrics, cues,
             ));
         });
 
@@ -296,14 +462,70 @@ impl VoiceService {
         self.send_command(VoiceCommand::SetDeafen(deafened));
     }
 
-    pub fn set_ptt(&self, enabled: bool) {
-        self.send_command(VoiceCommand::SetPtt(enabled));
+    pub fn set_all_users_muted(&self, muted: bool) {
+        self.send_command(VoiceCommand::SetAllUsersMuted(muted));
+    }
+
+    pub fn set_ptt_mode(&self, mode: PttMode) {
+        self.send_command(VoiceCommand::SetPttMode(mode));
+    }
+
+    pub fn set_ptt_key_held(&self, held: bool) {
+        self.send_command(VoiceCommand::SetPttKeyHeld(held));
     }
 
     pub fn set_ptt_hotkey(&self, hotkey: String) {
         self.send_command(VoiceCommand::SetPttHotkey(hotkey));
     }
 
+    pub fn set_jitter_buffer(&self, target_frames: usize, max_frames: usize) {
+        self.send_command(VoiceCommand::SetJitterBuffer {
+            target_frames,
+            max_frames,
+        });
+    }
+
+    pub fn set_adaptive_enabled(&self, enabled: bool) {
+        self.send_command(VoiceCommand::SetAdaptiveEnabled(enabled));
+    }
+
+    pub fn set_vad(&self, settings: VadSettings) {
+        self.send_command(VoiceCommand::SetVad(settings));
+    }
+
+    pub fn set_loss_injection(&self, injection: Option<LossInjection>) {
+        self.send_command(VoiceCommand::SetLossInjection(injection));
+    }
+
+    pub fn set_soundboard_mix(&self, mix: SoundboardConfig) {
+        self.send_command(VoiceCommand::SetSoundboardMix(mix));
+    }
+
+    pub fn apply_voice_quality(&self, quality: VoiceQualityConfig) {
+        self.send_command(VoiceCommand::ApplyVoiceQuality(quality));
+    }
+
+    pub fn set_self_position(&self, position: [f32; 3]) {
+        self.send_command(VoiceCommand::SetSelfPosition(position));
+    }
+
+    pub fn set_complexity(&self, complexity: i32) {
+        self.send_command(VoiceCommand::SetComplexity(complexity));
+    }
+
+    pub fn retry_audio(&self) {
+        self.send_command(VoiceCommand::RetryAudio);
+    }
+
+    // Skips the rest of the reconnect backoff; the attempt count still grows on failure.
+    pub fn reconnect_now(&self) -> Result<(), String> {
+        self.send_command_result(VoiceCommand::RetryNow)
+    }
+
+    pub fn set_audio_host(&self, audio_host: Option<String>) {
+        self.send_command(VoiceCommand::SetAudioHost(audio_host));
+    }
+
     pub fn set_input_device(&self, device_id: String) {
         self.send_command(VoiceCommand::SetInputDevice(device_id));
     }
@@ -312,8 +534,115 @@ impl VoiceService {
         self.send_command(VoiceCommand::SetOutputDevice(device_id));
     }
 
-    pub fn send_message(&self, message: String) -> Result<(), String> {
-        self.send_command_result(VoiceCommand::SendMessage(message))
+    // Without a channel id the message goes to the channel we're in.
+    pub fn send_message(&mut self, message: String, channel_id: Option<u32>) -> Result<(), String> {
+        self.acquire_text_slot()?;
+        self.send_command_result(VoiceCommand::SendMessage {
+            message,
+            channel_id,
+        })
+    }
+
+    pub fn send_private_message(&mut self, session: u32, message: String) -> Result<(), String> {
+        self.acquire_text_slot()?;
+        self.send_command_result(VoiceCommand::SendPrivateMessage { session, message })
+    }
+
+    // Checked here rather than in the worker so the caller gets the error and the
+    // connection stays up.
+    fn acquire_text_slot(&mut self) -> Result<(), String> {
+        if self.command_tx.is_none() {
+            return Err("voice service is not connected".to_string());
+        }
+        if !self.text_limiter.try_acquire(Instant::now()) {
+            return Err("sending messages too quickly; wait a moment and try again".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn start_recording(&self, app: AppHandle, include_self: bool) -> Result<String, String> {
+        if self.command_tx.is_none() {
+            return Err("voice service is not connected".to_string());
+        }
+        let recorder = VoiceRecorder::start(app, include_self)?;
+        let path = recorder.path().display().to_string();
+        self.send_command_result(VoiceCommand::StartRecording(recorder))?;
+        Ok(path)
+    }
+
+    pub fn stop_recording(&self) -> Result<(), String> {
+        self.send_command_result(VoiceCommand::StopRecording)
+    }
+
+    pub fn record_mic_sample(&self, app: AppHandle, seconds: u32) -> Result<MicSampleInfo, String> {
+        if self.command_tx.is_none() {
+            return Err("voice service is not connected".to_string());
+        }
+        if !(1..=MIC_SAMPLE_MAX_SECONDS).contains(&seconds) {
+            return Err(format!(
+                "mic sample length must be between 1 and {MIC_SAMPLE_MAX_SECONDS} seconds"
+            ));
+        }
+        let metrics = self.audio_quality_metrics();
+        if metrics.input_device_name.is_none() {
+            return Err("no microphone is capturing".to_string());
+        }
+
+        let recorder = VoiceRecorder::start_mic_sample(app, seconds)?;
+        let path = recorder.path().display().to_string();
+        self.send_command_result(VoiceCommand::StartMicSample(recorder))?;
+        Ok(MicSampleInfo {
+            path,
+            seconds,
+            sample_rate: OPUS_SAMPLE_RATE,
+            device_name: metrics.input_device_name,
+            device_sample_rate: metrics.input_sample_rate,
+        })
+    }
+
+    pub fn remove_user(
+        &self,
+        session: u32,
+        reason: Option<String>,
+        ban: bool,
+    ) -> Result<(), String> {
+        self.send_command_result(VoiceCommand::RemoveUser {
+            session,
+            reason,
+            ban,
+        })
+    }
+
+    pub fn join_channel(
+        &self,
+        channel_id: u32,
+        access_token: Option<String>,
+    ) -> Result<(), String> {
+        self.send_command_result(VoiceCommand::JoinChannel {
+            channel_id,
+            access_token,
+        })
+    }
+
+    pub fn set_channel_listener(&self, channel_id: u32, listen: bool) -> Result<(), String> {
+        self.send_command_result(VoiceCommand::SetChannelListener { channel_id, listen })
+    }
+
+    pub fn add_access_token(&self, token: String) {
+        self.send_command(VoiceCommand::AddAccessToken(token));
+    }
+
+    pub fn create_channel(
+        &self,
+        name: String,
+        parent_id: u32,
+        temporary: bool,
+    ) -> Result<(), String> {
+        self.send_command_result(VoiceCommand::CreateChannel {
+            name,
+            parent_id,
+            temporary,
+        })
     }
 
     pub fn queue_soundboard_samples(&self, samples_48k: Vec<f32>) -> Result<(), String> {
@@ -327,6 +656,17 @@ impl VoiceService {
             .unwrap_or_default()
     }
 
+    pub fn per_speaker_stats(&self) -> BTreeMap<u32, SpeakerStats> {
+        self.quality_metrics
+            .read()
+            .map(|snapshot| snapshot.rx_per_speaker.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn quality_metrics_handle(&self) -> Arc<StdRwLock<AudioQualityMetrics>> {
+        Arc::clone(&self.quality_metrics)
+    }
+
     fn send_command(&self, command: VoiceCommand) {
         if let Some(tx) = &self.command_tx {
             let _ = tx.send(command);
@@ -346,12 +686,65 @@ enum VoiceCommand {
     Disconnect,
     SetMute(bool),
     SetDeafen(bool),
-    SetPtt(bool),
+    SetAllUsersMuted(bool),
+    SetPttMode(PttMode),
+    SetPttKeyHeld(bool),
     SetPttHotkey(String),
+    SetJitterBuffer {
+        target_frames: usize,
+        max_frames: usize,
+    },
+    SetAdaptiveEnabled(bool),
+    ApplyVoiceQuality(VoiceQualityConfig),
+    SetSelfPosition([f32; 3]),
+    SetComplexity(i32),
+    SetVad(VadSettings),
     SetInputDevice(String),
+    RetryAudio,
+    RetryNow,
+    SetAudioHost(Option<String>),
     SetOutputDevice(String),
-    SendMessage(String),
+    SendMessage {
+        message: String,
+        channel_id: Option<u32>,
+    },
+    SendPrivateMessage {
+        session: u32,
+        message: String,
+    },
+    AddAccessToken(String),
+    JoinChannel {
+        channel_id: u32,
+        access_token: Option<String>,
+    },
+    SetChannelListener {
+        channel_id: u32,
+        listen: bool,
+    },
+    RemoveUser {
+        session: u32,
+        reason: Option<String>,
+        ban: bool,
+    },
+    CreateChannel {
+        name: String,
+        parent_id: u32,
+        temporary: bool,
+    },
     QueueSoundboardSamples(Vec<f32>),
+    SetSoundboardMix(SoundboardConfig),
+    SetLossInjection(Option<LossInjection>),
+    StartRecording(VoiceRecorder),
+    StopRecording,
+    StartMicSample(VoiceRecorder),
+}
+
+// Cue audio is resolved from the soundboard up front since the worker has no store access.
+#[derive(Debug, Clone, Default)]
+pub struct CueClips {
+    pub join: Option<Arc<[f32]>>,
+    pub leave: Option<Arc<[f32]>>,
+    pub mute: Option<Arc<[f32]>>,
 }
 
 struct LiveConnection {
@@ -364,11 +757,17 @@ struct ProtocolUser {
     session: u32,
     name: String,
     badge_codes: Vec<String>,
+    meta: UserMeta,
+    // Hash of the comment we last asked the server for, so each change is requested once.
+    requested_comment_hash: Vec<u8>,
     channel_id: u32,
     muted: bool,
     deafened: bool,
     speaking: bool,
     speaking_at: Option<Instant>,
+    position: Option<[f32; 3]>,
+    priority_speaker: bool,
+    listening_channel_ids: Vec<u32>,
 }
 
 impl ProtocolUser {
@@ -377,11 +776,16 @@ impl ProtocolUser {
             session,
             name: format!("User {}", session),
             badge_codes: Vec::new(),
+            meta: UserMeta::default(),
+            requested_comment_hash: Vec::new(),
             channel_id: 0,
             muted: false,
             deafened: false,
             speaking: false,
             speaking_at: None,
+            position: None,
+            priority_speaker: false,
+            listening_channel_ids: Vec::new(),
         }
     }
 }
@@ -392,7 +796,15 @@ struct ProtocolRoster {
     self_session: Option<u32>,
     active_channel_id: Option<u32>,
     default_channel_name: String,
+    // Channel we were in before a reconnect; preferred over the default when it still exists.
+    rejoin_channel_name: Option<String>,
+    // Off for servers that place users themselves; a reconnect still rejoins the last channel.
+    auto_join_default_channel: bool,
+    max_badge_codes: usize,
+    all_users_muted: bool,
     default_channel_join_requested: bool,
+    roster_dirty: bool,
+    last_emitted: Option<RosterEvent>,
 }
 
 impl ProtocolRoster {
@@ -403,7 +815,13 @@ impl ProtocolRoster {
             self_session: None,
             active_channel_id: None,
             default_channel_name,
+            rejoin_channel_name: None,
+            auto_join_default_channel: true,
+            max_badge_codes: DEFAULT_MAX_BADGE_CODES_PER_USER,
+            all_users_muted: false,
             default_channel_join_requested: false,
+            roster_dirty: false,
+            last_emitted: None,
         }
     }
 
@@ -434,6 +852,19 @@ impl ProtocolRoster {
         true
     }
 
+    // Servers send only a hash for long comments; the text (and its badges) needs a RequestBlob.
+    fn comment_blob_request(&mut self, msg: &msgs::UserState) -> Option<u32> {
+        if msg.has_comment() || !msg.has_comment_hash() {
+            return None;
+        }
+        let user = self.users.get_mut(&msg.get_session())?;
+        if user.requested_comment_hash == msg.get_comment_hash() {
+            return None;
+        }
+        user.requested_comment_hash = msg.get_comment_hash().to_vec();
+        Some(user.session)
+    }
+
     fn remove_channel(&mut self, channel_id: u32) -> bool {
         self.channels.remove(&channel_id).is_some()
     }
@@ -462,11 +893,17 @@ impl ProtocolRoster {
             }
         }
         if msg.has_comment() {
-            let next_badges = parse_badge_comment(msg.get_comment()).unwrap_or_default();
+            let next_badges =
+                parse_badge_comment(msg.get_comment(), self.max_badge_codes).unwrap_or_default();
             if user.badge_codes != next_badges {
                 user.badge_codes = next_badges;
                 changed = true;
             }
+            let next_meta = parse_meta_comment(msg.get_comment());
+            if user.meta != next_meta {
+                user.meta = next_meta;
+                changed = true;
+            }
         }
 
         if msg.has_channel_id() {
@@ -491,14 +928,35 @@ impl ProtocolRoster {
             changed = true;
         }
 
+        if msg.has_priority_speaker() && user.priority_speaker != msg.get_priority_speaker() {
+            user.priority_speaker = msg.get_priority_speaker();
+            changed = true;
+        }
+
+        for channel_id in listening_channel_field(msg, USER_STATE_LISTENING_CHANNEL_ADD) {
+            if let Err(index) = user.listening_channel_ids.binary_search(&channel_id) {
+                user.listening_channel_ids.insert(index, channel_id);
+                changed = true;
+            }
+        }
+        for channel_id in listening_channel_field(msg, USER_STATE_LISTENING_CHANNEL_REMOVE) {
+            if let Ok(index) = user.listening_channel_ids.binary_search(&channel_id) {
+                user.listening_channel_ids.remove(index);
+                changed = true;
+            }
+        }
+
         let mut self_event = None;
         if self.self_session == Some(session) {
             self.active_channel_id = Some(user.channel_id);
             self_event = Some(SelfEvent {
-                muted: user.muted,
+                muted: user.muted || current_self.listen_only,
                 deafened: user.deafened,
                 ptt_enabled: current_self.ptt_enabled,
+                ptt_mode: current_self.ptt_mode,
                 transmitting: current_self.transmitting,
+                listen_only: current_self.listen_only,
+                all_users_muted: current_self.all_users_muted,
             });
         }
 
@@ -509,10 +967,44 @@ impl ProtocolRoster {
         self.users.remove(&session).is_some()
     }
 
-    fn maybe_mark_speaking(&mut self, session: u32) -> Option<SpeakingEvent> {
+    fn in_target_channel(&self, session: u32) -> bool {
+        let Some(channel_id) = self.target_channel_id() else {
+            return false;
+        };
+        self.users
+            .get(&session)
+            .is_some_and(|user| user.channel_id == channel_id)
+    }
+
+    // Users seen before ServerSync are the initial state dump, not presence changes.
+    fn presence_event(&self, session: u32, joined: bool) -> Option<UserPresenceEvent> {
+        let self_session = self.self_session?;
+        if session == self_session {
+            return None;
+        }
+        let user = self.users.get(&session)?;
+        Some(UserPresenceEvent {
+            user_id: session.to_string(),
+            name: user.name.clone(),
+            joined,
+        })
+    }
+
+    fn maybe_mark_speaking(
+        &mut self,
+        session: u32,
+        position: Option<[f32; 3]>,
+    ) -> Option<SpeakingEvent> {
         let user = self.users.get_mut(&session)?;
         user.speaking_at = Some(Instant::now());
-        if user.speaking {
+        let moved = match (user.position, position) {
+            (Some(previous), Some(next)) => {
+                position_distance(previous, next) >= POSITION_EMIT_EPSILON
+            }
+            (previous, next) => previous.is_some() != next.is_some(),
+        };
+        user.position = position;
+        if user.speaking && !moved {
             return None;
         }
         user.speaking = true;
@@ -520,6 +1012,7 @@ impl ProtocolRoster {
             user_id: session.to_string(),
             speaking: true,
             level: Some(1.0),
+            position,
         })
     }
 
@@ -542,6 +1035,7 @@ impl ProtocolRoster {
                 user_id: user.session.to_string(),
                 speaking: false,
                 level: Some(0.0),
+                position: user.position,
             });
         }
         updates
@@ -555,10 +1049,30 @@ impl ProtocolRoster {
             .and_then(|session| self.users.get(&session).map(|user| user.channel_id))
     }
 
-    fn default_channel_id(&self) -> Option<u32> {
-        self.channels.iter().find_map(|(channel_id, name)| {
-            (name == &self.default_channel_name).then_some(*channel_id)
-        })
+    fn channel_id_by_name(&self, channel_name: &str) -> Option<u32> {
+        self.channels
+            .iter()
+            .find_map(|(channel_id, name)| (name == channel_name).then_some(*channel_id))
+    }
+
+    fn join_target_channel_id(&self) -> Option<u32> {
+        if let Some(rejoin) = self.rejoin_channel_name.as_deref() {
+            if let Some(channel_id) = self.channel_id_by_name(rejoin) {
+                return Some(channel_id);
+            }
+            // Every channel is known by ServerSync; only then is a missing one really gone.
+            self.self_session?;
+        }
+        if !self.auto_join_default_channel {
+            return None;
+        }
+        self.channel_id_by_name(&self.default_channel_name)
+    }
+
+    fn current_channel_name(&self) -> Option<String> {
+        self.self_session?;
+        let channel_id = self.target_channel_id()?;
+        self.channels.get(&channel_id).cloned()
     }
 
     fn build_roster_event(&self) -> RosterEvent {
@@ -577,13 +1091,22 @@ impl ProtocolRoster {
                 id: user.session.to_string(),
                 name: user.name.clone(),
                 badge_codes: user.badge_codes.clone(),
+                color: user.meta.color.clone(),
+                avatar_seed: user.meta.avatar_seed.clone(),
                 muted: user.muted,
                 deafened: user.deafened,
                 speaking: user.speaking,
+                locally_muted: self.all_users_muted && self.self_session != Some(user.session),
+                priority_speaker: user.priority_speaker,
+                listening_channel_ids: user
+                    .listening_channel_ids
+                    .iter()
+                    .map(u32::to_string)
+                    .collect(),
             })
             .collect::<Vec<_>>();
 
-        users.sort_by(|left, right| left.name.to_lowercase().cmp(&right.name.to_lowercase()));
+        users.sort_by_key(|user| user.name.to_lowercase());
 
         RosterEvent {
             channel: events::ChannelInfo {
@@ -594,6 +1117,35 @@ impl ProtocolRoster {
         }
     }
 
+    fn mark_dirty(&mut self) {
+        self.roster_dirty = true;
+    }
+
+    // Returns a roster event only when something changed since the last one emitted.
+    fn take_pending_roster_event(&mut self) -> Option<RosterEvent> {
+        if !self.roster_dirty {
+            return None;
+        }
+        self.roster_dirty = false;
+
+        let roster_event = self.build_roster_event();
+        if self.last_emitted.as_ref() == Some(&roster_event) {
+            return None;
+        }
+        self.last_emitted = Some(roster_event.clone());
+        Some(roster_event)
+    }
+
+    fn has_user(&self, session: u32) -> bool {
+        self.users.contains_key(&session)
+    }
+
+    fn is_priority_speaker(&self, session: u32) -> bool {
+        self.users
+            .get(&session)
+            .is_some_and(|user| user.priority_speaker)
+    }
+
     fn user_name_for_session(&self, session: u32) -> String {
         self.users
             .get(&session)
@@ -609,155 +1161,665 @@ struct InboundVoiceStream {
     buffered: BTreeMap<u64, Vec<u8>>,
     decoded: VecDeque<Vec<f32>>,
     last_packet_at: Option<Instant>,
+    position: Option<[f32; 3]>,
+    priority_speaker: bool,
+    stats: SpeakerStats,
 }
 
-enum DecodeAction {
-    Frame(Vec<u8>),
-    ConcealLoss,
+impl SpeakerStats {
+    fn add(&mut self, other: SpeakerStats) {
+        self.frames_decoded = self.frames_decoded.saturating_add(other.frames_decoded);
+        self.plc_frames = self.plc_frames.saturating_add(other.plc_frames);
+        self.fec_recovered = self.fec_recovered.saturating_add(other.fec_recovered);
+        self.late_frames_dropped = self
+            .late_frames_dropped
+            .saturating_add(other.late_frames_dropped);
+    }
 }
 
-#[derive(Clone, Copy)]
-struct UdpTransportStats {
-    good: u32,
-    late: u32,
-    lost: u32,
+// Per-session decoder plus the buffers it decodes into, reused across ticks.
+struct SessionDecoder {
+    decoder: OpusDecoder,
+    pcm_scratch: Vec<i16>,
+    frame_pool: Vec<Vec<f32>>,
 }
 
-struct MediaRuntime {
-    udp_socket: Option<std::net::UdpSocket>,
-    crypt_state: Option<ClientCryptState>,
-    input_capture: Option<InputCapture>,
-    input_converter: Option<MonoResampler>,
-    output_playback: Option<OutputPlayback>,
-    capture_48k: Vec<f32>,
-    soundboard_queue_48k: Vec<f32>,
-    mix_bus_48k: Vec<f32>,
-    encoder: OpusEncoder,
-    codec_tuning: CodecTuning,
-    jitter_tuning: JitterTuning,
-    decoders: HashMap<u32, OpusDecoder>,
-    inbound_streams: HashMap<u32, InboundVoiceStream>,
-    seq_num: u64,
-    transmitting: bool,
-    silence_frames: u32,
-    vad: VoiceActivityDetector,
-    muted: bool,
-    deafened: bool,
-    ptt_enabled: bool,
-    ptt_hotkey: String,
-    udp_consecutive_decrypt_failures: u32,
-    last_udp_audio_rx_at: Option<Instant>,
-    udp_degraded_until: Option<Instant>,
-    last_should_transmit: Option<bool>,
-    last_rx_arrival_at: Option<Instant>,
-    last_codec_adapt_at: Instant,
-    last_udp_stats: Option<UdpTransportStats>,
-    quality_snapshot: AudioQualityMetrics,
-    quality_shared: Arc<StdRwLock<AudioQualityMetrics>>,
+impl SessionDecoder {
+    fn new() -> Result<Self, String> {
+        let decoder = OpusDecoder::new(OPUS_SAMPLE_RATE, Channels::Mono)
+            .map_err(|err| format!("failed to create opus decoder: {err}"))?;
+        Ok(Self {
+            decoder,
+            pcm_scratch: vec![0_i16; OPUS_MAX_DECODED_SAMPLES],
+            frame_pool: Vec::with_capacity(DECODED_FRAME_POOL_LIMIT),
+        })
+    }
+
+    fn recycle_frame(&mut self, frame: Vec<f32>) {
+        if self.frame_pool.len() < DECODED_FRAME_POOL_LIMIT {
+            self.frame_pool.push(frame);
+        }
+    }
 }
 
-impl MediaRuntime {
-    fn new(
-        config: &AppConfig,
-        initial_self: &SelfEvent,
-        server_addr: SocketAddr,
-        quality_shared: Arc<StdRwLock<AudioQualityMetrics>>,
-    ) -> Result<Self, String> {
-        let codec_tuning = CodecTuning::new_from_config(config);
-        let jitter_tuning = JitterTuning::new_from_config(config);
-        let udp_socket = match create_udp_socket(server_addr) {
-            Ok(socket) => Some(socket),
-            Err(err) => {
-                log::warn!("failed to initialize UDP socket: {err}");
-                None
-            }
-        };
+// A clip currently playing on the soundboard, mixed alongside any other active clips.
+struct SoundboardVoice {
+    samples: Vec<f32>,
+    playhead: usize,
+    end: usize,
+    release_start: Option<usize>,
+}
 
-        let input_capture = match audio_in::start_input_capture(config.input_device.as_deref()) {
-            Ok(capture) => Some(capture),
-            Err(err) => {
-                log::warn!("failed to start input capture: {err}");
-                None
-            }
-        };
-        let input_converter = match input_capture.as_ref() {
-            Some(capture) => match MonoResampler::new(capture.sample_rate(), OPUS_SAMPLE_RATE) {
-                Ok(converter) => Some(converter),
-                Err(err) => {
-                    log::warn!("failed to initialize input resampler: {err}");
-                    None
-                }
-            },
-            None => None,
-        };
+impl SoundboardVoice {
+    fn new(samples: Vec<f32>) -> Self {
+        let end = samples.len();
+        Self {
+            samples,
+            playhead: 0,
+            end,
+            release_start: None,
+        }
+    }
 
-        let output_playback =
-            match audio_out::start_output_playback(config.output_device.as_deref()) {
-                Ok(playback) => Some(playback),
-                Err(err) => {
-                    log::warn!("failed to start output playback: {err}");
-                    None
-                }
+    fn is_releasing(&self) -> bool {
+        self.release_start.is_some()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.playhead >= self.end
+    }
+
+    // Cut the clip short behind a brief fade so stealing its slot doesn't click.
+    fn release(&mut self) {
+        self.release_start = Some(self.playhead);
+        self.end = self.end.min(self.playhead + SOUNDBOARD_CROSSFADE_SAMPLES);
+    }
+
+    fn mix_into(&mut self, frame: &mut [f32], gain: f32) -> bool {
+        let take = self.end.saturating_sub(self.playhead).min(frame.len());
+        for (offset, out) in frame[..take].iter_mut().enumerate() {
+            let position = self.playhead + offset;
+            let envelope = match self.release_start {
+                Some(start) => (self.end - position) as f32 / (self.end - start) as f32,
+                None => 1.0,
             };
+            *out += self.samples[position] * gain * envelope;
+        }
+        self.playhead += take;
+        take > 0
+    }
+}
 
-        let mut encoder = OpusEncoder::new(OPUS_SAMPLE_RATE, Channels::Mono, Application::Voip)
-            .map_err(|err| format!("failed to create opus encoder: {err}"))?;
-        configure_encoder(&mut encoder, codec_tuning)
-            .map_err(|err| format!("failed to configure opus encoder: {err}"))?;
+enum DecodeAction {
+    Frame(Vec<u8>),
+    // Rebuild the lost frame from the in-band FEC carried by the frame after it.
+    RecoverWithFec(Vec<u8>),
+    ConcealLoss,
+}
 
-        let mut quality_snapshot = AudioQualityMetrics {
-            connected: true,
-            tx_bitrate_bps: codec_tuning.current_bitrate_bps,
-            tx_packet_loss_percent: codec_tuning.current_packet_loss_pct,
-            rx_jitter_target_frames: jitter_tuning.target_frames,
-            rx_jitter_max_frames: jitter_tuning.max_frames,
-            ..AudioQualityMetrics::default()
-        };
-        if let Some(capture) = input_capture.as_ref() {
-            quality_snapshot.input_device_name = Some(capture.device_name().to_string());
-            quality_snapshot.input_sample_rate = Some(capture.sample_rate());
+// The server's own mute, suppress and deafen flags for us, kept across partial UserStates.
+#[derive(Debug, Default, Clone, Copy)]
+struct ServerEnforcement {
+    mute: bool,
+    suppress: bool,
+    deaf: bool,
+}
+
+impl ServerEnforcement {
+    fn apply(&mut self, msg: &msgs::UserState) {
+        if msg.has_mute() {
+            self.mute = msg.get_mute();
+        }
+        if msg.has_suppress() {
+            self.suppress = msg.get_suppress();
         }
-        if let Some(playback) = output_playback.as_ref() {
-            quality_snapshot.output_device_name = Some(playback.device_name().to_string());
-            quality_snapshot.output_sample_rate = Some(playback.sample_rate());
+        if msg.has_deaf() {
+            self.deaf = msg.get_deaf();
         }
+    }
 
-        if let Ok(mut shared) = quality_shared.write() {
-            *shared = quality_snapshot.clone();
+    fn against_intent(self, muted: bool, deafened: bool) -> SelfEnforcedEvent {
+        SelfEnforcedEvent {
+            muted: (self.mute || self.suppress) && !muted,
+            deafened: self.deaf && !deafened,
         }
+    }
+}
 
-        Ok(Self {
-            udp_socket,
-            crypt_state: None,
-            input_capture,
-            input_converter,
-            output_playback,
-            capture_48k: Vec::with_capacity(OPUS_FRAME_SAMPLES * 8),
-            soundboard_queue_48k: Vec::with_capacity(OPUS_FRAME_SAMPLES * 8),
-            mix_bus_48k: vec![0.0_f32; OPUS_FRAME_SAMPLES],
+// Fires once per continuous stretch of speech while muted, at most once per cooldown.
+#[derive(Debug, Default)]
+struct MutedSpeechDetector {
+    speech_frames: u32,
+    cooldown_frames: u32,
+}
+
+impl MutedSpeechDetector {
+    fn observe(&mut self, muted: bool, above_threshold: bool) -> bool {
+        self.cooldown_frames = self.cooldown_frames.saturating_sub(1);
+        if !muted || !above_threshold {
+            self.speech_frames = 0;
+            return false;
+        }
+        self.speech_frames = self.speech_frames.saturating_add(1);
+        if self.speech_frames != MUTED_SPEECH_WARN_FRAMES || self.cooldown_frames > 0 {
+            return false;
+        }
+        self.cooldown_frames = MUTED_SPEECH_COOLDOWN_FRAMES;
+        true
+    }
+}
+
+// Watches the server's count of good UDP packets from us, reported in its ping replies.
+#[derive(Debug, Default)]
+struct OneWayAudioDetector {
+    last_tx_udp_packets: u64,
+    last_server_good: Option<u32>,
+    unacked_windows: u32,
+    warned: bool,
+}
+
+impl OneWayAudioDetector {
+    // Returns true once when we keep sending UDP voice the server never counts.
+    fn observe(&mut self, tx_udp_packets: u64, server_good: Option<u32>) -> bool {
+        let tx_delta = tx_udp_packets.saturating_sub(self.last_tx_udp_packets);
+        self.last_tx_udp_packets = tx_udp_packets;
+        let Some(server_good) = server_good else {
+            return false;
+        };
+        let previous_good = self.last_server_good.replace(server_good);
+        if previous_good.map_or(true, |previous| server_good > previous) {
+            self.unacked_windows = 0;
+            self.warned = false;
+            return false;
+        }
+        if tx_delta == 0 {
+            return false;
+        }
+        self.unacked_windows = self.unacked_windows.saturating_add(1);
+        if self.unacked_windows < ONE_WAY_AUDIO_WINDOWS || self.warned {
+            return false;
+        }
+        self.warned = true;
+        true
+    }
+}
+
+// Drops one mixed frame at a time while the output queue sits above its high-water mark, so
+// clock drift between the media tick and the device cannot keep adding latency.
+#[derive(Debug, Default)]
+struct OutputLatencyDrain {
+    over_high_water_mixes: u32,
+}
+
+impl OutputLatencyDrain {
+    fn should_drop(&mut self, queued_samples: usize, high_water: usize, quiet: bool) -> bool {
+        if queued_samples <= high_water {
+            self.over_high_water_mixes = 0;
+            return false;
+        }
+        self.over_high_water_mixes = self.over_high_water_mixes.saturating_add(1);
+        let due = if quiet {
+            OUTPUT_LATENCY_QUIET_DROP_MIXES
+        } else {
+            OUTPUT_LATENCY_FORCED_DROP_MIXES
+        };
+        if self.over_high_water_mixes < due {
+            return false;
+        }
+        self.over_high_water_mixes = 0;
+        true
+    }
+}
+
+// Holds the newest device request until requests stop for the debounce window, so a UI that
+// fires on every hover rebuilds the stream once instead of once per event.
+#[derive(Debug, Default)]
+struct DeviceSwitchDebounce {
+    pending: Option<(String, Instant)>,
+}
+
+impl DeviceSwitchDebounce {
+    fn request(&mut self, device_id: String, active: Option<&str>, now: Instant) {
+        if active == Some(device_id.as_str()) {
+            self.pending = None;
+            return;
+        }
+        self.pending = Some((
+            device_id,
+            now + Duration::from_millis(DEVICE_SWITCH_DEBOUNCE_MS),
+        ));
+    }
+
+    fn take_due(&mut self, now: Instant) -> Option<String> {
+        if self.pending.as_ref().is_some_and(|(_, due)| now >= *due) {
+            self.pending.take().map(|(device_id, _)| device_id)
+        } else {
+            None
+        }
+    }
+}
+
+// Accumulates wire bytes between samples and reports the rate since the previous one.
+#[derive(Debug)]
+struct BandwidthMeter {
+    bytes: u64,
+    window_started: Instant,
+}
+
+impl BandwidthMeter {
+    fn new(now: Instant) -> Self {
+        Self {
+            bytes: 0,
+            window_started: now,
+        }
+    }
+
+    fn record(&mut self, bytes: usize) {
+        self.bytes = self.bytes.saturating_add(bytes as u64);
+    }
+
+    fn sample_kbps(&mut self, now: Instant) -> f32 {
+        let elapsed = now
+            .saturating_duration_since(self.window_started)
+            .as_secs_f32();
+        let kbps = if elapsed > 0.0 {
+            self.bytes as f32 * 8.0 / 1_000.0 / elapsed
+        } else {
+            0.0
+        };
+        self.bytes = 0;
+        self.window_started = now;
+        kbps
+    }
+}
+
+// Session, sequence number and Opus payload.
+type InboundFrame = (u32, u64, Vec<u8>);
+
+// Token bucket refilled evenly, so a full burst takes the whole window to earn back.
+#[derive(Debug)]
+struct TextRateLimiter {
+    tokens: f32,
+    last_refill: Instant,
+}
+
+impl TextRateLimiter {
+    fn new(now: Instant) -> Self {
+        Self {
+            tokens: TEXT_RATE_LIMIT_MESSAGES as f32,
+            last_refill: now,
+        }
+    }
+
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f32();
+        let refill_per_sec = TEXT_RATE_LIMIT_MESSAGES as f32 / TEXT_RATE_LIMIT_WINDOW_SECS as f32;
+        self.tokens = (self.tokens + elapsed * refill_per_sec).min(TEXT_RATE_LIMIT_MESSAGES as f32);
+        self.last_refill = now;
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+#[derive(Clone, Copy)]
+struct UdpTransportStats {
+    good: u32,
+    late: u32,
+    lost: u32,
+}
+
+struct MediaRuntime {
+    udp_socket: Option<std::net::UdpSocket>,
+    crypt_state: Option<ClientCryptState>,
+    input_capture: Option<InputCapture>,
+    input_converter: Option<MonoResampler>,
+    output_playback: Option<OutputPlayback>,
+    input_device_id: Option<String>,
+    output_device_id: Option<String>,
+    audio_host: Option<String>,
+    output_buffer: OutputBufferConfig,
+    output_resampler_quality: ResamplerQuality,
+    // Read whenever a device is opened; a change applies from the next open.
+    prefer_48k: bool,
+    pending_audio_warnings: Vec<AudioWarning>,
+    capture_48k: Vec<f32>,
+    soundboard_voices: Vec<SoundboardVoice>,
+    soundboard_mix: SoundboardConfig,
+    cue_clips: CueClips,
+    mix_bus_48k: Vec<f32>,
+    mix_bus_left_48k: Vec<f32>,
+    mix_bus_right_48k: Vec<f32>,
+    stereo_spread: bool,
+    priority_duck_gain: f32,
+    tx_limiter: bool,
+    self_position: Option<[f32; 3]>,
+    pcm_scratch: Vec<i16>,
+    packet_scratch: Vec<u8>,
+    silence_frame: Vec<f32>,
+    encoder: OpusEncoder,
+    codec_tuning: CodecTuning,
+    jitter_tuning: JitterTuning,
+    adaptive_enabled: bool,
+    bitrate_ceiling_bps: Option<i32>,
+    recorder: Option<VoiceRecorder>,
+    mic_sample: Option<VoiceRecorder>,
+    udp_rtt_ms: Option<f32>,
+    tcp_rtt_ms: Option<f32>,
+    mic_level_smoothed: f32,
+    last_mic_level_emit_at: Instant,
+    output_level_smoothed: f32,
+    output_peak: f32,
+    last_output_level_emit_at: Instant,
+    muted_speech: MutedSpeechDetector,
+    server_enforcement: ServerEnforcement,
+    last_enforced: SelfEnforcedEvent,
+    decoders: HashMap<u32, SessionDecoder>,
+    udp_rx_scratch: BytesMut,
+    inbound_streams: HashMap<u32, InboundVoiceStream>,
+    seq_num: u64,
+    transmitting: bool,
+    silence_frames: u32,
+    vad: VoiceActivityDetector,
+    muted: bool,
+    deafened: bool,
+    all_users_muted: bool,
+    listen_only: bool,
+    ptt_mode: PttMode,
+    ptt_key_held: bool,
+    ptt_hotkey: String,
+    udp_consecutive_decrypt_failures: u32,
+    udp_consecutive_send_failures: u32,
+    input_stalled_ticks: u32,
+    server_addr: SocketAddr,
+    last_udp_audio_rx_at: Option<Instant>,
+    udp_degraded_until: Option<Instant>,
+    last_should_transmit: Option<bool>,
+    last_rx_arrival_at: Option<Instant>,
+    last_codec_adapt_at: Instant,
+    last_udp_stats: Option<UdpTransportStats>,
+    server_udp_good: Option<u32>,
+    one_way_audio: OneWayAudioDetector,
+    output_latency_drain: OutputLatencyDrain,
+    input_device_switch: DeviceSwitchDebounce,
+    output_device_switch: DeviceSwitchDebounce,
+    encode_timed_frames: u64,
+    decode_timed_frames: u64,
+    tx_bandwidth: BandwidthMeter,
+    rx_bandwidth: BandwidthMeter,
+    loss_injector: Option<LossInjector<InboundFrame>>,
+    quality_snapshot: AudioQualityMetrics,
+    quality_shared: Arc<StdRwLock<AudioQualityMetrics>>,
+}
+
+impl MediaRuntime {
+    fn new(
+        config: &AppConfig,
+        initial_self: &SelfEvent,
+        server_addr: SocketAddr,
+        quality_shared: Arc<StdRwLock<AudioQualityMetrics>>,
+        cue_clips: CueClips,
+    ) -> Result<Self, String> {
+        let codec_tuning = CodecTuning::new_from_config(config);
+        let jitter_tuning = JitterTuning::new_from_config(config);
+        // Without a socket every UDP path (send, ping, receive, degrade) is skipped.
+        let udp_socket = if config.server.force_tcp_tunnel {
+            log::info!("udp disabled by force_tcp_tunnel; tunneling voice over tcp");
+            None
+        } else {
+            match create_udp_socket(server_addr) {
+                Ok(socket) => Some(socket),
+                Err(err) => {
+                    log::warn!("failed to initialize UDP socket: {err}");
+                    None
+                }
+            }
+        };
+
+        let mut encoder = OpusEncoder::new(OPUS_SAMPLE_RATE, Channels::Mono, Application::Voip)
+            .map_err(|err| format!("failed to create opus encoder: {err}"))?;
+        configure_encoder(&mut encoder, codec_tuning)
+            .map_err(|err| format!("failed to configure opus encoder: {err}"))?;
+
+        let quality_snapshot = AudioQualityMetrics {
+            connected: true,
+            tx_bitrate_bps: codec_tuning.current_bitrate_bps,
+            tx_packet_loss_percent: codec_tuning.current_packet_loss_pct,
+            rx_jitter_target_frames: jitter_tuning.target_frames,
+            rx_jitter_max_frames: jitter_tuning.max_frames,
+            ..AudioQualityMetrics::default()
+        };
+
+        let mut runtime = Self {
+            udp_socket,
+            crypt_state: None,
+            input_capture: None,
+            input_converter: None,
+            output_playback: None,
+            input_device_id: config.input_device.clone(),
+            output_device_id: config.output_device.clone(),
+            audio_host: config.audio_host.clone(),
+            output_buffer: OutputBufferConfig::new(
+                config.voice_quality.output_buffer_ms,
+                config.voice_quality.output_prefill_ms,
+            ),
+            output_resampler_quality: output_resampler_quality(&config.voice_quality),
+            prefer_48k: config.voice_quality.prefer_48k,
+            pending_audio_warnings: Vec::new(),
+            capture_48k: Vec::with_capacity(OPUS_FRAME_SAMPLES * 8),
+            soundboard_voices: Vec::with_capacity(SOUNDBOARD_MAX_VOICES + 1),
+            soundboard_mix: config.soundboard,
+            cue_clips,
+            mix_bus_48k: vec![0.0_f32; OPUS_FRAME_SAMPLES],
+            mix_bus_left_48k: vec![0.0_f32; OPUS_FRAME_SAMPLES],
+            mix_bus_right_48k: vec![0.0_f32; OPUS_FRAME_SAMPLES],
+            stereo_spread: config.voice_quality.stereo_spread,
+            priority_duck_gain: config.voice_quality.priority_duck_gain,
+            tx_limiter: config.voice_quality.tx_limiter,
+            self_position: None,
+            pcm_scratch: Vec::with_capacity(OPUS_FRAME_SAMPLES),
+            packet_scratch: vec![0_u8; OPUS_MAX_PACKET_SIZE],
+            silence_frame: vec![0.0_f32; OPUS_FRAME_SAMPLES],
             encoder,
             codec_tuning,
             jitter_tuning,
+            adaptive_enabled: config.voice_quality.adaptive_enabled,
+            bitrate_ceiling_bps: None,
+            recorder: None,
+            mic_sample: None,
+            udp_rtt_ms: None,
+            tcp_rtt_ms: None,
+            mic_level_smoothed: 0.0,
+            last_mic_level_emit_at: Instant::now(),
+            output_level_smoothed: 0.0,
+            output_peak: 0.0,
+            last_output_level_emit_at: Instant::now(),
+            muted_speech: MutedSpeechDetector::default(),
+            server_enforcement: ServerEnforcement::default(),
+            last_enforced: SelfEnforcedEvent::default(),
             decoders: HashMap::new(),
+            udp_rx_scratch: BytesMut::with_capacity(UDP_RX_BUFFER_BYTES),
             inbound_streams: HashMap::new(),
             seq_num: 0,
             transmitting: false,
             silence_frames: 0,
-            vad: VoiceActivityDetector::new(VAD_THRESHOLD),
-            muted: initial_self.muted,
+            vad: VoiceActivityDetector::with_settings(VadSettings::new(&config.voice_quality)),
+            muted: initial_self.muted || initial_self.listen_only,
             deafened: initial_self.deafened,
-            ptt_enabled: initial_self.ptt_enabled,
+            all_users_muted: initial_self.all_users_muted,
+            listen_only: initial_self.listen_only,
+            ptt_mode: initial_self.ptt_mode,
+            ptt_key_held: false,
             ptt_hotkey: config.ptt_hotkey.clone(),
             udp_consecutive_decrypt_failures: 0,
+            udp_consecutive_send_failures: 0,
+            input_stalled_ticks: 0,
+            server_addr,
             last_udp_audio_rx_at: None,
             udp_degraded_until: None,
             last_should_transmit: None,
             last_rx_arrival_at: None,
             last_codec_adapt_at: Instant::now(),
             last_udp_stats: None,
+            server_udp_good: None,
+            one_way_audio: OneWayAudioDetector::default(),
+            output_latency_drain: OutputLatencyDrain::default(),
+            input_device_switch: DeviceSwitchDebounce::default(),
+            output_device_switch: DeviceSwitchDebounce::default(),
+            encode_timed_frames: 0,
+            decode_timed_frames: 0,
+            tx_bandwidth: BandwidthMeter::new(Instant::now()),
+            rx_bandwidth: BandwidthMeter::new(Instant::now()),
+            loss_injector: None,
             quality_snapshot,
             quality_shared,
-        })
+        };
+        runtime.pending_audio_warnings = runtime.acquire_missing_audio();
+        Ok(runtime)
+    }
+
+    // Starts whichever devices aren't running yet; returns what is still unavailable.
+    fn acquire_missing_audio(&mut self) -> Vec<AudioWarning> {
+        let mut warnings = Vec::new();
+
+        let needs_devices =
+            (self.input_capture.is_none() && !self.listen_only) || self.output_playback.is_none();
+        if let Some(host_id) = self
+            .audio_host
+            .as_deref()
+            .filter(|host_id| needs_devices && canonical_audio_host_id(host_id).is_none())
+        {
+            warnings.push(AudioWarning {
+                kind: AudioWarningKind::SavedHostUnavailable,
+                detail: format!("audio host \"{host_id}\" is unavailable; using the default host"),
+            });
+        }
+
+        // Listen-only sessions never open a microphone.
+        if self.input_capture.is_none() && !self.listen_only {
+            match audio_in::start_input_capture(
+                self.input_device_id.as_deref(),
+                self.prefer_48k,
+                self.audio_host.as_deref(),
+            ) {
+                Ok(capture) => {
+                    if let Some(missing) = capture.missing_device_id() {
+                        warnings.push(AudioWarning {
+                            kind: AudioWarningKind::SavedInputUnavailable,
+                            detail: format!(
+                                "input device \"{missing}\" is unavailable; using \"{}\"",
+                                capture.device_name()
+                            ),
+                        });
+                    }
+                    self.quality_snapshot.input_device_name =
+                        Some(capture.device_name().to_string());
+                    self.quality_snapshot.input_sample_rate = Some(capture.sample_rate());
+                    self.input_converter = None;
+                    self.input_capture = Some(capture);
+                }
+                Err(err) => {
+                    log::warn!("failed to start input capture: {err}");
+                    warnings.push(AudioWarning {
+                        kind: AudioWarningKind::NoInputDevice,
+                        detail: err,
+                    });
+                }
+            }
+        }
+        if let Some(capture) = self.input_capture.as_ref() {
+            if self.input_converter.is_none() {
+                match MonoResampler::new(
+                    capture.sample_rate(),
+                    OPUS_SAMPLE_RATE,
+                    ResamplerQuality::Standard,
+                ) {
+                    Ok(converter) => self.input_converter = Some(converter),
+                    Err(err) => {
+                        log::warn!("failed to initialize input resampler: {err}");
+                        warnings.push(AudioWarning {
+                            kind: AudioWarningKind::ResamplerFailed,
+                            detail: err,
+                        });
+                    }
+                }
+            }
+        }
+
+        if self.output_playback.is_none() {
+            match audio_out::start_output_playback(
+                self.output_device_id.as_deref(),
+                self.output_buffer,
+                self.output_resampler_quality,
+                self.prefer_48k,
+                self.audio_host.as_deref(),
+            ) {
+                Ok(playback) => {
+                    if let Some(missing) = playback.missing_device_id() {
+                        warnings.push(AudioWarning {
+                            kind: AudioWarningKind::SavedOutputUnavailable,
+                            detail: format!(
+                                "output device \"{missing}\" is unavailable; using \"{}\"",
+                                playback.device_name()
+                            ),
+                        });
+                    }
+                    self.quality_snapshot.output_device_name =
+                        Some(playback.device_name().to_string());
+                    self.quality_snapshot.output_sample_rate = Some(playback.sample_rate());
+                    self.output_playback = Some(playback);
+                }
+                Err(err) => {
+                    log::warn!("failed to start output playback: {err}");
+                    warnings.push(AudioWarning {
+                        kind: AudioWarningKind::NoOutputDevice,
+                        detail: err,
+                    });
+                }
+            }
+        }
+
+        self.publish_quality_snapshot();
+        warnings
+    }
+
+    fn restart_stalled_input(&mut self, app: &AppHandle) {
+        self.input_stalled_ticks = 0;
+        log::warn!("input stream stopped delivering audio; restarting capture");
+        self.input_capture = None;
+        self.input_converter = None;
+        let warnings = self.acquire_missing_audio();
+        if self.input_capture.is_some() {
+            self.quality_snapshot.input_stream_restarts = self
+                .quality_snapshot
+                .input_stream_restarts
+                .saturating_add(1);
+            return;
+        }
+        let _ = events::emit_audio_warning(app, &AudioWarningEvent { warnings });
+    }
+
+    // Both streams are reopened on the new host, where saved devices are matched by name again.
+    fn set_audio_host(&mut self, app: &AppHandle, audio_host: Option<String>) {
+        if self.audio_host == audio_host {
+            return;
+        }
+        self.audio_host = audio_host;
+        self.input_capture = None;
+        self.input_converter = None;
+        self.output_playback = None;
+        self.retry_audio(app);
+    }
+
+    fn retry_audio(&mut self, app: &AppHandle) {
+        let payload = AudioWarningEvent {
+            warnings: self.acquire_missing_audio(),
+        };
+        let _ = events::emit_audio_warning(app, &payload);
     }
 
     fn apply_crypt_setup(
@@ -808,41 +1870,215 @@ impl MediaRuntime {
     }
 
     fn set_deafened(&mut self, deafened: bool) {
+        if deafened && !self.deafened {
+            // Drop buffered inbound audio so undeafening starts from fresh packets.
+            self.inbound_streams.clear();
+            self.decoders.clear();
+            if let Some(output) = &self.output_playback {
+                output.clear();
+            }
+        }
         self.deafened = deafened;
     }
 
-    fn set_ptt(&mut self, enabled: bool) {
-        self.ptt_enabled = enabled;
+    fn set_all_users_muted(&mut self, muted: bool) {
+        if muted && !self.all_users_muted {
+            self.inbound_streams.clear();
+            self.decoders.clear();
+            if let Some(output) = &self.output_playback {
+                output.clear();
+            }
+        }
+        self.all_users_muted = muted;
+    }
+
+    fn set_ptt_mode(&mut self, mode: PttMode) {
+        self.ptt_mode = mode;
+    }
+
+    // Cues are local only and go straight to the output queue, never to the encoder.
+    fn play_cue(&self, clip: Option<&Arc<[f32]>>) {
+        if self.deafened {
+            return;
+        }
+        if let (Some(samples), Some(output)) = (clip, &self.output_playback) {
+            output.push_mono_48k(samples);
+        }
     }
 
     fn set_ptt_hotkey(&mut self, hotkey: String) {
         self.ptt_hotkey = hotkey;
     }
 
-    fn enqueue_soundboard_samples(&mut self, mut samples_48k: Vec<f32>) {
-        if samples_48k.is_empty() {
+    fn set_jitter_buffer(&mut self, target_frames: usize, max_frames: usize) {
+        // Adaptive tuning rebuilds from the baseline on its next interval.
+        self.jitter_tuning = JitterTuning::new(target_frames, max_frames)
+            .with_prefill(self.jitter_tuning.prefill_frames);
+        self.quality_snapshot.rx_jitter_target_frames = self.jitter_tuning.target_frames;
+        self.quality_snapshot.rx_jitter_max_frames = self.jitter_tuning.max_frames;
+    }
+
+    fn set_adaptive_enabled(&mut self, enabled: bool) {
+        self.adaptive_enabled = enabled;
+        if !enabled {
+            self.reset_adaptive_tuning();
+        }
+    }
+
+    fn set_vad(&mut self, settings: VadSettings) {
+        self.vad.apply(settings);
+        log::info!(
+            "vad updated: on_threshold={:.5} off_threshold={:.5} hold_frames={} attack_frames={} smoothing={:.2}",
+            self.vad.on_threshold(),
+            self.vad.off_threshold(),
+            self.vad.hold_frames(),
+            self.vad.attack_frames(),
+            self.vad.smoothing()
+        );
+    }
+
+    fn set_complexity(&mut self, complexity: i32) {
+        let complexity = complexity.clamp(OPUS_COMPLEXITY_MIN, OPUS_COMPLEXITY_MAX);
+        if let Err(err) = self.encoder.set_complexity(complexity) {
+            log::warn!("opus complexity update failed: {err}");
+            return;
+        }
+        self.codec_tuning.complexity = complexity;
+        log::info!("opus complexity set to {complexity}");
+    }
+
+    fn apply_voice_quality(&mut self, quality: &VoiceQualityConfig) {
+        let mut tuning = CodecTuning::new(quality);
+        if let Some(ceiling) = self.bitrate_ceiling_bps {
+            tuning.current_bitrate_bps = tuning.current_bitrate_bps.min(ceiling);
+        }
+        if let Err(err) = configure_encoder(&mut self.encoder, tuning) {
+            log::warn!("opus reconfigure failed: {err}");
+        } else {
+            self.codec_tuning = tuning;
+            self.quality_snapshot.tx_bitrate_bps = tuning.current_bitrate_bps;
+            self.quality_snapshot.tx_packet_loss_percent = tuning.current_packet_loss_pct;
+        }
+
+        self.jitter_tuning.prefill_frames = quality.initial_prefill_frames;
+        self.set_jitter_buffer(quality.jitter_target_frames, quality.jitter_max_frames);
+        self.set_adaptive_enabled(quality.adaptive_enabled);
+        self.set_vad(VadSettings::new(quality));
+        self.stereo_spread = quality.stereo_spread;
+        self.priority_duck_gain = quality.priority_duck_gain;
+        self.prefer_48k = quality.prefer_48k;
+        self.tx_limiter = quality.tx_limiter;
+        let output_buffer =
+            OutputBufferConfig::new(quality.output_buffer_ms, quality.output_prefill_ms);
+        let resampler_quality = output_resampler_quality(quality);
+        if output_buffer != self.output_buffer || resampler_quality != self.output_resampler_quality
+        {
+            self.output_buffer = output_buffer;
+            self.output_resampler_quality = resampler_quality;
+            // Queue size and resamplers are fixed per stream, so the output has to be reopened.
+            if self.output_playback.take().is_some() {
+                for warning in self.acquire_missing_audio() {
+                    log::warn!(
+                        "output reopen after buffer change failed: {}",
+                        warning.detail
+                    );
+                }
+            }
+        }
+        self.publish_quality_snapshot();
+        log::info!(
+            "voice quality applied: bitrate={} packet_loss={} fec={} complexity={}",
+            self.codec_tuning.current_bitrate_bps,
+            self.codec_tuning.current_packet_loss_pct,
+            self.codec_tuning.inband_fec,
+            self.codec_tuning.complexity
+        );
+    }
+
+    fn set_server_max_bandwidth(&mut self, max_bandwidth_bps: u32) {
+        let ceiling = bitrate_ceiling_for_bandwidth(max_bandwidth_bps);
+        if self.bitrate_ceiling_bps == Some(ceiling) {
             return;
         }
-        if self.soundboard_queue_48k.len() >= SOUNDBOARD_QUEUE_LIMIT_SAMPLES {
-            self.soundboard_queue_48k.clear();
+        self.bitrate_ceiling_bps = Some(ceiling);
+        self.quality_snapshot.tx_bitrate_ceiling_bps = ceiling;
+        if ceiling < self.codec_tuning.baseline_bitrate_bps {
+            log::info!(
+                "clamping opus bitrate to {ceiling} bps for server max bandwidth {max_bandwidth_bps} bps"
+            );
+        }
+        self.apply_codec_tuning_if_changed(
+            self.codec_tuning.baseline_bitrate_bps,
+            self.codec_tuning.current_packet_loss_pct,
+        );
+    }
+
+    fn enqueue_soundboard_samples(&mut self, samples_48k: Vec<f32>) {
+        if samples_48k.is_empty() || self.listen_only {
+            return;
+        }
+        start_soundboard_voice(&mut self.soundboard_voices, samples_48k);
+    }
+
+    // A device counts as active only while its stream is open, so re-selecting a device that
+    // failed to start still retries it.
+    fn active_input_device(&self) -> Option<&str> {
+        self.input_capture
+            .as_ref()
+            .and(self.input_device_id.as_deref())
+    }
+
+    fn active_output_device(&self) -> Option<&str> {
+        self.output_playback
+            .as_ref()
+            .and(self.output_device_id.as_deref())
+    }
+
+    fn request_input_device(&mut self, device_id: String, now: Instant) {
+        let active = self.active_input_device().map(str::to_owned);
+        self.input_device_switch
+            .request(device_id, active.as_deref(), now);
+    }
+
+    fn request_output_device(&mut self, device_id: String, now: Instant) {
+        let active = self.active_output_device().map(str::to_owned);
+        self.output_device_switch
+            .request(device_id, active.as_deref(), now);
+    }
+
+    fn apply_due_device_switches(&mut self, now: Instant) {
+        if let Some(device_id) = self.input_device_switch.take_due(now) {
+            self.set_input_device(device_id);
         }
-        let available = SOUNDBOARD_QUEUE_LIMIT_SAMPLES
-            .saturating_sub(self.soundboard_queue_48k.len());
-        if samples_48k.len() > available {
-            let drop_count = samples_48k.len() - available;
-            samples_48k.drain(..drop_count);
+        if let Some(device_id) = self.output_device_switch.take_due(now) {
+            self.set_output_device(device_id);
         }
-        self.soundboard_queue_48k.extend(samples_48k);
     }
 
     fn set_input_device(&mut self, device_id: String) {
-        match audio_in::start_input_capture(Some(device_id.as_str())) {
+        if self.active_input_device() == Some(device_id.as_str()) {
+            return;
+        }
+        self.input_device_id = Some(device_id.clone());
+        if self.listen_only {
+            return;
+        }
+        match audio_in::start_input_capture(
+            Some(device_id.as_str()),
+            self.prefer_48k,
+            self.audio_host.as_deref(),
+        ) {
             Ok(capture) => {
-                self.input_converter = match MonoResampler::new(capture.sample_rate(), OPUS_SAMPLE_RATE)
-                {
+                self.input_converter = match MonoResampler::new(
+                    capture.sample_rate(),
+                    OPUS_SAMPLE_RATE,
+                    ResamplerQuality::Standard,
+                ) {
                     Ok(converter) => Some(converter),
                     Err(err) => {
-                        log::warn!("failed to initialize input resampler after device switch: {err}");
+                        log::warn!(
+                            "failed to initialize input resampler after device switch: {err}"
+                        );
                         None
                     }
                 };
@@ -857,16 +2093,40 @@ impl MediaRuntime {
         }
     }
 
+    // The new stream is opened before the old one is dropped, so a failed switch keeps the
+    // current output. The mix bus is pushed within the tick that fills it, so nothing is lost
+    // between streams beyond what was already queued for the old device.
     fn set_output_device(&mut self, device_id: String) {
-        match audio_out::start_output_playback(Some(device_id.as_str())) {
+        if self.active_output_device() == Some(device_id.as_str()) {
+            return;
+        }
+        match audio_out::start_output_playback(
+            Some(device_id.as_str()),
+            self.output_buffer,
+            self.output_resampler_quality,
+            self.prefer_48k,
+            self.audio_host.as_deref(),
+        ) {
             Ok(playback) => {
+                self.output_device_id = Some(device_id);
                 self.quality_snapshot.output_device_name = Some(playback.device_name().to_string());
                 self.quality_snapshot.output_sample_rate = Some(playback.sample_rate());
-                self.output_playback = Some(playback);
+                self.quality_snapshot.output_device_switches = self
+                    .quality_snapshot
+                    .output_device_switches
+                    .saturating_add(1);
+                if let Some(previous) = self.output_playback.replace(playback) {
+                    previous.clear();
+                }
                 self.publish_quality_snapshot();
             }
             Err(err) => {
-                log::warn!("failed to switch output device: {err}");
+                let keeping = if self.output_playback.is_some() {
+                    "keeping current output"
+                } else {
+                    "no output available"
+                };
+                log::warn!("failed to switch output device to {device_id}: {err}; {keeping}");
             }
         }
     }
@@ -890,6 +2150,7 @@ impl MediaRuntime {
         self.send_udp_packet(VoicePacket::Ping {
             timestamp: epoch_millis(),
         })
+        .map(|_| ())
     }
 
     fn poll_udp_inbound(
@@ -902,8 +2163,14 @@ impl MediaRuntime {
         }
 
         let mut roster_changed = false;
+        let mut received = 0_usize;
+        let mut buf = [0_u8; UDP_RX_BUFFER_BYTES];
         loop {
-            let mut buf = [0_u8; 2048];
+            if received >= UDP_RX_MAX_PACKETS_PER_TICK {
+                self.quality_snapshot.rx_packets_deferred =
+                    self.quality_snapshot.rx_packets_deferred.saturating_add(1);
+                break;
+            }
             let len = {
                 let Some(socket) = self.udp_socket.as_ref() else {
                     return Ok(roster_changed);
@@ -914,13 +2181,15 @@ impl MediaRuntime {
                     Err(err) => return Err(format!("udp receive failed: {err}")),
                 }
             };
+            received += 1;
 
-            let mut encrypted = BytesMut::from(&buf[..len]);
+            self.rx_bandwidth.record(len + UDP_IP_HEADER_BYTES);
+            refill_udp_scratch(&mut self.udp_rx_scratch, &buf[..len]);
             let decrypt_result = {
                 let Some(crypt) = self.crypt_state.as_mut() else {
                     continue;
                 };
-                crypt.decrypt(&mut encrypted)
+                crypt.decrypt(&mut self.udp_rx_scratch)
             };
             let packet = match decrypt_result {
                 Ok(Ok(packet)) => {
@@ -941,7 +2210,8 @@ impl MediaRuntime {
 
             match packet {
                 VoicePacket::Ping { timestamp } => {
-                    let _ = self.send_udp_packet(VoicePacket::Ping { timestamp });
+                    let sample = epoch_millis().saturating_sub(timestamp) as f32;
+                    self.udp_rtt_ms = Some(smooth_rtt(self.udp_rtt_ms, sample));
                 }
                 VoicePacket::Audio { .. } => {
                     if self.handle_incoming_voice(packet, app, roster)? {
@@ -964,6 +2234,14 @@ impl MediaRuntime {
         let mut drained = Vec::new();
         if let Some(capture) = &self.input_capture {
             capture.drain_samples(&mut drained);
+            if drained.is_empty() {
+                self.input_stalled_ticks = self.input_stalled_ticks.saturating_add(1);
+            } else {
+                self.input_stalled_ticks = 0;
+            }
+        }
+        if self.input_stalled_ticks >= INPUT_STALL_TICKS {
+            self.restart_stalled_input(app);
         }
 
         if !drained.is_empty() {
@@ -978,48 +2256,29 @@ impl MediaRuntime {
         }
 
         let mut sent_voice_frame = false;
-        while self.capture_48k.len() >= OPUS_FRAME_SAMPLES || !self.soundboard_queue_48k.is_empty()
-        {
+        while self.capture_48k.len() >= OPUS_FRAME_SAMPLES || !self.soundboard_voices.is_empty() {
             let mut frame = if self.capture_48k.len() >= OPUS_FRAME_SAMPLES {
-                self.capture_48k
+                let captured = self
+                    .capture_48k
                     .drain(..OPUS_FRAME_SAMPLES)
-                    .collect::<Vec<f32>>()
+                    .collect::<Vec<f32>>();
+                self.record_mic_sample_frame(&captured);
+                captured
             } else {
                 vec![0.0_f32; OPUS_FRAME_SAMPLES]
             };
-            let soundboard_take = self.soundboard_queue_48k.len().min(OPUS_FRAME_SAMPLES);
-            if soundboard_take > 0 {
-                for (idx, sample) in self.soundboard_queue_48k.drain(..soundboard_take).enumerate() {
-                    frame[idx] += sample * SOUNDBOARD_MIX_GAIN;
-                }
-            }
-
-            let mut clip_samples = 0_u64;
-            let mut limiter_activations = 0_u64;
-            for sample in &mut frame {
-                let pre = *sample * TX_HEADROOM_GAIN;
-                if pre.abs() >= 1.0 {
-                    clip_samples = clip_samples.saturating_add(1);
-                }
-                let limited = soft_limiter(pre * TX_LIMITER_DRIVE);
-                if (pre - limited).abs() > 0.02 {
-                    limiter_activations = limiter_activations.saturating_add(1);
-                }
-                *sample = limited;
-            }
-            self.quality_snapshot.tx_clip_samples = self
-                .quality_snapshot
-                .tx_clip_samples
-                .saturating_add(clip_samples);
-            self.quality_snapshot.tx_limiter_activations = self
-                .quality_snapshot
-                .tx_limiter_activations
-                .saturating_add(limiter_activations);
-
-            let level = rms_level(&frame);
-            let soundboard_gate_open = soundboard_take > 0 && !self.deafened;
-            let should_tx = should_send_voice_frame(soundboard_gate_open, self.should_transmit(level));
+            let soundboard_mixed =
+                mix_soundboard_voices(&mut self.soundboard_voices, &mut frame, self.soundboard_mix);
+
+            let inputs = self.tx_gate_inputs(soundboard_mixed);
+            let outcome = process_tx_frame(&mut frame, inputs, &mut self.vad);
+            outcome.record(&mut self.quality_snapshot);
+            let level = outcome.level;
+            let should_tx = outcome.should_tx;
+            self.emit_mic_level_if_due(app, level);
+            self.warn_if_talking_while_muted(app, level);
             self.log_tx_gate_transition(level, should_tx);
+            self.record_local_frame(&frame, should_tx);
 
             if should_tx {
                 self.silence_frames = 0;
@@ -1031,8 +2290,8 @@ impl MediaRuntime {
                     target: 0,
                     session_id: (),
                     seq_num: self.seq_num,
-                    payload: VoicePacketPayload::Opus(encoded.into(), false),
-                    position_info: None,
+                    payload: VoicePacketPayload::Opus(encoded, false),
+                    position_info: self.self_position.map(encode_position_info),
                 };
                 self.seq_num = self.seq_num.wrapping_add(OPUS_SEQ_STEP);
                 self.send_voice_packet(packet, sink).await?;
@@ -1052,11 +2311,96 @@ impl MediaRuntime {
         }
 
         self.adapt_codec_if_needed();
+        if !self.pending_audio_warnings.is_empty() {
+            let payload = AudioWarningEvent {
+                warnings: std::mem::take(&mut self.pending_audio_warnings),
+            };
+            let _ = events::emit_audio_warning(app, &payload);
+        }
         self.refresh_quality_snapshot();
 
         Ok(())
     }
 
+    fn emit_mic_level_if_due(&mut self, app: &AppHandle, level: f32) {
+        self.mic_level_smoothed += (level - self.mic_level_smoothed) * MIC_LEVEL_SMOOTHING_FACTOR;
+        if self.last_mic_level_emit_at.elapsed() < Duration::from_millis(MIC_LEVEL_EMIT_INTERVAL_MS)
+        {
+            return;
+        }
+        self.last_mic_level_emit_at = Instant::now();
+
+        let payload = MicLevelEvent {
+            level: self.mic_level_smoothed.clamp(0.0, 1.0),
+            on_threshold: self.vad.on_threshold(),
+            off_threshold: self.vad.off_threshold(),
+            muted: self.muted,
+        };
+        let _ = events::emit_mic_level(app, &payload);
+    }
+
+    fn observe_output_level(&mut self, level: f32, peak: f32) {
+        self.output_level_smoothed +=
+            (level - self.output_level_smoothed) * MIC_LEVEL_SMOOTHING_FACTOR;
+        self.output_peak = self.output_peak.max(peak);
+    }
+
+    fn emit_output_level_if_due(&mut self, app: &AppHandle) {
+        if self.last_output_level_emit_at.elapsed()
+            < Duration::from_millis(MIC_LEVEL_EMIT_INTERVAL_MS)
+        {
+            return;
+        }
+        self.last_output_level_emit_at = Instant::now();
+
+        let payload = OutputLevelEvent {
+            level: self.output_level_smoothed.clamp(0.0, 1.0),
+            peak: self.output_peak.clamp(0.0, 1.0),
+        };
+        self.output_peak = 0.0;
+        let _ = events::emit_output_level(app, &payload);
+    }
+
+    fn warn_if_talking_while_muted(&mut self, app: &AppHandle, level: f32) {
+        let muted = self.muted && !self.deafened;
+        if !self
+            .muted_speech
+            .observe(muted, level >= self.vad.on_threshold())
+        {
+            return;
+        }
+        log::info!("talking while muted: level={level:.5}");
+        let payload = TalkingWhileMutedEvent {
+            level: level.clamp(0.0, 1.0),
+        };
+        let _ = events::emit_talking_while_muted(app, &payload);
+    }
+
+    // Taps the capture before soundboard mixing, gain and the limiter.
+    fn record_mic_sample_frame(&mut self, frame: &[f32]) {
+        let Some(sample) = &self.mic_sample else {
+            return;
+        };
+        if !sample.push_local(frame) {
+            self.mic_sample = None;
+        }
+    }
+
+    fn record_local_frame(&mut self, frame: &[f32], transmitted: bool) {
+        let Some(recorder) = &self.recorder else {
+            return;
+        };
+        // Untransmitted frames are recorded as silence so the local track keeps time.
+        let pushed = if transmitted {
+            recorder.push_local(frame)
+        } else {
+            recorder.push_local(&self.silence_frame)
+        };
+        if !pushed {
+            self.recorder = None;
+        }
+    }
+
     async fn set_transmitting_state(
         &mut self,
         app: &AppHandle,
@@ -1078,16 +2422,34 @@ impl MediaRuntime {
         Ok(())
     }
 
+    // Ends any ongoing transmission so the server stops showing us as talking, then closes TLS.
+    async fn shutdown(&mut self, sink: &mut ControlSink) {
+        if self.transmitting {
+            if let Err(err) = self.send_termination_packet(sink).await {
+                log::warn!("failed to send final termination packet: {err}");
+            }
+            self.transmitting = false;
+        }
+        if let Err(err) = sink.close().await {
+            log::debug!("control stream close failed: {err}");
+        }
+    }
+
     async fn send_termination_packet(&mut self, sink: &mut ControlSink) -> Result<(), String> {
-        let silence = vec![0_f32; OPUS_FRAME_SAMPLES];
-        let encoded = self.encode_frame(&silence)?;
+        let written = encode_opus_frame(
+            &mut self.encoder,
+            &self.silence_frame,
+            &mut self.pcm_scratch,
+            &mut self.packet_scratch,
+        )?;
+        let encoded = Bytes::copy_from_slice(&self.packet_scratch[..written]);
         let packet = VoicePacket::Audio {
             _dst: PhantomData,
             target: 0,
             session_id: (),
             seq_num: self.seq_num,
-            payload: VoicePacketPayload::Opus(encoded.into(), true),
-            position_info: None,
+            payload: VoicePacketPayload::Opus(encoded, true),
+            position_info: self.self_position.map(encode_position_info),
         };
         self.seq_num = self.seq_num.wrapping_add(OPUS_SEQ_STEP);
         self.send_voice_packet(packet, sink).await
@@ -1099,12 +2461,19 @@ impl MediaRuntime {
         sink: &mut ControlSink,
     ) -> Result<(), String> {
         if self.can_send_udp_voice() {
-            match self.send_udp_packet(packet.clone()) {
-                Ok(()) => {
+            let mut result = self.send_udp_packet(packet.clone());
+            if result.is_err() && self.note_udp_send_failure() && self.rebind_udp_socket() {
+                result = self.send_udp_packet(packet.clone());
+            }
+            match result {
+                Ok(sent) => {
+                    self.tx_bandwidth.record(sent + UDP_IP_HEADER_BYTES);
+                    self.udp_consecutive_send_failures = 0;
                     self.quality_snapshot.tx_packets_sent_udp = self
                         .quality_snapshot
                         .tx_packets_sent_udp
                         .saturating_add(1);
+                    self.quality_snapshot.voice_transport = VoiceTransport::Udp;
                     return Ok(());
                 }
                 Err(err) => {
@@ -1118,6 +2487,8 @@ impl MediaRuntime {
             .quality_snapshot
             .tx_packets_sent_tcp
             .saturating_add(1);
+        self.quality_snapshot.voice_transport = VoiceTransport::Tcp;
+        self.tx_bandwidth.record(tunneled_voice_len(&packet));
         sink.send(ControlPacket::<Serverbound>::from(packet))
             .await
             .map_err(|err| format!("failed to send tunneled voice packet: {err}"))
@@ -1138,6 +2509,7 @@ impl MediaRuntime {
                 return false;
             }
             self.udp_degraded_until = None;
+            self.quality_snapshot.udp_degraded = false;
             self.udp_consecutive_decrypt_failures = 0;
             log::info!("udp degrade window expired; retrying udp voice path");
         }
@@ -1162,10 +2534,34 @@ impl MediaRuntime {
         self.udp_consecutive_decrypt_failures = 0;
         self.last_udp_audio_rx_at = Some(now);
         if self.udp_degraded_until.take().is_some() {
+            self.quality_snapshot.udp_degraded = false;
             log::info!("udp audio receive recovered; re-enabling udp voice path");
         }
     }
 
+    fn record_tcp_ping_echo(&mut self, timestamp: u64) {
+        let sample = epoch_millis().saturating_sub(timestamp) as f32;
+        self.tcp_rtt_ms = Some(smooth_rtt(self.tcp_rtt_ms, sample));
+    }
+
+    fn network_event(&mut self) -> NetworkEvent {
+        let udp_active = self.can_send_udp_voice();
+        let udp_rtt_ms = self.udp_rtt_ms.filter(|_| udp_active);
+        let event = NetworkEvent {
+            rtt_ms: udp_rtt_ms.or(self.tcp_rtt_ms),
+            udp_rtt_ms,
+            tcp_rtt_ms: self.tcp_rtt_ms,
+            packet_loss_percent: packet_loss_percent(
+                self.quality_snapshot.network_good_packets,
+                self.quality_snapshot.network_late_packets,
+                self.quality_snapshot.network_lost_packets,
+            ),
+        };
+        self.quality_snapshot.rtt_ms = event.rtt_ms;
+        self.quality_snapshot.packet_loss_percent = event.packet_loss_percent;
+        event
+    }
+
     fn mark_tunneled_audio_rx(&mut self) {
         let now = Instant::now();
         self.observe_rx_jitter(now);
@@ -1173,10 +2569,37 @@ impl MediaRuntime {
             self.quality_snapshot.rx_packets_received.saturating_add(1);
     }
 
+    // Returns true once failures are frequent enough that the socket itself is suspect.
+    fn note_udp_send_failure(&mut self) -> bool {
+        self.udp_consecutive_send_failures = self.udp_consecutive_send_failures.saturating_add(1);
+        self.udp_consecutive_send_failures >= UDP_SEND_FAILURE_REBIND_THRESHOLD
+    }
+
+    // A socket bound before a network change can keep failing; a fresh one picks up the new route.
+    fn rebind_udp_socket(&mut self) -> bool {
+        match create_udp_socket(self.server_addr) {
+            Ok(socket) => {
+                log::info!(
+                    "rebound udp socket after {} consecutive send failures",
+                    self.udp_consecutive_send_failures
+                );
+                self.udp_socket = Some(socket);
+                self.udp_consecutive_send_failures = 0;
+                self.udp_consecutive_decrypt_failures = 0;
+                true
+            }
+            Err(err) => {
+                log::warn!("udp socket rebind failed: {err}");
+                false
+            }
+        }
+    }
+
     fn degrade_udp_path(&mut self, reason: &str) {
         let now = Instant::now();
         self.udp_consecutive_decrypt_failures = 0;
         self.udp_degraded_until = Some(now + Duration::from_millis(UDP_DEGRADED_WINDOW_MS));
+        self.quality_snapshot.udp_degraded = true;
 
         let since_last_audio_ms = self
             .last_udp_audio_rx_at
@@ -1190,7 +2613,8 @@ impl MediaRuntime {
         }
     }
 
-    fn send_udp_packet(&mut self, packet: VoicePacket<Serverbound>) -> Result<(), String> {
+    // Returns the datagram length actually sent.
+    fn send_udp_packet(&mut self, packet: VoicePacket<Serverbound>) -> Result<usize, String> {
         let Some(socket) = self.udp_socket.as_ref() else {
             return Err("udp socket not initialized".to_string());
         };
@@ -1202,21 +2626,18 @@ impl MediaRuntime {
         crypt_state.encrypt(packet, &mut encrypted);
         socket
             .send(&encrypted)
-            .map_err(|err| format!("udp send failed: {err}"))?;
-        Ok(())
+            .map_err(|err| format!("udp send failed: {err}"))
     }
 
-    fn should_transmit(&mut self, level: f32) -> bool {
-        if self.muted || self.deafened {
-            return false;
-        }
-
-        // Hotkey press detection is not wired yet; do not block audio path.
-        if self.ptt_enabled {
-            return self.vad.is_speaking(level);
+    fn tx_gate_inputs(&self, soundboard_mixed: bool) -> TxGateInputs {
+        TxGateInputs {
+            muted: self.muted,
+            deafened: self.deafened,
+            ptt_mode: self.ptt_mode,
+            ptt_key_held: self.ptt_key_held,
+            soundboard_mixed,
+            tx_limiter: self.tx_limiter,
         }
-
-        self.vad.is_speaking(level)
     }
 
     fn log_tx_gate_transition(&mut self, level: f32, should_tx: bool) {
@@ -1229,34 +2650,49 @@ impl MediaRuntime {
             "muted"
         } else if self.deafened {
             "deafened"
-        } else if self.ptt_enabled {
-            "ptt_vad"
         } else {
-            "vad"
+            match self.ptt_mode {
+                PttMode::Vad => "vad",
+                PttMode::PushToTalk => "ptt_vad",
+                PttMode::PushToMute if self.ptt_key_held => "ptm_held",
+                PttMode::PushToMute => "ptm_vad",
+            }
         };
 
         log::debug!(
-            "voice tx gate changed: open={should_tx} level={level:.5} on_threshold={VAD_THRESHOLD:.5} off_threshold={VAD_OFF_THRESHOLD:.5} muted={} deafened={} ptt_enabled={} gate={gate}",
+            "voice tx gate changed: open={should_tx} level={level:.5} on_threshold={:.5} off_threshold={:.5} muted={} deafened={} ptt_mode={:?} gate={gate}",
+            self.vad.on_threshold(),
+            self.vad.off_threshold(),
             self.muted,
             self.deafened,
-            self.ptt_enabled,
+            self.ptt_mode,
         );
     }
 
-    fn encode_frame(&mut self, frame: &[f32]) -> Result<Vec<u8>, String> {
-        let mut pcm = Vec::with_capacity(frame.len());
-        for &sample in frame {
-            let clamped = sample.clamp(-1.0, 1.0);
-            pcm.push((clamped * i16::MAX as f32) as i16);
-        }
+    fn encode_frame(&mut self, frame: &[f32]) -> Result<Bytes, String> {
+        let started = Instant::now();
+        let written = encode_opus_frame(
+            &mut self.encoder,
+            frame,
+            &mut self.pcm_scratch,
+            &mut self.packet_scratch,
+        )?;
+        record_codec_time(
+            &mut self.quality_snapshot.encode_max_us,
+            &mut self.quality_snapshot.encode_avg_us,
+            &mut self.encode_timed_frames,
+            started.elapsed(),
+        );
+        self.record_packet_size(written);
+        Ok(Bytes::copy_from_slice(&self.packet_scratch[..written]))
+    }
 
-        let mut packet = vec![0_u8; OPUS_MAX_PACKET_SIZE];
-        let written = self
-            .encoder
-            .encode(&pcm, &mut packet)
-            .map_err(|err| format!("opus encode failed: {err}"))?;
-        packet.truncate(written);
-        Ok(packet)
+    fn record_packet_size(&mut self, len: usize) {
+        let buckets = &mut self.quality_snapshot.tx_packet_size_buckets;
+        buckets[packet_size_bucket(len)] += 1;
+        let count = buckets.iter().sum::<u64>() as f32;
+        let avg = &mut self.quality_snapshot.tx_packet_size_avg_bytes;
+        *avg += (len as f32 - *avg) / count;
     }
 
     fn handle_incoming_voice(
@@ -1269,26 +2705,36 @@ impl MediaRuntime {
             session_id,
             seq_num,
             payload,
+            position_info,
             ..
         } = packet
         else {
             return Ok(false);
         };
 
+        let position = position_info.as_deref().and_then(decode_position_info);
         let mut changed = false;
-        if let Some(update) = roster.maybe_mark_speaking(session_id) {
+        if let Some(update) = roster.maybe_mark_speaking(session_id, position) {
             let _ = events::emit_speaking(app, &update);
             changed = true;
         }
 
+        if self.deafened || self.all_users_muted {
+            return Ok(changed);
+        }
+
         if let VoicePacketPayload::Opus(frame, _) = payload {
             self.queue_inbound_voice(session_id, seq_num, frame.to_vec());
+            if let Some(stream) = self.inbound_streams.get_mut(&session_id) {
+                stream.position = position;
+                stream.priority_speaker = roster.is_priority_speaker(session_id);
+            }
         }
 
         Ok(changed)
     }
 
-    fn drain_inbound_playout(&mut self) -> Result<(), String> {
+    fn drain_inbound_playout(&mut self, app: &AppHandle) -> Result<(), String> {
         let session_ids = self.inbound_streams.keys().copied().collect::<Vec<_>>();
         for session_id in session_ids {
             let force_gap_conceal = self
@@ -1322,21 +2768,69 @@ impl MediaRuntime {
             mix_rounds = deficit_frames.clamp(1, self.jitter_tuning.max_frames);
         }
 
+        let mut mixed_any = false;
         for _ in 0..mix_rounds {
             if !self.mix_inbound_streams_for_playback() {
                 break;
             }
+            mixed_any = true;
         }
+        // Let the meter fall back to silence once nobody is being played out.
+        if !mixed_any {
+            self.observe_output_level(0.0, 0.0);
+        }
+        self.emit_output_level_if_due(app);
         self.cleanup_idle_inbound_streams();
         Ok(())
     }
 
-    fn queue_inbound_voice(
-        &mut self,
-        session_id: u32,
-        seq_num: u64,
-        frame: Vec<u8>,
-    ) {
+    // Lets the UI explain a mute or deafen the user did not ask for.
+    fn emit_enforcement_if_changed(&mut self, app: &AppHandle) {
+        let enforced = self
+            .server_enforcement
+            .against_intent(self.muted, self.deafened);
+        if enforced == self.last_enforced {
+            return;
+        }
+        self.last_enforced = enforced;
+        log::info!(
+            "server-enforced self state: muted={} deafened={}",
+            enforced.muted,
+            enforced.deafened
+        );
+        let _ = events::emit_self_enforced(app, &enforced);
+    }
+
+    fn set_loss_injection(&mut self, injection: Option<LossInjection>) {
+        self.loss_injector = injection
+            .filter(LossInjection::is_active)
+            .map(|settings| LossInjector::new(settings, epoch_millis()));
+        let settings = self.loss_injector.as_ref().map(LossInjector::settings);
+        log::info!("inbound loss injection: {settings:?}");
+        self.quality_snapshot.rx_injected_loss_percent =
+            settings.map_or(0.0, |settings| settings.loss_percent);
+        self.quality_snapshot.rx_injected_reorder_percent =
+            settings.map_or(0.0, |settings| settings.reorder_percent);
+        self.quality_snapshot.rx_injected_drops = 0;
+    }
+
+    fn queue_inbound_voice(&mut self, session_id: u32, seq_num: u64, frame: Vec<u8>) {
+        // Compiled out of release builds, where the command is refused anyway.
+        if cfg!(debug_assertions) {
+            if let Some(injector) = self.loss_injector.as_mut() {
+                let mut delivered = Vec::new();
+                injector.process((session_id, seq_num, frame), &mut delivered);
+                self.quality_snapshot.rx_injected_drops = injector.dropped();
+                for (session_id, seq_num, frame) in delivered {
+                    self.buffer_inbound_voice(session_id, seq_num, frame);
+                }
+                return;
+            }
+        }
+        self.buffer_inbound_voice(session_id, seq_num, frame);
+    }
+
+    fn buffer_inbound_voice(&mut self, session_id: u32, seq_num: u64, frame: Vec<u8>) {
         let stream = self.inbound_streams.entry(session_id).or_default();
         if let Some(expected) = stream.expected_seq {
             if seq_num < expected {
@@ -1347,6 +2841,8 @@ impl MediaRuntime {
                     .quality_snapshot
                     .rx_late_frames_dropped
                     .saturating_add(1);
+                stream.stats.late_frames_dropped =
+                    stream.stats.late_frames_dropped.saturating_add(1);
                 return;
             }
         }
@@ -1364,14 +2860,24 @@ impl MediaRuntime {
         actions: Vec<DecodeAction>,
     ) -> Result<(), String> {
         let mut decoded_frames = Vec::new();
+        let mut speaker = SpeakerStats::default();
         for action in actions {
             let decoded = match action {
                 DecodeAction::Frame(frame) => self.decode_frame(session_id, Some(&frame), false)?,
+                DecodeAction::RecoverWithFec(next_frame) => {
+                    self.quality_snapshot.rx_fec_recovered =
+                        self.quality_snapshot.rx_fec_recovered.saturating_add(1);
+                    self.quality_snapshot.rx_gap_events =
+                        self.quality_snapshot.rx_gap_events.saturating_add(1);
+                    speaker.fec_recovered += 1;
+                    self.decode_frame(session_id, Some(&next_frame), true)?
+                }
                 DecodeAction::ConcealLoss => {
                     self.quality_snapshot.rx_plc_frames =
                         self.quality_snapshot.rx_plc_frames.saturating_add(1);
                     self.quality_snapshot.rx_gap_events =
                         self.quality_snapshot.rx_gap_events.saturating_add(1);
+                    speaker.plc_frames += 1;
                     self.decode_frame(session_id, None, false)?
                 }
             };
@@ -1380,12 +2886,14 @@ impl MediaRuntime {
             }
             self.quality_snapshot.rx_frames_decoded =
                 self.quality_snapshot.rx_frames_decoded.saturating_add(1);
+            speaker.frames_decoded += 1;
             decoded_frames.push(decoded);
         }
 
         let Some(stream) = self.inbound_streams.get_mut(&session_id) else {
             return Ok(());
         };
+        stream.stats.add(speaker);
         for frame in decoded_frames {
             stream.decoded.push_back(frame);
         }
@@ -1394,25 +2902,71 @@ impl MediaRuntime {
 
     fn mix_inbound_streams_for_playback(&mut self) -> bool {
         let mut popped_frames = Vec::new();
-        for stream in self.inbound_streams.values_mut() {
+        for (&session_id, stream) in self.inbound_streams.iter_mut() {
             if let Some(frame) = stream.decoded.pop_front() {
-                popped_frames.push(frame);
+                popped_frames.push((session_id, frame, stream.position, stream.priority_speaker));
             }
         }
         if popped_frames.is_empty() {
             return false;
         }
+        // Others are ducked only while a priority speaker has audio in this frame.
+        if popped_frames.iter().any(|(_, _, _, priority)| *priority) {
+            for (_, frame, _, _) in popped_frames
+                .iter_mut()
+                .filter(|(_, _, _, priority)| !*priority)
+            {
+                frame
+                    .iter_mut()
+                    .for_each(|sample| *sample *= self.priority_duck_gain);
+            }
+        }
 
         let frame_refs = popped_frames
             .iter()
-            .map(|frame| frame.as_slice())
+            .map(|(_, frame, _, _)| frame.as_slice())
             .collect::<Vec<_>>();
-        let mix_result = mix_mono_frames(
-            &frame_refs,
-            &mut self.mix_bus_48k,
-            RX_MIX_HEADROOM_GAIN,
-            RX_LIMITER_DRIVE,
-        );
+        // Recordings stay mono, so the mono bus is still mixed when stereo playout needs it.
+        let mono_needed = !self.stereo_spread || self.recorder.is_some();
+        let mono_result = mono_needed.then(|| {
+            mix_mono_frames(
+                &frame_refs,
+                &mut self.mix_bus_48k,
+                RX_MIX_HEADROOM_GAIN,
+                RX_LIMITER_DRIVE,
+            )
+        });
+        let stereo_result = self.stereo_spread.then(|| {
+            let panned_refs = popped_frames
+                .iter()
+                .map(|(session_id, frame, position, _)| {
+                    let (pan, gain) = match (self.self_position, *position) {
+                        (Some(listener), Some(speaker)) => positional_pan_gain(listener, speaker),
+                        _ => (stereo_pan_for_session(*session_id, STEREO_PAN_SPREAD), 1.0),
+                    };
+                    (frame.as_slice(), pan, gain)
+                })
+                .collect::<Vec<_>>();
+            mix_stereo_frames(
+                &panned_refs,
+                &mut self.mix_bus_left_48k,
+                &mut self.mix_bus_right_48k,
+                RX_MIX_HEADROOM_GAIN,
+                RX_LIMITER_DRIVE,
+            )
+        });
+        let mix_result = stereo_result.or(mono_result).unwrap_or_default();
+        let (level, peak) = if self.stereo_spread {
+            let left = rms_level(&self.mix_bus_left_48k);
+            let right = rms_level(&self.mix_bus_right_48k);
+            (
+                ((left * left + right * right) / 2.0).sqrt(),
+                peak_level(&self.mix_bus_left_48k).max(peak_level(&self.mix_bus_right_48k)),
+            )
+        } else {
+            (rms_level(&self.mix_bus_48k), peak_level(&self.mix_bus_48k))
+        };
+        self.observe_output_level(level, peak);
         self.quality_snapshot.rx_mix_clip_samples = self
             .quality_snapshot
             .rx_mix_clip_samples
@@ -1421,9 +2975,36 @@ impl MediaRuntime {
             .quality_snapshot
             .rx_nan_samples
             .saturating_add(mix_result.nan_samples);
+        for (session_id, frame, _, _) in popped_frames {
+            if let Some(decoder) = self.decoders.get_mut(&session_id) {
+                decoder.recycle_frame(frame);
+            }
+        }
 
         if let Some(output) = &self.output_playback {
-            output.push_mono_48k(&self.mix_bus_48k);
+            let high_water = OPUS_FRAME_SAMPLES.saturating_mul(
+                self.jitter_tuning.target_frames + OUTPUT_LATENCY_HIGH_WATER_FRAMES,
+            );
+            let queued_samples = output.stats_snapshot().queued_samples;
+            if self.output_latency_drain.should_drop(
+                queued_samples,
+                high_water,
+                peak < OUTPUT_LATENCY_QUIET_PEAK,
+            ) {
+                self.quality_snapshot.output_latency_corrections = self
+                    .quality_snapshot
+                    .output_latency_corrections
+                    .saturating_add(1);
+            } else if self.stereo_spread {
+                output.push_stereo_48k(&self.mix_bus_left_48k, &self.mix_bus_right_48k);
+            } else {
+                output.push_mono_48k(&self.mix_bus_48k);
+            }
+        }
+        if let Some(recorder) = &self.recorder {
+            if !recorder.push_playback(&self.mix_bus_48k) {
+                self.recorder = None;
+            }
         }
         true
     }
@@ -1456,32 +3037,48 @@ impl MediaRuntime {
         frame: Option<&[u8]>,
         decode_fec: bool,
     ) -> Result<Vec<f32>, String> {
-        let decoder = match self.decoders.entry(session_id) {
+        let session_decoder = match self.decoders.entry(session_id) {
             Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(entry) => {
-                let decoder = OpusDecoder::new(OPUS_SAMPLE_RATE, Channels::Mono)
-                    .map_err(|err| format!("failed to create opus decoder: {err}"))?;
-                entry.insert(decoder)
-            }
+            Entry::Vacant(entry) => entry.insert(SessionDecoder::new()?),
         };
 
-        let mut decoded = vec![0_i16; OPUS_MAX_DECODED_SAMPLES];
         let encoded = frame.unwrap_or(&[]);
-        let written = decoder
-            .decode(encoded, &mut decoded, decode_fec)
-            .map_err(|err| format!("opus decode failed: {err}"))?;
-        decoded.truncate(written);
-        let mut nan_samples = 0_u64;
-        let mut out = Vec::with_capacity(decoded.len());
-        for sample in decoded {
-            let value = sample as f32 / i16::MAX as f32;
-            if value.is_finite() {
-                out.push(value);
-            } else {
-                nan_samples = nan_samples.saturating_add(1);
-                out.push(0.0);
+        // Opus sizes concealment and FEC output by the buffer, so those get exactly one frame.
+        let pcm_len = if frame.is_none() || decode_fec {
+            OPUS_FRAME_SAMPLES
+        } else {
+            OPUS_MAX_DECODED_SAMPLES
+        };
+        let started = Instant::now();
+        let decoded = session_decoder.decoder.decode(
+            encoded,
+            &mut session_decoder.pcm_scratch[..pcm_len],
+            decode_fec,
+        );
+        record_codec_time(
+            &mut self.quality_snapshot.decode_max_us,
+            &mut self.quality_snapshot.decode_avg_us,
+            &mut self.decode_timed_frames,
+            started.elapsed(),
+        );
+        let written = match decoded {
+            Ok(written) => written,
+            Err(err) => {
+                // One corrupt packet shouldn't tear down the connection; drop it and reset the decoder.
+                log::debug!("opus decode failed for session {session_id}; dropping frame: {err}");
+                if let Err(err) = session_decoder.decoder.reset_state() {
+                    log::warn!("opus decoder reset failed for session {session_id}: {err}");
+                }
+                self.quality_snapshot.rx_decode_errors =
+                    self.quality_snapshot.rx_decode_errors.saturating_add(1);
+                return Ok(Vec::new());
             }
-        }
+        };
+        let mut out = session_decoder
+            .frame_pool
+            .pop()
+            .unwrap_or_else(|| Vec::with_capacity(OPUS_FRAME_SAMPLES));
+        let nan_samples = pcm_to_f32_into(&session_decoder.pcm_scratch[..written], &mut out);
         if nan_samples > 0 {
             self.quality_snapshot.rx_nan_samples = self
                 .quality_snapshot
@@ -1507,16 +3104,11 @@ impl MediaRuntime {
             return;
         }
         self.last_codec_adapt_at = Instant::now();
+        self.quality_snapshot.tx_kbps = self.tx_bandwidth.sample_kbps(self.last_codec_adapt_at);
+        self.quality_snapshot.rx_kbps = self.rx_bandwidth.sample_kbps(self.last_codec_adapt_at);
 
         let Some(crypt) = self.crypt_state.as_ref() else {
-            self.apply_codec_tuning_if_changed(
-                self.codec_tuning.baseline_bitrate_bps,
-                self.codec_tuning.baseline_packet_loss_pct,
-            );
-            self.jitter_tuning.target_frames = self.jitter_tuning.baseline_target_frames;
-            self.jitter_tuning.max_frames = self.jitter_tuning.baseline_max_frames;
-            self.quality_snapshot.rx_jitter_target_frames = self.jitter_tuning.target_frames;
-            self.quality_snapshot.rx_jitter_max_frames = self.jitter_tuning.max_frames;
+            self.reset_adaptive_tuning();
             return;
         };
 
@@ -1528,8 +3120,29 @@ impl MediaRuntime {
         self.quality_snapshot.network_good_packets = current.good;
         self.quality_snapshot.network_late_packets = current.late;
         self.quality_snapshot.network_lost_packets = current.lost;
+        if self.one_way_audio.observe(
+            self.quality_snapshot.tx_packets_sent_udp,
+            self.server_udp_good,
+        ) {
+            let receiving = self
+                .last_udp_stats
+                .is_some_and(|previous| current.good > previous.good);
+            let detail = if receiving {
+                "server is not receiving our udp voice while we still receive theirs; a NAT or firewall may be blocking outbound UDP"
+            } else {
+                "server is not receiving our udp voice; a NAT or firewall may be blocking outbound UDP"
+            };
+            log::warn!("{detail}");
+            self.pending_audio_warnings.push(AudioWarning {
+                kind: AudioWarningKind::OneWayAudio,
+                detail: detail.to_string(),
+            });
+        }
 
         let previous = self.last_udp_stats.replace(current);
+        if !self.adaptive_enabled {
+            return;
+        }
         let Some(previous) = previous else {
             return;
         };
@@ -1587,9 +3200,23 @@ impl MediaRuntime {
         self.apply_codec_tuning_if_changed(target_bitrate, target_loss);
     }
 
+    fn reset_adaptive_tuning(&mut self) {
+        self.apply_codec_tuning_if_changed(
+            self.codec_tuning.baseline_bitrate_bps,
+            self.codec_tuning.baseline_packet_loss_pct,
+        );
+        self.jitter_tuning.target_frames = self.jitter_tuning.baseline_target_frames;
+        self.jitter_tuning.max_frames = self.jitter_tuning.baseline_max_frames;
+        self.quality_snapshot.rx_jitter_target_frames = self.jitter_tuning.target_frames;
+        self.quality_snapshot.rx_jitter_max_frames = self.jitter_tuning.max_frames;
+    }
+
     fn apply_codec_tuning_if_changed(&mut self, bitrate_bps: i32, packet_loss_pct: i32) {
-        let next_bitrate = bitrate_bps.clamp(OPUS_BITRATE_MIN_BPS, OPUS_BITRATE_MAX_BPS);
-        let next_packet_loss = packet_loss_pct.clamp(0, 25);
+        let mut next_bitrate = bitrate_bps.clamp(OPUS_BITRATE_MIN_BPS, OPUS_BITRATE_MAX_BPS);
+        if let Some(ceiling) = self.bitrate_ceiling_bps {
+            next_bitrate = next_bitrate.min(ceiling);
+        }
+        let next_packet_loss = packet_loss_pct.clamp(0, OPUS_TUNED_PACKET_LOSS_MAX_PCT);
 
         if next_bitrate != self.codec_tuning.current_bitrate_bps {
             if let Err(err) = self.encoder.set_bitrate(Bitrate::Bits(next_bitrate)) {
@@ -1632,8 +3259,17 @@ impl MediaRuntime {
             self.quality_snapshot.output_queued_samples = stats.queued_samples;
             self.quality_snapshot.output_device_name = Some(output.device_name().to_string());
             self.quality_snapshot.output_sample_rate = Some(output.sample_rate());
+            let buffer = output.buffer_config();
+            self.quality_snapshot.output_buffer_ms = buffer.buffer_ms;
+            self.quality_snapshot.output_prefill_ms = buffer.prefill_ms;
         }
 
+        self.quality_snapshot.rx_per_speaker = self
+            .inbound_streams
+            .iter()
+            .map(|(&session_id, stream)| (session_id, stream.stats))
+            .collect();
+
         self.publish_quality_snapshot();
     }
 
@@ -1649,7 +3285,7 @@ fn collect_decode_actions(
     force_gap_conceal: bool,
     jitter_tuning: JitterTuning,
 ) -> Vec<DecodeAction> {
-    if !stream.started && stream.buffered.len() >= jitter_tuning.target_frames {
+    if !stream.started && stream.buffered.len() >= jitter_tuning.start_frames() {
         stream.started = true;
     }
     if !stream.started {
@@ -1657,11 +3293,7 @@ fn collect_decode_actions(
     }
 
     let mut actions = Vec::new();
-    loop {
-        let Some(expected) = stream.expected_seq else {
-            break;
-        };
-
+    while let Some(expected) = stream.expected_seq {
         if let Some(frame) = stream.buffered.remove(&expected) {
             actions.push(DecodeAction::Frame(frame));
             stream.expected_seq = Some(expected.wrapping_add(OPUS_SEQ_STEP));
@@ -1684,7 +3316,11 @@ fn collect_decode_actions(
             break;
         }
 
-        actions.push(DecodeAction::ConcealLoss);
+        let following = stream.buffered.get(&expected.wrapping_add(OPUS_SEQ_STEP));
+        actions.push(match following {
+            Some(next_frame) => DecodeAction::RecoverWithFec(next_frame.clone()),
+            None => DecodeAction::ConcealLoss,
+        });
         stream.expected_seq = Some(expected.wrapping_add(OPUS_SEQ_STEP));
     }
 
@@ -1697,24 +3333,48 @@ async fn run_voice_worker(
     shared: VoiceSharedState,
     mut command_rx: mpsc::UnboundedReceiver<VoiceCommand>,
     quality_shared: Arc<StdRwLock<AudioQualityMetrics>>,
+    cue_clips: CueClips,
 ) {
+    let mut config = config;
     let mut reconnect_attempt: u32 = 0;
     let mut latest_reason: Option<String> = None;
     let mut should_exit = false;
     let mut has_connected_once = false;
+    let mut rejoin_channel_name: Option<String> = None;
 
     while !should_exit {
         let connecting_state = next_connecting_state(reconnect_attempt, has_connected_once);
-        set_connection_state(&app, &shared, connecting_state, latest_reason.clone()).await;
+        set_connection_state(
+            &app,
+            &shared,
+            connecting_state,
+            latest_reason.clone(),
+            Some(ConnectionPhase::Resolving),
+        )
+        .await;
+        *shared.server_info.write().await = ServerInfo::default();
 
-        let mut connection = match connect_mumble(&config).await {
+        let mut connection = match connect_mumble(&config, &app, &shared).await {
             Ok(connection) => connection,
             Err(err) => {
                 reconnect_attempt = reconnect_attempt.saturating_add(1);
                 latest_reason = Some(err);
+                if !config.server.auto_reconnect {
+                    break;
+                }
+                if reconnect_attempts_exhausted(
+                    reconnect_attempt,
+                    config.server.reconnect_max_attempts,
+                ) {
+                    latest_reason = Some("max reconnect attempts reached".to_string());
+                    break;
+                }
 
-                if wait_for_retry_or_disconnect(&mut command_rx, reconnect_delay(reconnect_attempt))
-                    .await
+                if wait_for_retry_or_disconnect(
+                    &mut command_rx,
+                    reconnect_delay(reconnect_attempt, config.server.reconnect_max_delay_secs),
+                )
+                .await
                 {
                     should_exit = true;
                 }
@@ -1725,7 +3385,14 @@ async fn run_voice_worker(
         reconnect_attempt = 0;
         latest_reason = None;
         has_connected_once = true;
-        set_connection_state(&app, &shared, ConnectionState::Connected, None).await;
+        set_connection_state(
+            &app,
+            &shared,
+            ConnectionState::Connected,
+            None,
+            Some(ConnectionPhase::AwaitingSync),
+        )
+        .await;
 
         let initial_self = shared.self_state.read().await.clone();
         let mut media = match MediaRuntime::new(
@@ -1733,6 +3400,7 @@ async fn run_voice_worker(
             &initial_self,
             connection.server_addr,
             Arc::clone(&quality_shared),
+            cue_clips.clone(),
         ) {
             Ok(runtime) => runtime,
             Err(err) => {
@@ -1740,7 +3408,17 @@ async fn run_voice_worker(
                 break;
             }
         };
+        if !media.pending_audio_warnings.is_empty() {
+            let payload = AudioWarningEvent {
+                warnings: std::mem::take(&mut media.pending_audio_warnings),
+            };
+            let _ = events::emit_audio_warning(&app, &payload);
+        }
         let mut roster = ProtocolRoster::new(config.server.default_channel.clone());
+        roster.rejoin_channel_name = rejoin_channel_name.take();
+        roster.auto_join_default_channel = config.server.auto_join_default_channel;
+        roster.max_badge_codes = config.max_badge_codes_per_user;
+        roster.all_users_muted = initial_self.all_users_muted;
 
         let mut ping_tick = interval(Duration::from_secs(10));
         ping_tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
@@ -1750,6 +3428,8 @@ async fn run_voice_worker(
         media_tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
         let mut speaking_tick = interval(Duration::from_millis(180));
         speaking_tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut network_tick = interval(Duration::from_millis(NETWORK_EVENT_INTERVAL_MS));
+        network_tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
         let mut tcp_packets_seen: u32 = 0;
 
         loop {
@@ -1757,6 +3437,13 @@ async fn run_voice_worker(
                 maybe_cmd = command_rx.recv() => {
                     match maybe_cmd {
                         None | Some(VoiceCommand::Disconnect) => {
+                            let flush = media.shutdown(&mut connection.sink);
+                            if timeout(Duration::from_millis(DISCONNECT_FLUSH_TIMEOUT_MS), flush)
+                                .await
+                                .is_err()
+                            {
+                                log::warn!("disconnect flush timed out; dropping connection");
+                            }
                             should_exit = true;
                             break;
                         }
@@ -1766,8 +3453,8 @@ async fn run_voice_worker(
                                 &mut connection.sink,
                                 &mut media,
                                 &app,
-                                &shared,
-                                &roster,
+                                &mut roster,
+                                &mut config.server,
                             ).await {
                                 latest_reason = Some(err);
                                 break;
@@ -1785,16 +3472,15 @@ async fn run_voice_worker(
                 _ = udp_ping_tick.tick() => {
                     let _ = media.send_udp_ping();
                 }
+                _ = network_tick.tick() => {
+                    let _ = events::emit_network(&app, &media.network_event());
+                }
                 _ = media_tick.tick() => {
+                    media.apply_due_device_switches(Instant::now());
                     match media.poll_udp_inbound(&app, &mut roster) {
                         Ok(roster_changed) => {
                             if roster_changed {
-                                let roster_event = roster.build_roster_event();
-                                {
-                                    let mut roster_state = shared.roster.write().await;
-                                    *roster_state = roster_event.clone();
-                                }
-                                let _ = events::emit_roster(&app, &roster_event);
+                                roster.mark_dirty();
                             }
                         }
                         Err(err) => {
@@ -1802,7 +3488,7 @@ async fn run_voice_worker(
                             break;
                         }
                     }
-                    if let Err(err) = media.drain_inbound_playout() {
+                    if let Err(err) = media.drain_inbound_playout(&app) {
                         latest_reason = Some(err);
                         break;
                     }
@@ -1810,6 +3496,7 @@ async fn run_voice_worker(
                         latest_reason = Some(err);
                         break;
                     }
+                    publish_roster(&app, &shared, &mut roster).await;
                 }
                 _ = speaking_tick.tick() => {
                     let expired = roster.expire_speaking(Duration::from_millis(650));
@@ -1819,12 +3506,7 @@ async fn run_voice_worker(
                     for update in expired {
                         let _ = events::emit_speaking(&app, &update);
                     }
-                    let roster_event = roster.build_roster_event();
-                    {
-                        let mut roster_state = shared.roster.write().await;
-                        *roster_state = roster_event.clone();
-                    }
-                    let _ = events::emit_roster(&app, &roster_event);
+                    roster.mark_dirty();
                 }
                 packet = connection.stream.next() => {
                     let Some(packet) = packet else {
@@ -1862,18 +3544,36 @@ async fn run_voice_worker(
         if should_exit {
             break;
         }
+        rejoin_channel_name = roster
+            .current_channel_name()
+            .or(roster.rejoin_channel_name.take());
 
         if latest_reason.is_some() {
+            // Without auto-reconnect the drop is surfaced as Disconnected and the UI decides.
+            if !config.server.auto_reconnect
+                || latest_reason.as_deref() == Some(SERVER_OPUS_UNSUPPORTED_REASON)
+            {
+                break;
+            }
             reconnect_attempt = reconnect_attempt.saturating_add(1);
+            if reconnect_attempts_exhausted(reconnect_attempt, config.server.reconnect_max_attempts)
+            {
+                latest_reason = Some("max reconnect attempts reached".to_string());
+                break;
+            }
             set_connection_state(
                 &app,
                 &shared,
                 ConnectionState::Reconnecting,
                 latest_reason.clone(),
+                None,
             )
             .await;
-            if wait_for_retry_or_disconnect(&mut command_rx, reconnect_delay(reconnect_attempt))
-                .await
+            if wait_for_retry_or_disconnect(
+                &mut command_rx,
+                reconnect_delay(reconnect_attempt, config.server.reconnect_max_delay_secs),
+            )
+            .await
             {
                 should_exit = true;
             }
@@ -1883,7 +3583,15 @@ async fn run_voice_worker(
     if let Ok(mut snapshot) = quality_shared.write() {
         snapshot.connected = false;
     }
-    set_connection_state(&app, &shared, ConnectionState::Disconnected, latest_reason).await;
+    *shared.server_info.write().await = ServerInfo::default();
+    set_connection_state(
+        &app,
+        &shared,
+        ConnectionState::Disconnected,
+        latest_reason,
+        None,
+    )
+    .await;
 }
 
 fn next_connecting_state(reconnect_attempt: u32, has_connected_once: bool) -> ConnectionState {
@@ -1894,22 +3602,38 @@ fn next_connecting_state(reconnect_attempt: u32, has_connected_once: bool) -> Co
     }
 }
 
+// Returns true to stop reconnecting. RetryNow cuts the backoff short; other commands have no
+// connection to act on while we wait and are dropped without ending the sleep.
 async fn wait_for_retry_or_disconnect(
     command_rx: &mut mpsc::UnboundedReceiver<VoiceCommand>,
     delay: Duration,
 ) -> bool {
-    tokio::select! {
-        maybe_cmd = command_rx.recv() => matches!(maybe_cmd, None | Some(VoiceCommand::Disconnect)),
-        _ = sleep(delay) => false,
+    let backoff = sleep(delay);
+    tokio::pin!(backoff);
+    loop {
+        tokio::select! {
+            maybe_cmd = command_rx.recv() => match maybe_cmd {
+                None | Some(VoiceCommand::Disconnect) => return true,
+                Some(VoiceCommand::RetryNow) => return false,
+                Some(_) => {}
+            },
+            _ = &mut backoff => return false,
+        }
     }
 }
 
-async fn connect_mumble(config: &AppConfig) -> Result<LiveConnection, String> {
-    let server_addr = resolve_server_addr(&config.server.host, config.server.port)?;
-    let tcp = TcpStream::connect(server_addr)
-        .await
-        .map_err(|err| format!("failed to connect TCP {}: {err}", server_addr))?;
-
+async fn connect_mumble(
+    config: &AppConfig,
+    app: &AppHandle,
+    shared: &VoiceSharedState,
+) -> Result<LiveConnection, String> {
+    let (connect_host, connect_port) = resolve_connect_target(&config.server).await;
+    let server_addrs = resolve_server_addrs(&connect_host, connect_port)?;
+    set_connection_phase(app, shared, ConnectionPhase::Tcp).await;
+    // The UDP socket is later bound to the family of whichever address won here.
+    let (tcp, server_addr) = connect_tcp_happy_eyeballs(&server_addrs).await?;
+
+    set_connection_phase(app, shared, ConnectionPhase::Tls).await;
     let mut tls_builder = NativeTlsConnector::builder();
     tls_builder.danger_accept_invalid_certs(config.server.allow_insecure_tls);
     let tls_connector: TlsConnector = tls_builder
@@ -1917,6 +3641,7 @@ async fn connect_mumble(config: &AppConfig) -> Result<LiveConnection, String> {
         .map_err(|err| format!("failed to build TLS connector: {err}"))?
         .into();
 
+    // The certificate is checked against the name the user entered, not the SRV target.
     let tls = tls_connector
         .connect(&config.server.host, tcp)
         .await
@@ -1925,6 +3650,7 @@ async fn connect_mumble(config: &AppConfig) -> Result<LiveConnection, String> {
     let framed = ClientControlCodec::new().framed(tls);
     let (mut sink, stream) = framed.split();
 
+    set_connection_phase(app, shared, ConnectionPhase::Version).await;
     let mut version = msgs::Version::new();
     version.set_version(pack_mumble_version(
         MUMBLE_MIN_CHANNEL_LISTENER_MAJOR,
@@ -1938,6 +3664,7 @@ async fn connect_mumble(config: &AppConfig) -> Result<LiveConnection, String> {
         .await
         .map_err(|err| format!("failed to send version packet: {err}"))?;
 
+    set_connection_phase(app, shared, ConnectionPhase::Authenticating).await;
     let auth_profile = derive_auth_profile(config);
     let mut authenticate = msgs::Authenticate::new();
     authenticate.set_username(auth_profile.auth_username);
@@ -1945,6 +3672,7 @@ async fn connect_mumble(config: &AppConfig) -> Result<LiveConnection, String> {
         authenticate.set_password(password);
     }
     authenticate.set_opus(true);
+    authenticate.set_tokens(config.server.all_access_tokens().into());
 
     sink.send(ControlPacket::<Serverbound>::from(authenticate))
         .await
@@ -1957,18 +3685,147 @@ async fn connect_mumble(config: &AppConfig) -> Result<LiveConnection, String> {
     })
 }
 
-fn resolve_server_addr(host: &str, port: u16) -> Result<SocketAddr, String> {
-    (host, port)
+// Falls back to the literal host and port whenever the SRV lookup comes up empty.
+async fn resolve_connect_target(server: &ServerConfig) -> (String, u16) {
+    let fallback_port = if server.port == 0 {
+        MUMBLE_DEFAULT_PORT
+    } else {
+        server.port
+    };
+    if !server.use_srv && server.port != 0 {
+        return (server.host.clone(), server.port);
+    }
+
+    match lookup_mumble_srv(&server.host).await {
+        Ok(Some((host, port))) => {
+            log::info!("SRV record for {} points at {host}:{port}", server.host);
+            (host, port)
+        }
+        Ok(None) => {
+            log::info!("no usable SRV record for {}", server.host);
+            (server.host.clone(), fallback_port)
+        }
+        Err(err) => {
+            log::warn!("SRV lookup for {} failed: {err}", server.host);
+            (server.host.clone(), fallback_port)
+        }
+    }
+}
+
+async fn lookup_mumble_srv(host: &str) -> Result<Option<(String, u16)>, String> {
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()
+        .map_err(|err| format!("failed to read system resolver config: {err}"))?;
+    let lookup = resolver
+        .srv_lookup(format!("{MUMBLE_SRV_PREFIX}{}", host.trim_end_matches('.')))
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(pick_srv_target(lookup.iter().map(|srv| {
+        (
+            srv.priority(),
+            srv.weight(),
+            srv.port(),
+            srv.target().to_utf8(),
+        )
+    })))
+}
+
+// Lowest priority wins, then the heaviest weight; a bare "." target means no service.
+fn pick_srv_target(
+    records: impl IntoIterator<Item = (u16, u16, u16, String)>,
+) -> Option<(String, u16)> {
+    let (_, _, port, target) = records
+        .into_iter()
+        .min_by_key(|(priority, weight, _, _)| (*priority, std::cmp::Reverse(*weight)))?;
+    let target = target.trim_end_matches('.');
+    (!target.is_empty() && port != 0).then(|| (target.to_string(), port))
+}
+
+fn resolve_server_addrs(host: &str, port: u16) -> Result<Vec<SocketAddr>, String> {
+    let resolved = (host, port)
         .to_socket_addrs()
         .map_err(|err| format!("failed to resolve server address {host}:{port}: {err}"))?
-        .next()
-        .ok_or_else(|| format!("no socket address resolved for {host}:{port}"))
+        .collect::<Vec<_>>();
+    if resolved.is_empty() {
+        return Err(format!("no socket address resolved for {host}:{port}"));
+    }
+    Ok(interleave_address_families(resolved))
+}
+
+// Alternates families starting with the resolver's first choice, dropping duplicates.
+fn interleave_address_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let mut unique = Vec::with_capacity(addrs.len());
+    for addr in addrs {
+        if !unique.contains(&addr) {
+            unique.push(addr);
+        }
+    }
+    let Some(first) = unique.first().copied() else {
+        return unique;
+    };
+
+    let (mut preferred, mut other): (VecDeque<_>, VecDeque<_>) = unique
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == first.is_ipv6());
+    let mut ordered = Vec::with_capacity(preferred.len() + other.len());
+    loop {
+        match (preferred.pop_front(), other.pop_front()) {
+            (None, None) => break,
+            (a, b) => ordered.extend(a.into_iter().chain(b)),
+        }
+    }
+    ordered
+}
+
+async fn connect_tcp_happy_eyeballs(
+    addrs: &[SocketAddr],
+) -> Result<(TcpStream, SocketAddr), String> {
+    let mut attempts = addrs
+        .iter()
+        .enumerate()
+        .map(|(idx, &addr)| async move {
+            if idx > 0 {
+                sleep(Duration::from_millis(
+                    HAPPY_EYEBALLS_STAGGER_MS * idx as u64,
+                ))
+                .await;
+            }
+            TcpStream::connect(addr)
+                .await
+                .map(|stream| (stream, addr))
+                .map_err(|err| format!("failed to connect TCP {addr}: {err}"))
+        })
+        .collect::<FuturesUnordered<_>>();
+
+    let mut last_err = None;
+    while let Some(result) = attempts.next().await {
+        match result {
+            Ok(connected) => {
+                if addrs.len() > 1 {
+                    log::info!(
+                        "connected to {} of {} resolved addresses",
+                        connected.1,
+                        addrs.len()
+                    );
+                }
+                return Ok(connected);
+            }
+            Err(err) => {
+                log::debug!("{err}");
+                last_err = Some(err);
+            }
+        }
+    }
+    Err(last_err.unwrap_or_else(|| "no server addresses to connect to".to_string()))
 }
 
 fn pack_mumble_version(major: u32, minor: u32, patch: u32) -> u32 {
     ((major & 0xFFFF) << 16) | ((minor & 0xFF) << 8) | (patch & 0xFF)
 }
 
+fn unpack_mumble_version(packed: u32) -> (u32, u32, u32) {
+    ((packed >> 16) & 0xFFFF, (packed >> 8) & 0xFF, packed & 0xFF)
+}
+
 fn create_udp_socket(server_addr: SocketAddr) -> Result<std::net::UdpSocket, String> {
     let bind_addr = match server_addr {
         SocketAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
@@ -1991,10 +3848,16 @@ struct AuthProfile {
 }
 
 fn derive_auth_profile(config: &AppConfig) -> AuthProfile {
-    if config.nickname == SUPERUSER_TRIGGER_NICKNAME {
+    let auth = &config.auth;
+    let admin_selected = auth.use_admin_account
+        || auth
+            .admin_trigger_nickname
+            .as_ref()
+            .is_some_and(|trigger| trigger == &config.nickname);
+    if let Some(admin_username) = auth.admin_username.clone().filter(|_| admin_selected) {
         return AuthProfile {
-            auth_username: SUPERUSER_AUTH_USERNAME.to_string(),
-            auth_password: Some(SUPERUSER_AUTH_PASSWORD.to_string()),
+            auth_username: admin_username,
+            auth_password: auth.admin_password.clone(),
         };
     }
 
@@ -2004,7 +3867,7 @@ fn derive_auth_profile(config: &AppConfig) -> AuthProfile {
             .server
             .password
             .clone()
-            .or_else(|| Some(DEFAULT_USER_PASSWORD.to_string())),
+            .or_else(|| auth.fallback_password.clone()),
     }
 }
 
@@ -2013,218 +3876,161 @@ fn badge_codes_for_nickname(config: &AppConfig) -> Vec<String> {
         .badge_profiles
         .get(&config.nickname)
         .cloned()
-        .map(normalize_badge_codes)
+        .map(|codes| normalize_badge_codes(codes, config.max_badge_codes_per_user))
         .unwrap_or_default()
 }
 
-fn normalize_badge_codes(raw_codes: Vec<String>) -> Vec<String> {
-    let mut normalized = Vec::new();
+fn output_resampler_quality(voice: &VoiceQualityConfig) -> ResamplerQuality {
+    if voice.hq_resampling {
+        ResamplerQuality::High
+    } else {
+        ResamplerQuality::Standard
+    }
+}
 
-    for raw in raw_codes {
-        let code = raw.trim().to_ascii_lowercase();
-        if code.is_empty() || code.len() > MAX_BADGE_CODE_LEN {
-            continue;
-        }
-        if !code.bytes().all(|value| {
-            value.is_ascii_lowercase() || value.is_ascii_digit() || value == b'-' || value == b'_'
-        }) {
-            continue;
-        }
-        if normalized.contains(&code) {
-            continue;
+fn start_soundboard_voice(voices: &mut Vec<SoundboardVoice>, samples: Vec<f32>) {
+    let playing = voices.iter().filter(|voice| !voice.is_releasing()).count();
+    if playing >= SOUNDBOARD_MAX_VOICES {
+        if let Some(oldest) = voices.iter_mut().find(|voice| !voice.is_releasing()) {
+            oldest.release();
         }
-        normalized.push(code);
-        if normalized.len() >= MAX_BADGE_CODES_PER_USER {
-            break;
+    }
+    voices.push(SoundboardVoice::new(samples));
+}
+
+// Sums every active clip into the frame and drops the ones that finished.
+fn mix_soundboard_voices(
+    voices: &mut Vec<SoundboardVoice>,
+    frame: &mut [f32],
+    mix: SoundboardConfig,
+) -> bool {
+    // The frame holds only mic audio until the clips are added below.
+    if mix.duck_voice && !voices.is_empty() {
+        for sample in frame.iter_mut() {
+            *sample *= SOUNDBOARD_DUCK_GAIN;
         }
     }
+    let gain = mix.mix_gain.clamp(0.0, SOUNDBOARD_MIX_GAIN_MAX);
+    let mut mixed = false;
+    for voice in voices.iter_mut() {
+        mixed |= voice.mix_into(frame, gain);
+    }
+    voices.retain(|voice| !voice.is_finished());
+    mixed
+}
 
-    normalized
+// With the limiter off the frame is only clamped so the encoder never sees out-of-range samples.
+fn shape_tx_sample(pre: f32, limiter: bool) -> f32 {
+    if limiter {
+        soft_limiter(pre * TX_LIMITER_DRIVE)
+    } else {
+        pre.clamp(-1.0, 1.0)
+    }
 }
 
-fn encode_badge_comment(badge_codes: &[String]) -> String {
-    let normalized = normalize_badge_codes(badge_codes.to_vec());
-    format!("{}{}", HARMONY_BADGES_COMMENT_PREFIX, normalized.join(","))
+// What the transmit path needs to know about the session to shape and gate one frame.
+#[derive(Debug, Clone, Copy)]
+struct TxGateInputs {
+    muted: bool,
+    deafened: bool,
+    ptt_mode: PttMode,
+    ptt_key_held: bool,
+    soundboard_mixed: bool,
+    tx_limiter: bool,
 }
 
-fn parse_badge_comment(comment: &str) -> Option<Vec<String>> {
-    let payload = comment.strip_prefix(HARMONY_BADGES_COMMENT_PREFIX)?;
-    let codes = payload
-        .split(',')
-        .map(str::trim)
-        .filter(|code| !code.is_empty())
-        .map(ToOwned::to_owned)
-        .collect::<Vec<_>>();
-    Some(normalize_badge_codes(codes))
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TxFrameOutcome {
+    level: f32,
+    should_tx: bool,
+    clip_samples: u64,
+    limiter_activations: u64,
 }
 
-fn should_send_voice_frame(has_soundboard_audio: bool, mic_gate_open: bool) -> bool {
-    has_soundboard_audio || mic_gate_open
+impl TxFrameOutcome {
+    fn record(&self, metrics: &mut AudioQualityMetrics) {
+        metrics.tx_clip_samples = metrics.tx_clip_samples.saturating_add(self.clip_samples);
+        metrics.tx_limiter_activations = metrics
+            .tx_limiter_activations
+            .saturating_add(self.limiter_activations);
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::core::config::ServerConfig;
+// Gain, limiter and gate decision for one capture frame, shaped in place ready to encode.
+// Kept free of devices and the control sink so the hot path can be tested directly.
+fn process_tx_frame(
+    frame: &mut [f32],
+    inputs: TxGateInputs,
+    vad: &mut VoiceActivityDetector,
+) -> TxFrameOutcome {
+    let mut clip_samples = 0_u64;
+    let mut limiter_activations = 0_u64;
+    for sample in frame.iter_mut() {
+        let pre = *sample * TX_HEADROOM_GAIN;
+        if pre.abs() >= 1.0 {
+            clip_samples = clip_samples.saturating_add(1);
+        }
+        let shaped = shape_tx_sample(pre, inputs.tx_limiter);
+        if inputs.tx_limiter && (pre - shaped).abs() > 0.02 {
+            limiter_activations = limiter_activations.saturating_add(1);
+        }
+        *sample = shaped;
+    }
 
-    #[test]
-    fn derive_auth_profile_uses_superuser_credentials_for_trigger_nickname() {
-        let config = AppConfig {
-            nickname: SUPERUSER_TRIGGER_NICKNAME.to_string(),
-            server: ServerConfig {
-                password: Some("normal-password".to_string()),
-                ..ServerConfig::default()
-            },
-            ..AppConfig::default()
-        };
-
-        let profile = derive_auth_profile(&config);
-        assert_eq!(profile.auth_username, SUPERUSER_AUTH_USERNAME);
-        assert_eq!(
-            profile.auth_password.as_deref(),
-            Some(SUPERUSER_AUTH_PASSWORD)
-        );
-    }
-
-    #[test]
-    fn derive_auth_profile_uses_nickname_and_normal_password_for_regular_users() {
-        let config = AppConfig {
-            nickname: "friend01".to_string(),
-            server: ServerConfig {
-                password: Some("custom-normal-password".to_string()),
-                ..ServerConfig::default()
-            },
-            ..AppConfig::default()
-        };
-
-        let profile = derive_auth_profile(&config);
-        assert_eq!(profile.auth_username, "friend01");
-        assert_eq!(
-            profile.auth_password.as_deref(),
-            Some("custom-normal-password")
-        );
-    }
-
-    #[test]
-    fn derive_auth_profile_falls_back_to_default_user_password() {
-        let config = AppConfig {
-            nickname: "friend02".to_string(),
-            server: ServerConfig {
-                password: None,
-                ..ServerConfig::default()
-            },
-            ..AppConfig::default()
-        };
-
-        let profile = derive_auth_profile(&config);
-        assert_eq!(profile.auth_username, "friend02");
-        assert_eq!(
-            profile.auth_password.as_deref(),
-            Some(DEFAULT_USER_PASSWORD)
-        );
-    }
-
-    #[test]
-    fn next_connecting_state_only_uses_connecting_for_initial_attempt() {
-        assert_eq!(next_connecting_state(0, false), ConnectionState::Connecting);
-        assert_eq!(
-            next_connecting_state(1, false),
-            ConnectionState::Reconnecting
-        );
-        assert_eq!(
-            next_connecting_state(0, true),
-            ConnectionState::Reconnecting
-        );
-    }
-
-    #[test]
-    fn reconnect_delay_uses_exponential_backoff_with_cap() {
-        assert_eq!(reconnect_delay(1), Duration::from_secs(2));
-        assert_eq!(reconnect_delay(2), Duration::from_secs(4));
-        assert_eq!(reconnect_delay(5), Duration::from_secs(32));
-        assert_eq!(reconnect_delay(6), Duration::from_secs(32));
-        assert_eq!(reconnect_delay(100), Duration::from_secs(32));
-    }
-
-    #[test]
-    fn apply_user_state_preserves_ptt_and_transmitting_for_self_events() {
-        let mut roster = ProtocolRoster::new("Game Night".to_string());
-        roster.set_self_session(42);
-
-        let mut msg = msgs::UserState::new();
-        msg.set_session(42);
-        msg.set_name("mason".to_string());
-        msg.set_self_mute(true);
-
-        let current_self = SelfEvent {
-            muted: false,
-            deafened: false,
-            ptt_enabled: true,
-            transmitting: true,
-        };
-
-        let (_changed, maybe_self) = roster.apply_user_state(&msg, &current_self);
-        let self_event = maybe_self.expect("self event should be present");
-
-        assert_eq!(
-            self_event,
-            SelfEvent {
-                muted: true,
-                deafened: false,
-                ptt_enabled: true,
-                transmitting: true,
-            }
-        );
+    let level = rms_level(frame);
+    // The VAD only advances while the mic could transmit, matching the old gate.
+    let mic_gate_open = !inputs.muted
+        && !inputs.deafened
+        && ptt_gate_open(inputs.ptt_mode, inputs.ptt_key_held, vad.is_speaking(level));
+    let soundboard_gate_open = inputs.soundboard_mixed && !inputs.deafened;
+    TxFrameOutcome {
+        level,
+        should_tx: should_send_voice_frame(soundboard_gate_open, mic_gate_open),
+        clip_samples,
+        limiter_activations,
     }
+}
 
-    #[test]
-    fn badge_comment_round_trip_encodes_and_decodes() {
-        let input = vec!["rainbow-core".to_string(), "party-parrot".to_string()];
-        let encoded = encode_badge_comment(&input);
-        let decoded = parse_badge_comment(&encoded).expect("should parse encoded payload");
-        assert_eq!(decoded, input);
+fn tunneled_voice_len<Dst: VoicePacketDst>(packet: &VoicePacket<Dst>) -> usize {
+    match packet {
+        VoicePacket::Audio {
+            payload: VoicePacketPayload::Opus(frame, _),
+            ..
+        } => frame.len() + TUNNEL_VOICE_OVERHEAD_BYTES,
+        _ => TUNNEL_VOICE_OVERHEAD_BYTES,
     }
+}
 
-    #[test]
-    fn parse_badge_comment_ignores_non_harmony_payload() {
-        assert_eq!(parse_badge_comment("hello world"), None);
-    }
+fn record_codec_time(max_us: &mut u64, avg_us: &mut f32, frames: &mut u64, elapsed: Duration) {
+    let us = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+    *max_us = (*max_us).max(us);
+    *frames = frames.saturating_add(1);
+    *avg_us += (us as f32 - *avg_us) / *frames as f32;
+}
 
-    #[test]
-    fn parse_badge_comment_normalizes_dedupes_and_caps() {
-        let parsed = parse_badge_comment(
-            "harmony_badges:v1:RAINBOW-CORE,party-parrot,rainbow-core,invalid!,a,b,c,d,e",
-        )
-        .expect("payload should parse");
-        assert_eq!(
-            parsed,
-            vec![
-                "rainbow-core".to_string(),
-                "party-parrot".to_string(),
-                "a".to_string(),
-                "b".to_string(),
-                "c".to_string(),
-            ]
-        );
+fn packet_size_bucket(len: usize) -> usize {
+    match len {
+        0..=49 => 0,
+        50..=99 => 1,
+        100..=199 => 2,
+        _ => 3,
     }
+}
 
-    #[test]
-    fn should_send_voice_frame_allows_soundboard_when_mic_gate_is_closed() {
-        assert!(should_send_voice_frame(true, false));
-        assert!(should_send_voice_frame(true, true));
-    }
+// Servers that predate Opus never set the flag, so a missing field means CELT/Speex only.
+fn server_supports_opus(msg: &msgs::CodecVersion) -> bool {
+    msg.has_opus() && msg.get_opus()
+}
 
-    #[test]
-    fn should_send_voice_frame_respects_mic_gate_without_soundboard() {
-        assert!(should_send_voice_frame(false, true));
-        assert!(!should_send_voice_frame(false, false));
-    }
+fn should_send_voice_frame(has_soundboard_audio: bool, mic_gate_open: bool) -> bool {
+    has_soundboard_audio || mic_gate_open
+}
 
-    #[test]
-    fn pack_mumble_version_encodes_major_minor_patch() {
-        assert_eq!(pack_mumble_version(1, 4, 0), 0x010400);
-        assert_eq!(pack_mumble_version(1, 5, 9), 0x010509);
-        assert_eq!(pack_mumble_version(2, 255, 255), 0x02FFFF);
+fn ptt_gate_open(mode: PttMode, key_held: bool, vad_speaking: bool) -> bool {
+    match mode {
+        // Hotkey press detection is not wired for PTT yet; do not block audio path.
+        PttMode::Vad | PttMode::PushToTalk => vad_speaking,
+        PttMode::PushToMute => vad_speaking && !key_held,
     }
 }
 
@@ -2233,46 +4039,178 @@ async fn handle_live_command(
     sink: &mut ControlSink,
     media: &mut MediaRuntime,
     app: &AppHandle,
-    shared: &VoiceSharedState,
-    roster: &ProtocolRoster,
+    roster: &mut ProtocolRoster,
+    server: &mut ServerConfig,
 ) -> Result<(), String> {
     match command {
         VoiceCommand::Disconnect => Ok(()),
         VoiceCommand::SetMute(muted) => {
+            if media.muted != muted {
+                media.play_cue(media.cue_clips.mute.as_ref());
+            }
             media.set_muted(muted);
+            media.emit_enforcement_if_changed(app);
             send_self_state_update(sink, Some(muted), None).await
         }
         VoiceCommand::SetDeafen(deafened) => {
+            let was_deafened = media.deafened;
             media.set_deafened(deafened);
+            media.emit_enforcement_if_changed(app);
+            if was_deafened && !deafened {
+                media.play_cue(media.cue_clips.mute.as_ref());
+            }
             send_self_state_update(sink, None, Some(deafened)).await
         }
-        VoiceCommand::SetPtt(enabled) => {
-            media.set_ptt(enabled);
-            let next = {
-                let mut state = shared.self_state.write().await;
-                state.ptt_enabled = enabled;
-                state.clone()
-            };
-            let _ = events::emit_self(app, &next);
+        VoiceCommand::SetAllUsersMuted(muted) => {
+            media.set_all_users_muted(muted);
+            roster.all_users_muted = muted;
+            roster.mark_dirty();
+            Ok(())
+        }
+        VoiceCommand::SetPttMode(mode) => {
+            media.set_ptt_mode(mode);
+            Ok(())
+        }
+        VoiceCommand::SetPttKeyHeld(held) => {
+            media.ptt_key_held = held;
             Ok(())
         }
         VoiceCommand::SetPttHotkey(hotkey) => {
             media.set_ptt_hotkey(hotkey);
             Ok(())
         }
+        VoiceCommand::SetJitterBuffer {
+            target_frames,
+            max_frames,
+        } => {
+            media.set_jitter_buffer(target_frames, max_frames);
+            Ok(())
+        }
+        VoiceCommand::SetAdaptiveEnabled(enabled) => {
+            media.set_adaptive_enabled(enabled);
+            Ok(())
+        }
+        VoiceCommand::ApplyVoiceQuality(quality) => {
+            media.apply_voice_quality(&quality);
+            Ok(())
+        }
+        VoiceCommand::SetSelfPosition(position) => {
+            media.self_position = Some(position);
+            Ok(())
+        }
+        VoiceCommand::SetVad(settings) => {
+            media.set_vad(settings);
+            Ok(())
+        }
+        VoiceCommand::SetComplexity(complexity) => {
+            media.set_complexity(complexity);
+            Ok(())
+        }
         VoiceCommand::SetInputDevice(device_id) => {
-            media.set_input_device(device_id);
+            media.request_input_device(device_id, Instant::now());
+            Ok(())
+        }
+        VoiceCommand::RetryAudio => {
+            media.retry_audio(app);
+            Ok(())
+        }
+        // Only meaningful during backoff; a connection that just came back ignores it.
+        VoiceCommand::RetryNow => Ok(()),
+        VoiceCommand::SetAudioHost(audio_host) => {
+            media.set_audio_host(app, audio_host);
             Ok(())
         }
         VoiceCommand::SetOutputDevice(device_id) => {
-            media.set_output_device(device_id);
+            media.request_output_device(device_id, Instant::now());
             Ok(())
         }
-        VoiceCommand::SendMessage(message) => send_text_message(sink, roster, message).await,
+        VoiceCommand::SendMessage {
+            message,
+            channel_id,
+        } => {
+            if let Some(channel_id) = channel_id {
+                if !roster.channels.contains_key(&channel_id) {
+                    log::warn!("dropping message for unknown channel {channel_id}");
+                    return Ok(());
+                }
+            }
+            send_text_message(sink, roster, message, channel_id).await
+        }
+        VoiceCommand::SendPrivateMessage { session, message } => {
+            if !roster.has_user(session) {
+                log::warn!("dropping private message for unknown session {session}");
+                return Ok(());
+            }
+            send_private_text_message(sink, session, message).await
+        }
+        VoiceCommand::AddAccessToken(token) => {
+            if server.tokens.contains(&token) {
+                return Ok(());
+            }
+            server.tokens.push(token);
+            send_access_tokens(sink, server).await
+        }
+        VoiceCommand::JoinChannel {
+            channel_id,
+            access_token,
+        } => {
+            if let Some(token) = access_token {
+                server.access_tokens.insert(channel_id.to_string(), token);
+                send_access_tokens(sink, server).await?;
+            }
+            send_channel_join(sink, channel_id).await
+        }
+        VoiceCommand::SetChannelListener { channel_id, listen } => {
+            let Some(session) = roster.self_session else {
+                log::warn!("skipping channel listener change before the server assigned a session");
+                return Ok(());
+            };
+            if !roster.channels.contains_key(&channel_id) {
+                log::warn!("skipping channel listener change for unknown channel {channel_id}");
+                return Ok(());
+            }
+            send_channel_listener(sink, session, channel_id, listen).await
+        }
+        VoiceCommand::RemoveUser {
+            session,
+            reason,
+            ban,
+        } => {
+            if !roster.has_user(session) {
+                log::warn!("skipping remove for unknown session {session}");
+                return Ok(());
+            }
+            send_remove_user(sink, session, reason, ban).await
+        }
+        VoiceCommand::CreateChannel {
+            name,
+            parent_id,
+            temporary,
+        } => send_create_channel(sink, name, parent_id, temporary).await,
         VoiceCommand::QueueSoundboardSamples(samples_48k) => {
             media.enqueue_soundboard_samples(samples_48k);
             Ok(())
         }
+        VoiceCommand::SetSoundboardMix(mix) => {
+            media.soundboard_mix = mix;
+            Ok(())
+        }
+        VoiceCommand::SetLossInjection(injection) => {
+            media.set_loss_injection(injection);
+            Ok(())
+        }
+        VoiceCommand::StartRecording(recorder) => {
+            media.recorder = Some(recorder);
+            Ok(())
+        }
+        VoiceCommand::StopRecording => {
+            media.recorder = None;
+            Ok(())
+        }
+        VoiceCommand::StartMicSample(recorder) => {
+            media.mic_sample = Some(recorder);
+            Ok(())
+        }
     }
 }
 
@@ -2280,11 +4218,12 @@ async fn send_text_message(
     sink: &mut ControlSink,
     roster: &ProtocolRoster,
     message: String,
+    channel_id: Option<u32>,
 ) -> Result<(), String> {
     let mut text = msgs::TextMessage::new();
     text.set_message(message);
 
-    if let Some(channel_id) = roster.target_channel_id() {
+    if let Some(channel_id) = channel_id.or_else(|| roster.target_channel_id()) {
         text.mut_channel_id().push(channel_id);
     } else {
         text.mut_tree_id().push(0);
@@ -2295,6 +4234,132 @@ async fn send_text_message(
         .map_err(|err| format!("failed to send text message: {err}"))
 }
 
+// Decrypt splits views off the front of the scratch buffer; once the previous packet is
+// dropped, reserve rewinds into the same allocation instead of making a new one.
+fn refill_udp_scratch(scratch: &mut BytesMut, datagram: &[u8]) {
+    scratch.clear();
+    scratch.reserve(datagram.len());
+    scratch.extend_from_slice(datagram);
+}
+
+async fn send_private_text_message(
+    sink: &mut ControlSink,
+    session: u32,
+    message: String,
+) -> Result<(), String> {
+    let mut text = msgs::TextMessage::new();
+    text.set_message(message);
+    text.mut_session().push(session);
+
+    sink.send(ControlPacket::<Serverbound>::from(text))
+        .await
+        .map_err(|err| format!("failed to send private message: {err}"))
+}
+
+// Mumble treats channel passwords as access tokens; resending Authenticate replaces the list.
+async fn send_access_tokens(sink: &mut ControlSink, server: &ServerConfig) -> Result<(), String> {
+    let mut authenticate = msgs::Authenticate::new();
+    authenticate.set_tokens(server.all_access_tokens().into());
+
+    sink.send(ControlPacket::<Serverbound>::from(authenticate))
+        .await
+        .map_err(|err| format!("failed to send access tokens: {err}"))
+}
+
+async fn send_channel_join(sink: &mut ControlSink, channel_id: u32) -> Result<(), String> {
+    let mut state = msgs::UserState::new();
+    state.set_channel_id(channel_id);
+    sink.send(ControlPacket::<Serverbound>::from(state))
+        .await
+        .map_err(|err| format!("failed to request channel switch: {err}"))
+}
+
+async fn send_channel_listener(
+    sink: &mut ControlSink,
+    session: u32,
+    channel_id: u32,
+    listen: bool,
+) -> Result<(), String> {
+    sink.send(ControlPacket::<Serverbound>::from(channel_listener_state(
+        session, channel_id, listen,
+    )))
+    .await
+    .map_err(|err| format!("failed to update channel listener: {err}"))
+}
+
+fn channel_listener_state(session: u32, channel_id: u32, listen: bool) -> msgs::UserState {
+    let field = if listen {
+        USER_STATE_LISTENING_CHANNEL_ADD
+    } else {
+        USER_STATE_LISTENING_CHANNEL_REMOVE
+    };
+    let mut state = msgs::UserState::new();
+    state.set_session(session);
+    state
+        .mut_unknown_fields()
+        .add_varint(field, u64::from(channel_id));
+    state
+}
+
+// Murmur writes these repeated fields unpacked, one varint per channel.
+fn listening_channel_field(msg: &msgs::UserState, field: u32) -> Vec<u32> {
+    msg.get_unknown_fields()
+        .get(field)
+        .map(|values| {
+            values
+                .varint
+                .iter()
+                .filter_map(|&value| u32::try_from(value).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+async fn send_remove_user(
+    sink: &mut ControlSink,
+    session: u32,
+    reason: Option<String>,
+    ban: bool,
+) -> Result<(), String> {
+    let mut remove = msgs::UserRemove::new();
+    remove.set_session(session);
+    if let Some(reason) = reason {
+        remove.set_reason(reason);
+    }
+    remove.set_ban(ban);
+
+    sink.send(ControlPacket::<Serverbound>::from(remove))
+        .await
+        .map_err(|err| format!("failed to send user remove: {err}"))
+}
+
+async fn send_create_channel(
+    sink: &mut ControlSink,
+    name: String,
+    parent_id: u32,
+    temporary: bool,
+) -> Result<(), String> {
+    let mut channel = msgs::ChannelState::new();
+    channel.set_name(name);
+    channel.set_parent(parent_id);
+    channel.set_temporary(temporary);
+
+    sink.send(ControlPacket::<Serverbound>::from(channel))
+        .await
+        .map_err(|err| format!("failed to send channel create: {err}"))
+}
+
+async fn publish_roster(app: &AppHandle, shared: &VoiceSharedState, roster: &mut ProtocolRoster) {
+    let Some(roster_event) = roster.take_pending_roster_event() else {
+        return;
+    };
+    {
+        let mut roster_state = shared.roster.write().await;
+        *roster_state = roster_event.clone();
+    }
+    let _ = events::emit_roster(app, &roster_event);
+}
+
 async fn handle_control_packet(
     packet: ControlPacket<mumble_protocol::Clientbound>,
     app: &AppHandle,
@@ -2305,6 +4370,7 @@ async fn handle_control_packet(
     media: &mut MediaRuntime,
 ) -> Result<(), String> {
     let mut roster_changed = false;
+    let mut roster_emit_now = false;
     let mut self_changed = false;
 
     match packet {
@@ -2316,12 +4382,71 @@ async fn handle_control_packet(
             };
             return Err(reason);
         }
+        ControlPacket::Version(msg) => {
+            let mut info = shared.server_info.write().await;
+            if msg.has_version() {
+                let (major, minor, patch) = unpack_mumble_version(msg.get_version());
+                info.version = Some(format!("{major}.{minor}.{patch}"));
+            }
+            info.release = msg.has_release().then(|| msg.get_release().to_string());
+            info.os = msg.has_os().then(|| {
+                format!("{} {}", msg.get_os(), msg.get_os_version())
+                    .trim()
+                    .to_string()
+            });
+        }
         ControlPacket::ServerSync(msg) => {
+            set_connection_phase(app, shared, ConnectionPhase::Synced).await;
+            if msg.has_welcome_text() {
+                shared.server_info.write().await.welcome_text =
+                    Some(msg.get_welcome_text().to_string());
+            }
             roster.set_self_session(msg.get_session());
-            send_self_badge_comment(sink, &badge_codes_for_nickname(config)).await?;
+            if msg.has_max_bandwidth() {
+                media.set_server_max_bandwidth(msg.get_max_bandwidth());
+            }
+            send_self_badge_comment(
+                sink,
+                &badge_codes_for_nickname(config),
+                config.max_badge_codes_per_user,
+                &UserMeta::normalized(
+                    config.display_color.as_deref(),
+                    config.avatar_seed.as_deref(),
+                ),
+            )
+            .await?;
+            // Covers start_muted/start_deafened and restores local state after a reconnect.
+            if media.muted || media.deafened {
+                send_self_state_update(sink, Some(media.muted), Some(media.deafened)).await?;
+                // Mirror it locally so the self sync below doesn't flash the server's old state.
+                if let Some(user) = roster.users.get_mut(&msg.get_session()) {
+                    user.muted = media.muted;
+                    user.deafened = media.deafened;
+                }
+            }
             roster_changed = true;
+            // Flush the initial sync right away so the UI populates without waiting a tick.
+            roster_emit_now = true;
             let _ = media.send_udp_ping();
         }
+        ControlPacket::Ping(msg) if msg.has_timestamp() => {
+            media.record_tcp_ping_echo(msg.get_timestamp());
+            if msg.has_good() {
+                media.server_udp_good = Some(msg.get_good());
+            }
+        }
+        ControlPacket::ServerConfig(msg) if msg.has_max_bandwidth() => {
+            media.set_server_max_bandwidth(msg.get_max_bandwidth());
+        }
+        ControlPacket::CodecVersion(msg) if !server_supports_opus(&msg) => {
+            log::warn!(
+                "server negotiated a legacy codec: alpha={} beta={} prefer_alpha={}",
+                msg.get_alpha(),
+                msg.get_beta(),
+                msg.get_prefer_alpha()
+            );
+            return Err(SERVER_OPUS_UNSUPPORTED_REASON.to_string());
+        }
         ControlPacket::CryptSetup(msg) => {
             if let Some(response) = media.apply_crypt_setup(&msg)? {
                 sink.send(ControlPacket::<Serverbound>::from(response))
@@ -2335,13 +4460,18 @@ async fn handle_control_packet(
                 .map(|session| roster.user_name_for_session(session))
                 .unwrap_or_else(|| "Server".to_string());
             let channel_id = msg.get_channel_id().first().copied();
+            let private = !msg.get_session().is_empty();
+            let kind = message_kind(actor_session.is_some(), !msg.get_tree_id().is_empty());
             let payload = MessageEvent {
                 actor_session: actor_session.map(|session| session.to_string()),
                 actor_name,
                 channel_id: channel_id.map(|value| value.to_string()),
                 message: msg.take_message(),
+                private,
+                kind,
                 timestamp_ms: epoch_millis(),
             };
+            record_message(shared, payload.clone()).await;
             let _ = events::emit_message(app, &payload);
         }
         ControlPacket::ChannelState(msg) => {
@@ -2352,8 +4482,29 @@ async fn handle_control_packet(
         }
         ControlPacket::UserState(msg) => {
             let current_self = { shared.self_state.read().await.clone() };
+            let was_present = roster.in_target_channel(msg.get_session());
             let (changed, maybe_self) = roster.apply_user_state(&msg, &current_self);
             roster_changed = changed || roster_changed;
+            if let Some(session) = roster.comment_blob_request(&msg) {
+                request_comment_blob(sink, session).await?;
+            }
+            if roster.self_session == Some(msg.get_session()) {
+                media.server_enforcement.apply(&msg);
+                media.emit_enforcement_if_changed(app);
+            }
+
+            let is_present = roster.in_target_channel(msg.get_session());
+            if was_present != is_present {
+                if let Some(payload) = roster.presence_event(msg.get_session(), is_present) {
+                    let cue = if is_present {
+                        &media.cue_clips.join
+                    } else {
+                        &media.cue_clips.leave
+                    };
+                    media.play_cue(cue.as_ref());
+                    let _ = events::emit_user_presence(app, &payload);
+                }
+            }
 
             if let Some(self_event) = maybe_self {
                 {
@@ -2365,9 +4516,28 @@ async fn handle_control_packet(
             }
         }
         ControlPacket::UserRemove(msg) => {
-            roster_changed = roster.remove_user(msg.get_session()) || roster_changed;
+            let session = msg.get_session();
+            let presence = roster
+                .in_target_channel(session)
+                .then(|| roster.presence_event(session, false))
+                .flatten();
+            roster_changed = roster.remove_user(session) || roster_changed;
+            if let Some(payload) = presence {
+                media.play_cue(media.cue_clips.leave.as_ref());
+                let _ = events::emit_user_presence(app, &payload);
+            }
+        }
+        ControlPacket::PermissionDenied(msg) => {
+            let payload = permission_denied_event(&msg);
+            log::info!(
+                "server denied action: type={} reason={}",
+                payload.denial_type,
+                payload.reason
+            );
+            let _ = events::emit_permission_denied(app, &payload);
         }
         ControlPacket::UDPTunnel(packet) => {
+            media.rx_bandwidth.record(tunneled_voice_len(&packet));
             media.mark_tunneled_audio_rx();
             if media.handle_incoming_voice(*packet, app, roster)? {
                 roster_changed = true;
@@ -2381,12 +4551,10 @@ async fn handle_control_packet(
     }
 
     if roster_changed {
-        let roster_event = roster.build_roster_event();
-        {
-            let mut roster_state = shared.roster.write().await;
-            *roster_state = roster_event.clone();
+        roster.mark_dirty();
+        if roster_emit_now {
+            publish_roster(app, shared, roster).await;
         }
-        let _ = events::emit_roster(app, &roster_event);
     }
 
     if !self_changed {
@@ -2395,10 +4563,13 @@ async fn handle_control_packet(
                 let next = {
                     let mut self_state = shared.self_state.write().await;
                     let next = SelfEvent {
-                        muted: user.muted,
+                        muted: user.muted || self_state.listen_only,
                         deafened: user.deafened,
                         ptt_enabled: self_state.ptt_enabled,
+                        ptt_mode: self_state.ptt_mode,
                         transmitting: self_state.transmitting,
+                        listen_only: self_state.listen_only,
+                        all_users_muted: self_state.all_users_muted,
                     };
                     *self_state = next.clone();
                     next
@@ -2411,12 +4582,62 @@ async fn handle_control_packet(
     Ok(())
 }
 
+fn permission_denied_event(msg: &msgs::PermissionDenied) -> PermissionDeniedEvent {
+    use msgs::PermissionDenied_DenyType as DenyType;
+
+    let (denial_type, fallback_reason) = match msg.get_field_type() {
+        DenyType::Text => ("text", "action denied by server"),
+        DenyType::Permission => ("permission", "you do not have permission for that action"),
+        DenyType::SuperUser => ("super_user", "not allowed for the SuperUser account"),
+        DenyType::ChannelName => ("channel_name", "invalid channel name"),
+        DenyType::TextTooLong => ("text_too_long", "message is too long"),
+        DenyType::H9K => ("h9k", "action denied by server"),
+        DenyType::TemporaryChannel => ("temporary_channel", "not allowed in a temporary channel"),
+        DenyType::MissingCertificate => ("missing_certificate", "a client certificate is required"),
+        DenyType::UserName => ("user_name", "invalid user name"),
+        DenyType::ChannelFull => ("channel_full", "channel is full"),
+        DenyType::NestingLimit => ("nesting_limit", "channel nesting limit reached"),
+        DenyType::ChannelCountLimit => ("channel_count_limit", "channel count limit reached"),
+    };
+    let reason = if msg.has_reason() && !msg.get_reason().trim().is_empty() {
+        msg.get_reason().trim().to_string()
+    } else {
+        fallback_reason.to_string()
+    };
+
+    PermissionDeniedEvent {
+        denial_type: denial_type.to_string(),
+        reason,
+        channel_id: msg
+            .has_channel_id()
+            .then(|| msg.get_channel_id().to_string()),
+        timestamp_ms: epoch_millis(),
+    }
+}
+
+// Messages without an actor, or sent down a channel tree, are server notices.
+fn message_kind(has_actor: bool, tree_targeted: bool) -> MessageKind {
+    if !has_actor || tree_targeted {
+        MessageKind::System
+    } else {
+        MessageKind::Chat
+    }
+}
+
+async fn record_message(shared: &VoiceSharedState, payload: MessageEvent) {
+    let mut history = shared.message_history.write().await;
+    while history.len() >= MESSAGE_HISTORY_LIMIT {
+        history.pop_front();
+    }
+    history.push_back(payload);
+}
+
 async fn maybe_join_default_channel(
     config: &AppConfig,
     roster: &mut ProtocolRoster,
     sink: &mut ControlSink,
 ) -> Result<bool, String> {
-    if config.server.default_channel.is_empty() {
+    if config.server.default_channel.is_empty() && roster.rejoin_channel_name.is_none() {
         return Ok(false);
     }
 
@@ -2424,7 +4645,7 @@ async fn maybe_join_default_channel(
         return Ok(false);
     }
 
-    let Some(target_channel_id) = roster.default_channel_id() else {
+    let Some(target_channel_id) = roster.join_target_channel_id() else {
         return Ok(false);
     };
 
@@ -2461,12 +4682,22 @@ async fn send_self_state_update(
         .map_err(|err| format!("failed to send user state update: {err}"))
 }
 
+async fn request_comment_blob(sink: &mut ControlSink, session: u32) -> Result<(), String> {
+    let mut request = msgs::RequestBlob::new();
+    request.mut_session_comment().push(session);
+    sink.send(ControlPacket::<Serverbound>::from(request))
+        .await
+        .map_err(|err| format!("failed to request user comment: {err}"))
+}
+
 async fn send_self_badge_comment(
     sink: &mut ControlSink,
     badge_codes: &[String],
+    max_codes: usize,
+    meta: &UserMeta,
 ) -> Result<(), String> {
     let mut update = msgs::UserState::new();
-    update.set_comment(encode_badge_comment(badge_codes));
+    update.set_comment(encode_badge_comment(badge_codes, max_codes, meta));
     sink.send(ControlPacket::<Serverbound>::from(update))
         .await
         .map_err(|err| format!("failed to send badge metadata: {err}"))
@@ -2499,6 +4730,22 @@ async fn send_ping(
         .map_err(|err| format!("failed to send ping: {err}"))
 }
 
+fn smooth_rtt(previous: Option<f32>, sample_ms: f32) -> f32 {
+    match previous {
+        Some(current) => current + (sample_ms - current) * RTT_SMOOTHING_FACTOR,
+        None => sample_ms,
+    }
+}
+
+fn packet_loss_percent(good: u32, late: u32, lost: u32) -> f32 {
+    let missed = u64::from(late) + u64::from(lost);
+    let total = u64::from(good) + missed;
+    if total == 0 {
+        return 0.0;
+    }
+    missed as f32 * 100.0 / total as f32
+}
+
 fn epoch_millis() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -2506,9 +4753,101 @@ fn epoch_millis() -> u64 {
         .as_millis() as u64
 }
 
-fn reconnect_delay(attempt: u32) -> Duration {
-    let exponent = attempt.min(5);
-    Duration::from_secs(2u64.pow(exponent))
+fn reconnect_delay(attempt: u32, max_delay_secs: u64) -> Duration {
+    let exponent = attempt.min(16);
+    Duration::from_secs(2u64.pow(exponent).min(max_delay_secs.max(1)))
+}
+
+// A zero limit keeps retrying; the first failed connect counts as attempt one.
+fn reconnect_attempts_exhausted(attempt: u32, max_attempts: u32) -> bool {
+    max_attempts > 0 && attempt > max_attempts
+}
+
+fn bitrate_ceiling_for_bandwidth(max_bandwidth_bps: u32) -> i32 {
+    let overhead_bps = VOICE_PACKET_OVERHEAD_BYTES * 8 * VOICE_PACKETS_PER_SECOND;
+    let available = i32::try_from(max_bandwidth_bps)
+        .unwrap_or(i32::MAX)
+        .saturating_sub(overhead_bps);
+    available.clamp(OPUS_BITRATE_FLOOR_BPS, OPUS_BITRATE_MAX_BPS)
+}
+
+// Mumble carries positions as three f32s (x, y, z) after the audio payload, copied in host
+// byte order by PacketDataStream, which is little-endian on every platform it ships on.
+fn encode_position_info(position: [f32; 3]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(POSITION_INFO_LEN);
+    for axis in position {
+        buf.extend_from_slice(&axis.to_le_bytes());
+    }
+    buf.freeze()
+}
+
+fn decode_position_info(bytes: &[u8]) -> Option<[f32; 3]> {
+    if bytes.len() < POSITION_INFO_LEN {
+        return None;
+    }
+    let axis = |idx: usize| {
+        let start = idx * 4;
+        f32::from_le_bytes(bytes[start..start + 4].try_into().unwrap_or_default())
+    };
+    let position = [axis(0), axis(1), axis(2)];
+    position
+        .iter()
+        .all(|value| value.is_finite())
+        .then_some(position)
+}
+
+fn position_distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let dx = a[0] - b[0];
+    let dy = a[1] - b[1];
+    let dz = a[2] - b[2];
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+// Listener orientation isn't sent, so the listener is assumed to face +z with +x to the right.
+fn positional_pan_gain(listener: [f32; 3], speaker: [f32; 3]) -> (f32, f32) {
+    let distance = position_distance(listener, speaker);
+    if distance <= f32::EPSILON {
+        return (0.0, 1.0);
+    }
+    let pan = ((speaker[0] - listener[0]) / distance).clamp(-1.0, 1.0);
+    let falloff = ((distance - POSITIONAL_MIN_DISTANCE)
+        / (POSITIONAL_MAX_DISTANCE - POSITIONAL_MIN_DISTANCE))
+        .clamp(0.0, 1.0);
+    (pan, 1.0 - falloff * (1.0 - POSITIONAL_MIN_GAIN))
+}
+
+// Replaces the contents of `out`; non-finite samples become silence and are counted.
+fn pcm_to_f32_into(pcm: &[i16], out: &mut Vec<f32>) -> u64 {
+    out.clear();
+    let mut nan_samples = 0_u64;
+    for &sample in pcm {
+        let value = sample as f32 / i16::MAX as f32;
+        if value.is_finite() {
+            out.push(value);
+        } else {
+            nan_samples = nan_samples.saturating_add(1);
+            out.push(0.0);
+        }
+    }
+    nan_samples
+}
+
+// Encodes into caller-owned scratch buffers so the transmit path doesn't allocate per frame.
+fn encode_opus_frame(
+    encoder: &mut OpusEncoder,
+    frame: &[f32],
+    pcm: &mut Vec<i16>,
+    packet: &mut [u8],
+) -> Result<usize, String> {
+    pcm.clear();
+    pcm.extend(
+        frame
+            .iter()
+            .map(|&sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16),
+    );
+    encoder
+        .encode(pcm, packet)
+        .map_err(|err| format!("opus encode failed: {err}"))
 }
 
 fn configure_encoder(encoder: &mut OpusEncoder, tuning: CodecTuning) -> Result<(), String> {
@@ -2516,7 +4855,7 @@ fn configure_encoder(encoder: &mut OpusEncoder, tuning: CodecTuning) -> Result<(
         .set_bitrate(Bitrate::Bits(tuning.current_bitrate_bps))
         .map_err(|err| format!("set_bitrate failed: {err}"))?;
     encoder
-        .set_complexity(OPUS_COMPLEXITY)
+        .set_complexity(tuning.complexity)
         .map_err(|err| format!("set_complexity failed: {err}"))?;
     encoder
         .set_vbr(true)
@@ -2533,27 +4872,1100 @@ fn configure_encoder(encoder: &mut OpusEncoder, tuning: CodecTuning) -> Result<(
     Ok(())
 }
 
-fn rms_level(frame: &[f32]) -> f32 {
-    if frame.is_empty() {
-        return 0.0;
-    }
-    let mut sum = 0.0_f32;
-    for &sample in frame {
-        sum += sample * sample;
-    }
-    (sum / frame.len() as f32).sqrt()
-}
-
 async fn set_connection_state(
     app: &AppHandle,
     shared: &VoiceSharedState,
     state: ConnectionState,
     reason: Option<String>,
+    phase: Option<ConnectionPhase>,
 ) {
-    let payload = ConnectionEvent { state, reason };
+    let payload = ConnectionEvent {
+        state,
+        reason,
+        phase,
+    };
     {
         let mut current = shared.connection.write().await;
         *current = payload.clone();
     }
     let _ = events::emit_connection(app, &payload);
 }
+
+// Advances the handshake phase without touching the state or reason.
+async fn set_connection_phase(app: &AppHandle, shared: &VoiceSharedState, phase: ConnectionPhase) {
+    let payload = {
+        let mut current = shared.connection.write().await;
+        current.phase = Some(phase);
+        current.clone()
+    };
+    let _ = events::emit_connection(app, &payload);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{AuthConfig, ServerConfig};
+    use mumble_protocol::crypt::ServerCryptState;
+
+    #[test]
+    fn udp_scratch_reuses_its_allocation_across_decrypts() {
+        let mut server = ServerCryptState::generate_new();
+        let mut client = ClientCryptState::new_from(
+            *server.get_key(),
+            server.get_decrypt_nonce(),
+            server.get_encrypt_nonce(),
+        );
+        let mut scratch = BytesMut::with_capacity(UDP_RX_BUFFER_BYTES);
+        let allocation = scratch.as_ptr() as usize..scratch.as_ptr() as usize + UDP_RX_BUFFER_BYTES;
+
+        // Enough packets to walk off the end of the buffer a few times.
+        for seq_num in 0..128_u64 {
+            let packet = VoicePacket::Audio {
+                _dst: PhantomData,
+                target: 0,
+                session_id: seq_num as u32 % 3,
+                seq_num,
+                payload: VoicePacketPayload::Opus(Bytes::from(vec![7_u8; 60]), false),
+                position_info: None,
+            };
+            let mut datagram = BytesMut::new();
+            server.encrypt(packet, &mut datagram);
+
+            refill_udp_scratch(&mut scratch, &datagram);
+            assert!(allocation.contains(&(scratch.as_ptr() as usize)));
+
+            let decoded = client.decrypt(&mut scratch).unwrap().unwrap();
+            let VoicePacket::Audio {
+                payload: VoicePacketPayload::Opus(frame, _),
+                ..
+            } = decoded
+            else {
+                panic!("expected an opus packet");
+            };
+            assert_eq!(frame.as_ref(), &[7_u8; 60]);
+        }
+    }
+
+    #[test]
+    fn message_kind_marks_actorless_and_tree_messages_as_system() {
+        assert_eq!(message_kind(true, false), MessageKind::Chat);
+        assert_eq!(message_kind(false, false), MessageKind::System);
+        assert_eq!(message_kind(true, true), MessageKind::System);
+    }
+
+    #[test]
+    fn derive_auth_profile_uses_superuser_credentials_for_trigger_nickname() {
+        let auth = AuthConfig::default();
+        let config = AppConfig {
+            nickname: auth.admin_trigger_nickname.clone().unwrap(),
+            server: ServerConfig {
+                password: Some("normal-password".to_string()),
+                ..ServerConfig::default()
+            },
+            ..AppConfig::default()
+        };
+
+        let profile = derive_auth_profile(&config);
+        assert_eq!(Some(profile.auth_username), auth.admin_username);
+        assert_eq!(profile.auth_password, auth.admin_password);
+    }
+
+    #[test]
+    fn derive_auth_profile_uses_explicit_admin_pair_only_when_opted_in() {
+        let mut config = AppConfig {
+            nickname: "friend01".to_string(),
+            auth: AuthConfig {
+                admin_trigger_nickname: None,
+                admin_username: Some("ops".to_string()),
+                admin_password: Some("ops-password".to_string()),
+                ..AuthConfig::default()
+            },
+            ..AppConfig::default()
+        };
+
+        // A null trigger turns the admin login off rather than on.
+        assert_eq!(derive_auth_profile(&config).auth_username, "friend01");
+
+        config.auth.use_admin_account = true;
+        let profile = derive_auth_profile(&config);
+        assert_eq!(profile.auth_username, "ops");
+        assert_eq!(profile.auth_password.as_deref(), Some("ops-password"));
+    }
+
+    #[test]
+    fn derive_auth_profile_uses_nickname_and_normal_password_for_regular_users() {
+        let config = AppConfig {
+            nickname: "friend01".to_string(),
+            server: ServerConfig {
+                password: Some("custom-normal-password".to_string()),
+                ..ServerConfig::default()
+            },
+            ..AppConfig::default()
+        };
+
+        let profile = derive_auth_profile(&config);
+        assert_eq!(profile.auth_username, "friend01");
+        assert_eq!(
+            profile.auth_password.as_deref(),
+            Some("custom-normal-password")
+        );
+    }
+
+    #[test]
+    fn derive_auth_profile_falls_back_to_default_user_password() {
+        let config = AppConfig {
+            nickname: "friend02".to_string(),
+            server: ServerConfig {
+                password: None,
+                ..ServerConfig::default()
+            },
+            ..AppConfig::default()
+        };
+
+        let profile = derive_auth_profile(&config);
+        assert_eq!(profile.auth_username, "friend02");
+        assert_eq!(
+            profile.auth_password,
+            AuthConfig::default().fallback_password
+        );
+    }
+
+    #[test]
+    fn next_connecting_state_only_uses_connecting_for_initial_attempt() {
+        assert_eq!(next_connecting_state(0, false), ConnectionState::Connecting);
+        assert_eq!(
+            next_connecting_state(1, false),
+            ConnectionState::Reconnecting
+        );
+        assert_eq!(
+            next_connecting_state(0, true),
+            ConnectionState::Reconnecting
+        );
+    }
+
+    #[test]
+    fn reconnect_delay_uses_exponential_backoff_with_cap() {
+        assert_eq!(reconnect_delay(1, 32), Duration::from_secs(2));
+        assert_eq!(reconnect_delay(2, 32), Duration::from_secs(4));
+        assert_eq!(reconnect_delay(5, 32), Duration::from_secs(32));
+        assert_eq!(reconnect_delay(6, 32), Duration::from_secs(32));
+        assert_eq!(reconnect_delay(100, 32), Duration::from_secs(32));
+    }
+
+    #[test]
+    fn reconnect_delay_honors_configured_cap() {
+        assert_eq!(reconnect_delay(3, 5), Duration::from_secs(5));
+        assert_eq!(reconnect_delay(10, 300), Duration::from_secs(300));
+        assert_eq!(reconnect_delay(4, 0), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn retry_now_ends_the_backoff_but_other_commands_do_not() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tx.send(VoiceCommand::SetMute(true)).unwrap();
+        tx.send(VoiceCommand::RetryNow).unwrap();
+        let waited = timeout(
+            Duration::from_secs(5),
+            wait_for_retry_or_disconnect(&mut rx, Duration::from_secs(60)),
+        )
+        .await;
+        assert_eq!(waited, Ok(false));
+
+        tx.send(VoiceCommand::SetMute(false)).unwrap();
+        tx.send(VoiceCommand::Disconnect).unwrap();
+        assert!(wait_for_retry_or_disconnect(&mut rx, Duration::from_secs(60)).await);
+    }
+
+    #[test]
+    fn reconnect_attempts_exhausted_treats_zero_as_unlimited() {
+        assert!(!reconnect_attempts_exhausted(1_000, 0));
+        assert!(!reconnect_attempts_exhausted(3, 3));
+        assert!(reconnect_attempts_exhausted(4, 3));
+    }
+
+    #[test]
+    fn take_pending_roster_event_skips_clean_and_unchanged_rosters() {
+        let mut roster = ProtocolRoster::new("Game Night".to_string());
+        assert!(roster.take_pending_roster_event().is_none());
+
+        roster.mark_dirty();
+        assert!(roster.take_pending_roster_event().is_some());
+
+        roster.mark_dirty();
+        assert!(roster.take_pending_roster_event().is_none());
+
+        let mut msg = msgs::UserState::new();
+        msg.set_session(7);
+        msg.set_name("guest".to_string());
+        roster.apply_user_state(&msg, &SelfEvent::default());
+        roster.mark_dirty();
+        let roster_event = roster
+            .take_pending_roster_event()
+            .expect("new user should produce a roster event");
+        assert_eq!(roster_event.users.len(), 1);
+    }
+
+    #[test]
+    fn speaking_starts_on_packet_arrival_and_expires_after_silence() {
+        let mut roster = ProtocolRoster::new("Game Night".to_string());
+        roster.users.insert(7, ProtocolUser::new(7));
+
+        let started = roster
+            .maybe_mark_speaking(7, None)
+            .expect("first packet starts");
+        assert!(started.speaking);
+        assert_eq!(roster.maybe_mark_speaking(7, None), None);
+        assert!(roster.expire_speaking(Duration::from_secs(1)).is_empty());
+
+        roster.users.get_mut(&7).unwrap().speaking_at =
+            Some(Instant::now() - Duration::from_secs(2));
+        let expired = roster.expire_speaking(Duration::from_secs(1));
+        assert_eq!(expired.len(), 1);
+        assert!(!expired[0].speaking);
+        assert!(roster.maybe_mark_speaking(7, None).is_some());
+    }
+
+    #[test]
+    fn join_target_prefers_the_rejoin_channel_until_it_is_known_missing() {
+        let mut roster = ProtocolRoster::new("Game Night".to_string());
+        roster.rejoin_channel_name = Some("Raid".to_string());
+        roster.channels.insert(1, "Game Night".to_string());
+        assert_eq!(roster.join_target_channel_id(), None);
+
+        roster.set_self_session(5);
+        assert_eq!(roster.join_target_channel_id(), Some(1));
+
+        roster.channels.insert(2, "Raid".to_string());
+        assert_eq!(roster.join_target_channel_id(), Some(2));
+
+        roster.auto_join_default_channel = false;
+        assert_eq!(roster.join_target_channel_id(), Some(2));
+        roster.rejoin_channel_name = None;
+        assert_eq!(roster.join_target_channel_id(), None);
+    }
+
+    #[test]
+    fn initial_prefill_starts_playout_before_the_jitter_target() {
+        let waiting = || {
+            let mut stream = InboundVoiceStream {
+                expected_seq: Some(0),
+                ..InboundVoiceStream::default()
+            };
+            stream.buffered.insert(0, vec![1]);
+            stream
+        };
+
+        let mut stream = waiting();
+        assert!(collect_decode_actions(&mut stream, false, JitterTuning::new(4, 10)).is_empty());
+        assert!(!stream.started);
+
+        let mut stream = waiting();
+        let tuning = JitterTuning::new(4, 10).with_prefill(Some(1));
+        let actions = collect_decode_actions(&mut stream, false, tuning);
+        assert!(matches!(&actions[..], [DecodeAction::Frame(frame)] if frame == &[1]));
+        assert_eq!(tuning.target_frames, 4);
+
+        assert_eq!(
+            JitterTuning::new(4, 10)
+                .with_prefill(Some(40))
+                .start_frames(),
+            10
+        );
+    }
+
+    #[test]
+    fn collect_decode_actions_recovers_single_loss_with_fec() {
+        let mut stream = InboundVoiceStream {
+            expected_seq: Some(0),
+            ..InboundVoiceStream::default()
+        };
+        stream.buffered.insert(0, vec![1]);
+        stream.buffered.insert(OPUS_SEQ_STEP * 2, vec![3]);
+        stream.buffered.insert(OPUS_SEQ_STEP * 5, vec![6]);
+
+        let actions = collect_decode_actions(&mut stream, true, JitterTuning::new(1, 10));
+        assert_eq!(actions.len(), 6);
+        assert!(matches!(&actions[0], DecodeAction::Frame(frame) if frame == &[1]));
+        assert!(matches!(&actions[1], DecodeAction::RecoverWithFec(frame) if frame == &[3]));
+        assert!(matches!(&actions[2], DecodeAction::Frame(frame) if frame == &[3]));
+        assert!(matches!(&actions[3], DecodeAction::ConcealLoss));
+        assert!(matches!(&actions[4], DecodeAction::RecoverWithFec(frame) if frame == &[6]));
+        assert!(matches!(&actions[5], DecodeAction::Frame(frame) if frame == &[6]));
+    }
+
+    #[test]
+    fn presence_events_skip_initial_sync_and_self() {
+        let mut roster = ProtocolRoster::new("Game Night".to_string());
+        let mut guest = msgs::UserState::new();
+        guest.set_session(7);
+        guest.set_name("guest".to_string());
+        guest.set_channel_id(3);
+        roster.apply_user_state(&guest, &SelfEvent::default());
+        assert!(roster.presence_event(7, true).is_none());
+
+        let mut own = msgs::UserState::new();
+        own.set_session(42);
+        own.set_channel_id(3);
+        roster.set_self_session(42);
+        roster.apply_user_state(&own, &SelfEvent::default());
+        assert!(roster.in_target_channel(7));
+        assert!(roster.presence_event(42, true).is_none());
+
+        let payload = roster
+            .presence_event(7, false)
+            .expect("other users should produce presence events after sync");
+        assert_eq!(payload.user_id, "7");
+        assert_eq!(payload.name, "guest");
+        assert!(!payload.joined);
+
+        guest.set_channel_id(4);
+        roster.apply_user_state(&guest, &SelfEvent::default());
+        assert!(!roster.in_target_channel(7));
+    }
+
+    #[test]
+    fn comment_hash_without_comment_requests_the_blob_once() {
+        let mut roster = ProtocolRoster::new("Game Night".to_string());
+        let mut msg = msgs::UserState::new();
+        msg.set_session(7);
+        msg.set_comment_hash(vec![1, 2, 3]);
+        roster.apply_user_state(&msg, &SelfEvent::default());
+
+        assert_eq!(roster.comment_blob_request(&msg), Some(7));
+        assert_eq!(roster.comment_blob_request(&msg), None);
+
+        msg.set_comment_hash(vec![4, 5, 6]);
+        assert_eq!(roster.comment_blob_request(&msg), Some(7));
+
+        let mut reply = msgs::UserState::new();
+        reply.set_session(7);
+        reply.set_comment("harmony_badges:v1:party-parrot".to_string());
+        assert_eq!(roster.comment_blob_request(&reply), None);
+        roster.apply_user_state(&reply, &SelfEvent::default());
+        assert_eq!(
+            roster.users[&7].badge_codes,
+            vec!["party-parrot".to_string()]
+        );
+    }
+
+    #[test]
+    fn all_users_muted_marks_everyone_but_self_in_the_roster() {
+        let mut roster = ProtocolRoster::new("Game Night".to_string());
+        roster.set_self_session(42);
+        for (session, name) in [(42, "mason"), (7, "guest")] {
+            let mut msg = msgs::UserState::new();
+            msg.set_session(session);
+            msg.set_name(name.to_string());
+            roster.apply_user_state(&msg, &SelfEvent::default());
+        }
+
+        roster.all_users_muted = true;
+        let muted = roster
+            .build_roster_event()
+            .users
+            .into_iter()
+            .map(|user| (user.id, user.locally_muted))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            muted,
+            vec![("7".to_string(), true), ("42".to_string(), false)]
+        );
+
+        roster.all_users_muted = false;
+        assert!(roster
+            .build_roster_event()
+            .users
+            .iter()
+            .all(|user| !user.locally_muted));
+    }
+
+    #[test]
+    fn apply_user_state_preserves_ptt_and_transmitting_for_self_events() {
+        let mut roster = ProtocolRoster::new("Game Night".to_string());
+        roster.set_self_session(42);
+
+        let mut msg = msgs::UserState::new();
+        msg.set_session(42);
+        msg.set_name("mason".to_string());
+        msg.set_self_mute(true);
+
+        let current_self = SelfEvent {
+            muted: false,
+            deafened: false,
+            ptt_enabled: true,
+            ptt_mode: PttMode::PushToTalk,
+            transmitting: true,
+            listen_only: false,
+            all_users_muted: false,
+        };
+
+        let (_changed, maybe_self) = roster.apply_user_state(&msg, &current_self);
+        let self_event = maybe_self.expect("self event should be present");
+
+        assert_eq!(
+            self_event,
+            SelfEvent {
+                muted: true,
+                deafened: false,
+                ptt_enabled: true,
+                ptt_mode: PttMode::PushToTalk,
+                transmitting: true,
+                listen_only: false,
+                all_users_muted: false,
+            }
+        );
+    }
+
+    #[test]
+    fn channel_listener_round_trips_through_unknown_user_state_fields() {
+        let mut roster = ProtocolRoster::new("Lobby".to_string());
+        roster.set_self_session(9);
+        let self_state = SelfEvent::default();
+
+        // Re-parse the wire bytes so the fields arrive the way the server's would.
+        let reparse = |state: msgs::UserState| {
+            msgs::UserState::parse_from_bytes(&state.write_to_bytes().unwrap()).unwrap()
+        };
+        let (changed, _) =
+            roster.apply_user_state(&reparse(channel_listener_state(9, 4, true)), &self_state);
+        assert!(changed);
+        let (changed, _) =
+            roster.apply_user_state(&reparse(channel_listener_state(9, 2, true)), &self_state);
+        assert!(changed);
+        assert_eq!(
+            roster.build_roster_event().users[0].listening_channel_ids,
+            vec!["2".to_string(), "4".to_string()]
+        );
+
+        let (changed, _) =
+            roster.apply_user_state(&reparse(channel_listener_state(9, 4, true)), &self_state);
+        assert!(!changed);
+        roster.apply_user_state(&reparse(channel_listener_state(9, 4, false)), &self_state);
+        assert_eq!(
+            roster.build_roster_event().users[0].listening_channel_ids,
+            vec!["2".to_string()]
+        );
+    }
+
+    #[test]
+    fn priority_speaker_flag_survives_updates_that_omit_it() {
+        let mut roster = ProtocolRoster::new("Game Night".to_string());
+        let current_self = SelfEvent::default();
+
+        let mut msg = msgs::UserState::new();
+        msg.set_session(9);
+        msg.set_priority_speaker(true);
+        assert!(roster.apply_user_state(&msg, &current_self).0);
+        assert!(roster.is_priority_speaker(9));
+
+        let mut rename = msgs::UserState::new();
+        rename.set_session(9);
+        rename.set_name("dj".to_string());
+        roster.apply_user_state(&rename, &current_self);
+        assert!(roster.is_priority_speaker(9));
+        assert!(roster.build_roster_event().users[0].priority_speaker);
+
+        msg.set_priority_speaker(false);
+        assert!(roster.apply_user_state(&msg, &current_self).0);
+        assert!(!roster.is_priority_speaker(9));
+    }
+
+    #[test]
+    fn mix_soundboard_voices_layers_overlapping_clips() {
+        let mut voices = Vec::new();
+        start_soundboard_voice(&mut voices, vec![0.2; 4]);
+        start_soundboard_voice(&mut voices, vec![0.3; 8]);
+
+        let mut frame = vec![0.0_f32; 4];
+        assert!(mix_soundboard_voices(
+            &mut voices,
+            &mut frame,
+            SoundboardConfig::default()
+        ));
+        assert!(frame
+            .iter()
+            .all(|sample| (sample - 0.5 * SoundboardConfig::default().mix_gain).abs() < 1e-6));
+        assert_eq!(voices.len(), 1);
+
+        let mut frame = vec![0.0_f32; 8];
+        assert!(mix_soundboard_voices(
+            &mut voices,
+            &mut frame,
+            SoundboardConfig::default()
+        ));
+        assert!(voices.is_empty());
+        assert!(!mix_soundboard_voices(
+            &mut voices,
+            &mut frame,
+            SoundboardConfig::default()
+        ));
+    }
+
+    #[test]
+    fn duck_voice_attenuates_mic_only_while_a_clip_plays() {
+        let mix = SoundboardConfig {
+            mix_gain: 0.5,
+            duck_voice: true,
+        };
+        let mut voices = Vec::new();
+        let mut frame = vec![0.4_f32; 4];
+        assert!(!mix_soundboard_voices(&mut voices, &mut frame, mix));
+        assert!(frame.iter().all(|sample| (sample - 0.4).abs() < 1e-6));
+
+        start_soundboard_voice(&mut voices, vec![0.2; 4]);
+        assert!(mix_soundboard_voices(&mut voices, &mut frame, mix));
+        let expected = 0.4 * SOUNDBOARD_DUCK_GAIN + 0.2 * 0.5;
+        assert!(frame.iter().all(|sample| (sample - expected).abs() < 1e-6));
+    }
+
+    #[test]
+    fn start_soundboard_voice_fades_out_the_oldest_clip_at_the_cap() {
+        let mut voices = Vec::new();
+        for _ in 0..SOUNDBOARD_MAX_VOICES {
+            start_soundboard_voice(&mut voices, vec![0.2; OPUS_FRAME_SAMPLES * 4]);
+        }
+        let mut output = vec![0.0_f32; OPUS_FRAME_SAMPLES];
+        mix_soundboard_voices(&mut voices, &mut output, SoundboardConfig::default());
+
+        start_soundboard_voice(&mut voices, vec![0.0; OPUS_FRAME_SAMPLES * 4]);
+        for _ in 0..2 {
+            let mut frame = vec![0.0_f32; OPUS_FRAME_SAMPLES];
+            mix_soundboard_voices(&mut voices, &mut frame, SoundboardConfig::default());
+            output.extend(frame);
+        }
+
+        assert_eq!(voices.len(), SOUNDBOARD_MAX_VOICES);
+        let max_step = output
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .fold(0.0_f32, f32::max);
+        assert!(max_step < 0.01, "max step {max_step}");
+        let settled = output.last().copied().unwrap_or_default();
+        let expected =
+            0.2 * SoundboardConfig::default().mix_gain * (SOUNDBOARD_MAX_VOICES - 1) as f32;
+        assert!((settled - expected).abs() < 1e-5);
+    }
+
+    fn open_tx_inputs() -> TxGateInputs {
+        TxGateInputs {
+            muted: false,
+            deafened: false,
+            ptt_mode: PttMode::Vad,
+            ptt_key_held: false,
+            soundboard_mixed: false,
+            tx_limiter: true,
+        }
+    }
+
+    fn tone_frame(amplitude: f32) -> Vec<f32> {
+        (0..OPUS_FRAME_SAMPLES)
+            .map(|index| {
+                let phase =
+                    index as f32 * 440.0 * 2.0 * std::f32::consts::PI / OPUS_SAMPLE_RATE as f32;
+                amplitude * phase.sin()
+            })
+            .collect()
+    }
+
+    fn default_vad() -> VoiceActivityDetector {
+        VoiceActivityDetector::with_settings(VadSettings::new(&VoiceQualityConfig::default()))
+    }
+
+    #[test]
+    fn tx_pipeline_opens_on_tone_and_closes_after_hold() {
+        let mut vad = default_vad();
+        let inputs = open_tx_inputs();
+
+        let mut silence = vec![0.0; OPUS_FRAME_SAMPLES];
+        assert!(!process_tx_frame(&mut silence, inputs, &mut vad).should_tx);
+
+        let mut tone = tone_frame(0.3);
+        let outcome = process_tx_frame(&mut tone, inputs, &mut vad);
+        assert!(outcome.should_tx);
+        assert!(outcome.level > vad.on_threshold());
+        assert_eq!(outcome.clip_samples, 0);
+
+        let mut encoder =
+            OpusEncoder::new(OPUS_SAMPLE_RATE, Channels::Mono, Application::Voip).unwrap();
+        let mut pcm = Vec::new();
+        let mut packet = vec![0_u8; OPUS_MAX_PACKET_SIZE];
+        let written = encode_opus_frame(&mut encoder, &tone, &mut pcm, &mut packet).unwrap();
+        assert!(written > 0);
+
+        let hold = vad.hold_frames();
+        for _ in 0..hold {
+            let mut silence = vec![0.0; OPUS_FRAME_SAMPLES];
+            assert!(process_tx_frame(&mut silence, inputs, &mut vad).should_tx);
+        }
+        let mut silence = vec![0.0; OPUS_FRAME_SAMPLES];
+        assert!(!process_tx_frame(&mut silence, inputs, &mut vad).should_tx);
+    }
+
+    #[test]
+    fn tx_pipeline_counts_clipping_and_limiter_activity() {
+        let mut metrics = AudioQualityMetrics::default();
+        let mut vad = default_vad();
+
+        let mut hot = vec![1.5; OPUS_FRAME_SAMPLES];
+        let limited = process_tx_frame(&mut hot, open_tx_inputs(), &mut vad);
+        assert_eq!(limited.clip_samples, OPUS_FRAME_SAMPLES as u64);
+        assert_eq!(limited.limiter_activations, OPUS_FRAME_SAMPLES as u64);
+        assert!(hot.iter().all(|sample| sample.abs() <= 1.0));
+        limited.record(&mut metrics);
+
+        let mut hot = vec![1.5; OPUS_FRAME_SAMPLES];
+        let inputs = TxGateInputs {
+            tx_limiter: false,
+            ..open_tx_inputs()
+        };
+        let clamped = process_tx_frame(&mut hot, inputs, &mut vad);
+        assert_eq!(clamped.clip_samples, OPUS_FRAME_SAMPLES as u64);
+        assert_eq!(clamped.limiter_activations, 0);
+        assert!(hot.iter().all(|&sample| sample == 1.0));
+        clamped.record(&mut metrics);
+
+        assert_eq!(metrics.tx_clip_samples, 2 * OPUS_FRAME_SAMPLES as u64);
+        assert_eq!(metrics.tx_limiter_activations, OPUS_FRAME_SAMPLES as u64);
+    }
+
+    #[test]
+    fn tx_pipeline_gates_mic_on_mute_but_not_soundboard() {
+        let mut vad = default_vad();
+        let muted = TxGateInputs {
+            muted: true,
+            ..open_tx_inputs()
+        };
+        assert!(!process_tx_frame(&mut tone_frame(0.3), muted, &mut vad).should_tx);
+
+        let soundboard = TxGateInputs {
+            soundboard_mixed: true,
+            ..muted
+        };
+        assert!(process_tx_frame(&mut tone_frame(0.3), soundboard, &mut vad).should_tx);
+
+        let deafened = TxGateInputs {
+            deafened: true,
+            ..soundboard
+        };
+        assert!(!process_tx_frame(&mut tone_frame(0.3), deafened, &mut vad).should_tx);
+    }
+
+    #[test]
+    fn codec_version_without_opus_is_rejected() {
+        let mut legacy = msgs::CodecVersion::new();
+        legacy.set_alpha(-2147483637);
+        legacy.set_beta(0);
+        legacy.set_prefer_alpha(true);
+        assert!(!server_supports_opus(&legacy));
+
+        legacy.set_opus(false);
+        assert!(!server_supports_opus(&legacy));
+
+        legacy.set_opus(true);
+        assert!(server_supports_opus(&legacy));
+    }
+
+    #[test]
+    fn should_send_voice_frame_allows_soundboard_when_mic_gate_is_closed() {
+        assert!(should_send_voice_frame(true, false));
+        assert!(should_send_voice_frame(true, true));
+    }
+
+    #[test]
+    fn should_send_voice_frame_respects_mic_gate_without_soundboard() {
+        assert!(should_send_voice_frame(false, true));
+        assert!(!should_send_voice_frame(false, false));
+    }
+
+    #[test]
+    fn muted_speech_warns_once_per_stretch_and_respects_cooldown() {
+        let mut detector = MutedSpeechDetector::default();
+        let warnings = (0..MUTED_SPEECH_WARN_FRAMES * 2)
+            .filter(|_| detector.observe(true, true))
+            .count();
+        assert_eq!(warnings, 1);
+
+        // A pause starts a new stretch, but the cooldown still suppresses it.
+        assert!(!detector.observe(true, false));
+        assert!(!(0..MUTED_SPEECH_WARN_FRAMES).any(|_| detector.observe(true, true)));
+
+        let mut detector = MutedSpeechDetector::default();
+        assert!(!(0..MUTED_SPEECH_WARN_FRAMES * 2).any(|_| detector.observe(false, true)));
+    }
+
+    #[test]
+    fn one_way_audio_warns_once_when_server_stops_counting_udp() {
+        let mut detector = OneWayAudioDetector::default();
+        assert!(!detector.observe(50, Some(10)));
+        let warnings = (1..=ONE_WAY_AUDIO_WINDOWS as u64 * 2)
+            .filter(|window| detector.observe(50 + window * 50, Some(10)))
+            .count();
+        assert_eq!(warnings, 1);
+
+        // Server acknowledges again, so a later stall can warn afresh.
+        assert!(!detector.observe(10_000, Some(11)));
+        assert!(!detector.observe(10_000, Some(11)));
+    }
+
+    #[test]
+    fn output_latency_drain_prefers_quiet_frames_and_resets_below_the_mark() {
+        let mut drain = OutputLatencyDrain::default();
+        let drops = (0..OUTPUT_LATENCY_QUIET_DROP_MIXES * 2)
+            .filter(|_| drain.should_drop(5_000, 4_000, true))
+            .count();
+        assert_eq!(drops, 2);
+
+        let mut drain = OutputLatencyDrain::default();
+        assert!(
+            !(1..OUTPUT_LATENCY_FORCED_DROP_MIXES).any(|_| drain.should_drop(5_000, 4_000, false))
+        );
+        assert!(drain.should_drop(5_000, 4_000, false));
+
+        // Falling back under the mark starts the count over.
+        let mut drain = OutputLatencyDrain::default();
+        assert!(
+            !(1..OUTPUT_LATENCY_QUIET_DROP_MIXES).any(|_| drain.should_drop(5_000, 4_000, true))
+        );
+        assert!(!drain.should_drop(4_000, 4_000, true));
+        assert!(!drain.should_drop(5_000, 4_000, true));
+    }
+
+    #[test]
+    fn device_switch_debounce_applies_only_the_latest_request() {
+        let window = Duration::from_millis(DEVICE_SWITCH_DEBOUNCE_MS);
+        let start = Instant::now();
+        let mut debounce = DeviceSwitchDebounce::default();
+
+        debounce.request("Headset".to_string(), Some("Speakers"), start);
+        debounce.request(
+            "USB Audio Device".to_string(),
+            Some("Speakers"),
+            start + window / 2,
+        );
+        assert_eq!(debounce.take_due(start + window), None);
+        assert_eq!(
+            debounce.take_due(start + window / 2 + window),
+            Some("USB Audio Device".to_string())
+        );
+        assert_eq!(debounce.take_due(start + window * 4), None);
+
+        // Settling back on the active device cancels the pending switch.
+        debounce.request("Headset".to_string(), Some("Speakers"), start);
+        debounce.request("Speakers".to_string(), Some("Speakers"), start);
+        assert_eq!(debounce.take_due(start + window * 4), None);
+    }
+
+    #[test]
+    fn one_way_audio_ignores_windows_without_server_stats_or_tx() {
+        let mut detector = OneWayAudioDetector::default();
+        assert!(!(1..=ONE_WAY_AUDIO_WINDOWS as u64 * 2)
+            .any(|window| detector.observe(window * 50, None)));
+        assert!(!(0..ONE_WAY_AUDIO_WINDOWS * 2).any(|_| detector.observe(0, Some(3))));
+    }
+
+    #[test]
+    fn disabled_tx_limiter_passes_samples_through_with_a_hard_clamp() {
+        assert_eq!(shape_tx_sample(0.5, false), 0.5);
+        assert_eq!(shape_tx_sample(1.4, false), 1.0);
+        assert_eq!(shape_tx_sample(-1.4, false), -1.0);
+        assert_ne!(shape_tx_sample(0.5, true), 0.5);
+    }
+
+    #[test]
+    fn text_rate_limiter_blocks_bursts_and_refills_over_the_window() {
+        let start = Instant::now();
+        let mut limiter = TextRateLimiter::new(start);
+        assert!((0..TEXT_RATE_LIMIT_MESSAGES).all(|_| limiter.try_acquire(start)));
+        assert!(!limiter.try_acquire(start));
+
+        let one_slot = Duration::from_secs(TEXT_RATE_LIMIT_WINDOW_SECS) / TEXT_RATE_LIMIT_MESSAGES;
+        assert!(limiter.try_acquire(start + one_slot));
+        assert!(!limiter.try_acquire(start + one_slot));
+    }
+
+    #[test]
+    fn server_enforcement_reports_only_flags_that_contradict_local_intent() {
+        let mut enforcement = ServerEnforcement::default();
+        let mut msg = msgs::UserState::new();
+        msg.set_mute(true);
+        enforcement.apply(&msg);
+        assert!(enforcement.against_intent(false, false).muted);
+        assert!(!enforcement.against_intent(true, false).muted);
+
+        // A later UserState without the field keeps the server mute.
+        enforcement.apply(&msgs::UserState::new());
+        assert!(enforcement.against_intent(false, false).muted);
+
+        let mut msg = msgs::UserState::new();
+        msg.set_mute(false);
+        msg.set_deaf(true);
+        enforcement.apply(&msg);
+        assert_eq!(
+            enforcement.against_intent(false, false),
+            SelfEnforcedEvent {
+                muted: false,
+                deafened: true,
+            }
+        );
+    }
+
+    #[test]
+    fn bandwidth_meter_reports_kbps_and_resets_each_sample() {
+        let start = Instant::now();
+        let mut meter = BandwidthMeter::new(start);
+        meter.record(3_000);
+        meter.record(1_000);
+        let kbps = meter.sample_kbps(start + Duration::from_secs(1));
+        assert!((kbps - 32.0).abs() < 0.01);
+        assert_eq!(meter.sample_kbps(start + Duration::from_secs(2)), 0.0);
+    }
+
+    #[test]
+    fn packet_size_bucket_splits_at_bucket_edges() {
+        assert_eq!(packet_size_bucket(3), 0);
+        assert_eq!(packet_size_bucket(49), 0);
+        assert_eq!(packet_size_bucket(50), 1);
+        assert_eq!(packet_size_bucket(199), 2);
+        assert_eq!(packet_size_bucket(200), 3);
+        assert_eq!(packet_size_bucket(1_275), 3);
+    }
+
+    #[test]
+    fn record_codec_time_tracks_max_and_running_mean() {
+        let (mut max_us, mut avg_us, mut frames) = (0, 0.0, 0);
+        for us in [100, 300, 200] {
+            record_codec_time(
+                &mut max_us,
+                &mut avg_us,
+                &mut frames,
+                Duration::from_micros(us),
+            );
+        }
+        assert_eq!(max_us, 300);
+        assert_eq!(frames, 3);
+        assert!((avg_us - 200.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn push_to_mute_closes_gate_while_key_is_held() {
+        assert!(ptt_gate_open(PttMode::PushToMute, false, true));
+        assert!(!ptt_gate_open(PttMode::PushToMute, true, true));
+        assert!(!ptt_gate_open(PttMode::PushToMute, false, false));
+        assert!(ptt_gate_open(PttMode::Vad, true, true));
+    }
+
+    #[test]
+    fn jitter_tuning_clamps_live_values_and_keeps_max_above_target() {
+        let tuning = JitterTuning::new(20, 1);
+        assert_eq!(tuning.baseline_target_frames, RX_JITTER_TARGET_MAX);
+        assert_eq!(tuning.baseline_max_frames, RX_JITTER_TARGET_MAX + 2);
+        assert_eq!(tuning.target_frames, tuning.baseline_target_frames);
+        assert_eq!(tuning.max_frames, tuning.baseline_max_frames);
+    }
+
+    #[test]
+    fn permission_denied_event_prefers_server_reason() {
+        let mut msg = msgs::PermissionDenied::new();
+        msg.set_field_type(msgs::PermissionDenied_DenyType::Permission);
+        msg.set_channel_id(7);
+        msg.set_reason("Channel is password protected".to_string());
+
+        let event = permission_denied_event(&msg);
+        assert_eq!(event.denial_type, "permission");
+        assert_eq!(event.reason, "Channel is password protected");
+        assert_eq!(event.channel_id.as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn permission_denied_event_falls_back_to_type_description() {
+        let mut msg = msgs::PermissionDenied::new();
+        msg.set_field_type(msgs::PermissionDenied_DenyType::TextTooLong);
+
+        let event = permission_denied_event(&msg);
+        assert_eq!(event.denial_type, "text_too_long");
+        assert_eq!(event.reason, "message is too long");
+        assert_eq!(event.channel_id, None);
+    }
+
+    #[test]
+    fn bitrate_ceiling_subtracts_packet_overhead() {
+        assert_eq!(bitrate_ceiling_for_bandwidth(72_000), 72_000 - 14_800);
+        assert_eq!(
+            bitrate_ceiling_for_bandwidth(u32::MAX),
+            OPUS_BITRATE_MAX_BPS
+        );
+        assert_eq!(
+            bitrate_ceiling_for_bandwidth(10_000),
+            OPUS_BITRATE_FLOOR_BPS
+        );
+    }
+
+    #[test]
+    fn smooth_rtt_seeds_with_first_sample_then_averages() {
+        assert_eq!(smooth_rtt(None, 40.0), 40.0);
+        assert_eq!(smooth_rtt(Some(40.0), 120.0), 50.0);
+    }
+
+    #[test]
+    fn packet_loss_percent_counts_late_and_lost_packets() {
+        assert_eq!(packet_loss_percent(0, 0, 0), 0.0);
+        assert_eq!(packet_loss_percent(90, 5, 5), 10.0);
+    }
+
+    #[test]
+    fn build_vad_clamps_threshold_and_keeps_off_ratio() {
+        let build_vad = |threshold, off_threshold| {
+            VoiceActivityDetector::with_settings(VadSettings {
+                threshold,
+                off_threshold,
+                hold_frames: 5,
+                attack_frames: 1,
+                smoothing: 0.0,
+            })
+        };
+        let vad = build_vad(2.0, None);
+        assert_eq!(vad.on_threshold(), 0.5);
+        assert!((vad.off_threshold() - 0.35).abs() < f32::EPSILON);
+
+        let vad = build_vad(0.02, Some(0.05));
+        assert_eq!(vad.off_threshold(), 0.02);
+    }
+
+    #[test]
+    fn encode_opus_frame_with_reused_scratch_matches_fresh_buffers() {
+        let new_encoder = || {
+            let mut encoder =
+                OpusEncoder::new(OPUS_SAMPLE_RATE, Channels::Mono, Application::Voip).unwrap();
+            configure_encoder(
+                &mut encoder,
+                CodecTuning::new_from_config(&AppConfig::default()),
+            )
+            .unwrap();
+            encoder
+        };
+        let mut fresh_encoder = new_encoder();
+        let mut reused_encoder = new_encoder();
+        let mut pcm_scratch = Vec::with_capacity(OPUS_FRAME_SAMPLES);
+        let mut packet_scratch = vec![0_u8; OPUS_MAX_PACKET_SIZE];
+
+        for frame_index in 0..50 {
+            let frame: Vec<f32> = if frame_index % 10 < 7 {
+                (0..OPUS_FRAME_SAMPLES)
+                    .map(|i| {
+                        let t = (frame_index * OPUS_FRAME_SAMPLES + i) as f32;
+                        (t * 440.0 * std::f32::consts::TAU / OPUS_SAMPLE_RATE as f32).sin() * 0.4
+                    })
+                    .collect()
+            } else {
+                vec![0.0_f32; OPUS_FRAME_SAMPLES]
+            };
+
+            let mut fresh_pcm = Vec::new();
+            let mut fresh_packet = vec![0_u8; OPUS_MAX_PACKET_SIZE];
+            let fresh_len = encode_opus_frame(
+                &mut fresh_encoder,
+                &frame,
+                &mut fresh_pcm,
+                &mut fresh_packet,
+            )
+            .unwrap();
+            let reused_len = encode_opus_frame(
+                &mut reused_encoder,
+                &frame,
+                &mut pcm_scratch,
+                &mut packet_scratch,
+            )
+            .unwrap();
+
+            assert_eq!(fresh_packet[..fresh_len], packet_scratch[..reused_len]);
+            assert_eq!(pcm_scratch.len(), OPUS_FRAME_SAMPLES);
+        }
+    }
+
+    #[test]
+    fn pcm_to_f32_into_overwrites_reused_buffer() {
+        let mut out = vec![f32::NAN; OPUS_FRAME_SAMPLES];
+        let nan_samples = pcm_to_f32_into(&[0, i16::MAX, -i16::MAX], &mut out);
+        assert_eq!(nan_samples, 0);
+        assert_eq!(out, vec![0.0, 1.0, -1.0]);
+
+        let nan_samples = pcm_to_f32_into(&[i16::MAX], &mut out);
+        assert_eq!(nan_samples, 0);
+        assert_eq!(out, vec![1.0]);
+    }
+
+    #[test]
+    fn position_info_round_trips_and_rejects_short_payloads() {
+        let encoded = encode_position_info([1.5, -2.0, 30.25]);
+        assert_eq!(encoded.len(), POSITION_INFO_LEN);
+        assert_eq!(decode_position_info(&encoded), Some([1.5, -2.0, 30.25]));
+        assert_eq!(decode_position_info(&encoded[..8]), None);
+    }
+
+    #[test]
+    fn position_info_matches_mumble_little_endian_floats() {
+        let wire = [
+            0x00, 0x00, 0xC0, 0x3F, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0xF2, 0x41,
+        ];
+        assert_eq!(decode_position_info(&wire), Some([1.5, -2.0, 30.25]));
+        assert_eq!(&encode_position_info([1.5, -2.0, 30.25])[..], &wire[..]);
+    }
+
+    #[test]
+    fn positional_pan_gain_pans_by_side_and_attenuates_with_distance() {
+        let (pan, gain) = positional_pan_gain([0.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        assert_eq!(pan, 1.0);
+        assert_eq!(gain, 1.0);
+
+        let (pan, gain) = positional_pan_gain([0.0, 0.0, 0.0], [-100.0, 0.0, 0.0]);
+        assert_eq!(pan, -1.0);
+        assert!((gain - POSITIONAL_MIN_GAIN).abs() < 1e-6);
+
+        let (pan, _) = positional_pan_gain([0.0, 0.0, 0.0], [0.0, 0.0, 10.0]);
+        assert_eq!(pan, 0.0);
+    }
+
+    #[test]
+    fn pick_srv_target_prefers_priority_then_weight() {
+        let records = vec![
+            (20, 100, 64740, "backup.example.com.".to_string()),
+            (10, 5, 64739, "light.example.com.".to_string()),
+            (10, 50, 64738, "voice.example.com.".to_string()),
+        ];
+        assert_eq!(
+            pick_srv_target(records),
+            Some(("voice.example.com".to_string(), 64738))
+        );
+        assert_eq!(pick_srv_target(vec![(0, 0, 0, ".".to_string())]), None);
+        assert_eq!(pick_srv_target(Vec::new()), None);
+    }
+
+    #[test]
+    fn interleave_address_families_alternates_and_dedupes() {
+        let v6a: SocketAddr = "[2001:db8::1]:64738".parse().unwrap();
+        let v6b: SocketAddr = "[2001:db8::2]:64738".parse().unwrap();
+        let v4a: SocketAddr = "192.0.2.1:64738".parse().unwrap();
+        let v4b: SocketAddr = "192.0.2.2:64738".parse().unwrap();
+
+        assert_eq!(
+            interleave_address_families(vec![v6a, v6b, v6a, v4a, v4b]),
+            vec![v6a, v4a, v6b, v4b]
+        );
+        assert_eq!(
+            interleave_address_families(vec![v4a, v6a, v6b]),
+            vec![v4a, v6a, v6b]
+        );
+    }
+
+    #[test]
+    fn pack_mumble_version_encodes_major_minor_patch() {
+        assert_eq!(pack_mumble_version(1, 4, 0), 0x010400);
+        assert_eq!(pack_mumble_version(1, 5, 9), 0x010509);
+        assert_eq!(pack_mumble_version(2, 255, 255), 0x02FFFF);
+        assert_eq!(unpack_mumble_version(0x010509), (1, 5, 9));
+        assert_eq!(
+            unpack_mumble_version(pack_mumble_version(2, 255, 255)),
+            (2, 255, 255)
+        );
+    }
+}
@@ -1,14 +1,31 @@
-#![allow(dead_code)]
+use serde::Serialize;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use super::client::{OPUS_FRAME_SAMPLES, OPUS_SAMPLE_RATE};
+
+pub const OPUS_SUPPORTED_SAMPLE_RATES: [u32; 5] = [8_000, 12_000, 16_000, 24_000, 48_000];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CodecCapabilities {
     pub opus_backend: &'static str,
+    pub supported_sample_rates: Vec<u32>,
+    pub sample_rate: u32,
+    pub frame_samples: usize,
+    pub frame_ms: u32,
+    pub fec_available: bool,
+    pub dtx_available: bool,
 }
 
 impl Default for CodecCapabilities {
     fn default() -> Self {
+        let opus_enabled = cfg!(feature = "real-opus");
         Self {
             opus_backend: opus_backend_name(),
+            supported_sample_rates: OPUS_SUPPORTED_SAMPLE_RATES.to_vec(),
+            sample_rate: OPUS_SAMPLE_RATE,
+            frame_samples: OPUS_FRAME_SAMPLES,
+            frame_ms: (OPUS_FRAME_SAMPLES as u64 * 1_000 / u64::from(OPUS_SAMPLE_RATE)) as u32,
+            fec_available: opus_enabled,
+            dtx_available: opus_enabled,
         }
     }
 }
@@ -16,7 +33,7 @@ impl Default for CodecCapabilities {
 pub fn opus_backend_name() -> &'static str {
     #[cfg(feature = "real-opus")]
     {
-        return "opus2";
+        "opus2"
     }
 
     #[cfg(not(feature = "real-opus"))]
@@ -24,3 +41,17 @@ pub fn opus_backend_name() -> &'static str {
         "disabled"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_capabilities_describe_active_frame_format() {
+        let capabilities = CodecCapabilities::default();
+        assert_eq!(capabilities.frame_ms, 20);
+        assert!(capabilities
+            .supported_sample_rates
+            .contains(&capabilities.sample_rate));
+    }
+}
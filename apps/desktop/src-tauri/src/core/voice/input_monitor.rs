@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tauri::AppHandle;
+use tokio::sync::oneshot;
+
+use super::audio_in;
+use super::quality::rms_level;
+use crate::core::events::{self, InputLevelEvent};
+
+const INPUT_MONITOR_EMIT_INTERVAL_MS: u64 = 100;
+
+// cpal streams are not Send, so the monitor capture lives on its own blocking task.
+pub struct InputMonitor {
+    worker: Option<tauri::async_runtime::JoinHandle<()>>,
+    stop_flag: Option<Arc<AtomicBool>>,
+}
+
+impl InputMonitor {
+    pub fn new() -> Self {
+        Self {
+            worker: None,
+            stop_flag: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.worker.is_some()
+    }
+
+    pub async fn start(
+        &mut self,
+        app: AppHandle,
+        device_id: Option<String>,
+        prefer_48k: bool,
+        host_id: Option<String>,
+    ) -> Result<(), String> {
+        self.stop().await;
+
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let worker_stop_flag = Arc::clone(&stop_flag);
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let handle = tauri::async_runtime::spawn_blocking(move || {
+            run_input_monitor(
+                app,
+                device_id,
+                prefer_48k,
+                host_id,
+                worker_stop_flag,
+                ready_tx,
+            );
+        });
+
+        match ready_rx.await {
+            Ok(Ok(())) => {
+                self.worker = Some(handle);
+                self.stop_flag = Some(stop_flag);
+                Ok(())
+            }
+            Ok(Err(err)) => {
+                let _ = handle.await;
+                Err(err)
+            }
+            Err(_) => {
+                let _ = handle.await;
+                Err("input monitor exited before starting".to_string())
+            }
+        }
+    }
+
+    pub async fn stop(&mut self) {
+        if let Some(stop_flag) = self.stop_flag.take() {
+            stop_flag.store(true, Ordering::Relaxed);
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.await;
+        }
+    }
+}
+
+fn run_input_monitor(
+    app: AppHandle,
+    device_id: Option<String>,
+    prefer_48k: bool,
+    host_id: Option<String>,
+    stop_flag: Arc<AtomicBool>,
+    ready_tx: oneshot::Sender<Result<(), String>>,
+) {
+    let capture =
+        match audio_in::start_input_capture(device_id.as_deref(), prefer_48k, host_id.as_deref()) {
+            Ok(capture) => capture,
+            Err(err) => {
+                let _ = ready_tx.send(Err(err));
+                return;
+            }
+        };
+    let _ = ready_tx.send(Ok(()));
+    log::info!(
+        "input monitor started: device=\"{}\"",
+        capture.device_name()
+    );
+
+    let mut window = Vec::new();
+    while !stop_flag.load(Ordering::Relaxed) {
+        std::thread::sleep(Duration::from_millis(INPUT_MONITOR_EMIT_INTERVAL_MS));
+        window.clear();
+        capture.drain_samples(&mut window);
+        let payload = InputLevelEvent {
+            level: rms_level(&window).clamp(0.0, 1.0),
+        };
+        let _ = events::emit_input_level(&app, &payload);
+    }
+
+    log::info!("input monitor stopped");
+}
@@ -0,0 +1,121 @@
+pub const LOSS_INJECTION_MAX_PERCENT: f32 = 50.0;
+
+// Developer knob for exercising jitter, PLC and FEC without a lossy network.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LossInjection {
+    pub loss_percent: f32,
+    pub reorder_percent: f32,
+}
+
+impl LossInjection {
+    pub fn is_active(&self) -> bool {
+        self.loss_percent > 0.0 || self.reorder_percent > 0.0
+    }
+}
+
+// Drops or holds back inbound frames at random; a held frame is released after the next one.
+#[derive(Debug)]
+pub struct LossInjector<T> {
+    settings: LossInjection,
+    rng_state: u64,
+    held: Option<T>,
+    dropped: u64,
+}
+
+impl<T> LossInjector<T> {
+    pub fn new(settings: LossInjection, seed: u64) -> Self {
+        Self {
+            settings,
+            // xorshift never leaves zero.
+            rng_state: seed | 1,
+            held: None,
+            dropped: 0,
+        }
+    }
+
+    pub fn settings(&self) -> LossInjection {
+        self.settings
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    pub fn process(&mut self, item: T, delivered: &mut Vec<T>) {
+        let roll = self.next_unit() * 100.0;
+        if roll < self.settings.loss_percent {
+            self.dropped = self.dropped.saturating_add(1);
+            return;
+        }
+        if roll < self.settings.loss_percent + self.settings.reorder_percent {
+            if let Some(previous) = self.held.replace(item) {
+                delivered.push(previous);
+            }
+            return;
+        }
+        delivered.push(item);
+        if let Some(previous) = self.held.take() {
+            delivered.push(previous);
+        }
+    }
+
+    fn next_unit(&mut self) -> f32 {
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng_state = x;
+        (x >> 40) as f32 / (1_u64 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_loss_drops_everything_and_counts_it() {
+        let settings = LossInjection {
+            loss_percent: 100.0,
+            reorder_percent: 0.0,
+        };
+        let mut injector = LossInjector::new(settings, 7);
+        let mut delivered = Vec::new();
+        for seq in 0..10 {
+            injector.process(seq, &mut delivered);
+        }
+        assert!(delivered.is_empty());
+        assert_eq!(injector.dropped(), 10);
+    }
+
+    #[test]
+    fn reorder_releases_a_held_frame_behind_the_next_one() {
+        let hold = LossInjection {
+            loss_percent: 0.0,
+            reorder_percent: 100.0,
+        };
+        let mut injector = LossInjector::new(hold, 7);
+        let mut delivered = Vec::new();
+        injector.process(1, &mut delivered);
+        assert!(delivered.is_empty());
+
+        injector.settings.reorder_percent = 0.0;
+        injector.process(2, &mut delivered);
+        assert_eq!(delivered, vec![2, 1]);
+    }
+
+    #[test]
+    fn partial_loss_lands_near_the_configured_rate() {
+        let settings = LossInjection {
+            loss_percent: 20.0,
+            reorder_percent: 0.0,
+        };
+        let mut injector = LossInjector::new(settings, 12_345);
+        let mut delivered = Vec::new();
+        for seq in 0..10_000 {
+            injector.process(seq, &mut delivered);
+        }
+        let dropped = injector.dropped() as f32 / 10_000.0;
+        assert!((0.17..0.23).contains(&dropped), "dropped {dropped}");
+    }
+}
@@ -1,24 +1,270 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
 pub mod audio_in;
 pub mod audio_out;
 pub mod client;
 pub mod codec;
 pub mod hotkeys;
+pub mod input_monitor;
+pub mod loss_injection;
 pub mod quality;
+pub mod recorder;
 pub mod resampler;
 pub mod vad;
 
-pub use client::{AudioQualityMetrics, VoiceService, VoiceSharedState};
+pub use client::{
+    AudioQualityMetrics, CueClips, SpeakerStats, VoiceService, VoiceSharedState, VoiceTransport,
+    OPUS_BITRATE_MAX_BPS, OPUS_BITRATE_MIN_BPS, OPUS_COMPLEXITY_MAX, OPUS_COMPLEXITY_MIN,
+    OPUS_PACKET_LOSS_MAX_PCT, RX_JITTER_MAX_MAX, RX_JITTER_MAX_MIN, RX_JITTER_TARGET_MAX,
+    RX_JITTER_TARGET_MIN, SOUNDBOARD_MIX_GAIN_MAX,
+};
+pub use input_monitor::InputMonitor;
+
+// The codec's rate; a device opened here needs no resampling stage.
+const PREFERRED_DEVICE_SAMPLE_RATE: u32 = 48_000;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AudioDevice {
     pub id: String,
     pub name: String,
+    pub default_sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+    pub sample_format: Option<String>,
+}
+
+impl AudioDevice {
+    // Devices that fail to report a default config are still listed by name.
+    fn new(id: String, name: String, config: Option<cpal::SupportedStreamConfig>) -> Self {
+        Self {
+            id,
+            name,
+            default_sample_rate: config.as_ref().map(|config| config.sample_rate().0),
+            channels: config.as_ref().map(|config| config.channels()),
+            sample_format: config.map(|config| config.sample_format().to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct AudioHost {
+    pub id: String,
+    pub default: bool,
+}
+
+pub fn list_audio_hosts() -> Vec<AudioHost> {
+    let default_id = cpal::default_host().id();
+    cpal::available_hosts()
+        .into_iter()
+        .map(|host_id| AudioHost {
+            id: host_id.name().to_string(),
+            default: host_id == default_id,
+        })
+        .collect()
+}
+
+// Saved ids are matched case-insensitively and handed back in cpal's spelling.
+pub fn canonical_audio_host_id(raw: &str) -> Option<String> {
+    find_host_id(raw).map(|host_id| host_id.name().to_string())
+}
+
+fn find_host_id(raw: &str) -> Option<cpal::HostId> {
+    cpal::available_hosts()
+        .into_iter()
+        .find(|host_id| host_id.name().eq_ignore_ascii_case(raw.trim()))
+}
+
+// Like a saved device, a saved host that is missing here falls back to the default without
+// touching the config, so the choice comes back once the backend is available again.
+fn resolve_host(host_id: Option<&str>) -> cpal::Host {
+    let Some(raw) = host_id else {
+        return cpal::default_host();
+    };
+    match find_host_id(raw).map(cpal::host_from_id) {
+        Some(Ok(host)) => host,
+        Some(Err(err)) => {
+            log::warn!("audio host \"{raw}\" failed to open: {err}; using the default host");
+            cpal::default_host()
+        }
+        None => {
+            log::warn!("audio host \"{raw}\" is not available; using the default host");
+            cpal::default_host()
+        }
+    }
+}
+
+// The first device with a name keeps the bare name as its id, so saved selections still match;
+// later duplicates get "#2", "#3", ... in enumeration order.
+#[derive(Default)]
+struct DeviceIdAllocator {
+    seen: HashMap<String, usize>,
 }
 
-pub fn list_input_devices() -> Vec<AudioDevice> {
-    audio_in::list_input_devices()
+impl DeviceIdAllocator {
+    fn next_id(&mut self, name: &str) -> String {
+        let count = self.seen.entry(name.to_string()).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            name.to_string()
+        } else {
+            format!("{name}#{count}")
+        }
+    }
 }
 
-pub fn list_output_devices() -> Vec<AudioDevice> {
-    audio_out::list_output_devices()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeviceMatch {
+    Found(usize),
+    Default,
+    SavedMissing,
+}
+
+// A missing saved device falls back to the default without touching the saved id, so the
+// preference comes back once the device is plugged in again.
+fn match_device(names: &[String], selected_device_id: Option<&str>) -> DeviceMatch {
+    let Some(target_id) = selected_device_id else {
+        return DeviceMatch::Default;
+    };
+    let mut ids = DeviceIdAllocator::default();
+    names
+        .iter()
+        .position(|name| ids.next_id(name) == target_id)
+        .map_or(DeviceMatch::SavedMissing, DeviceMatch::Found)
+}
+
+// Only ranges in the default's sample format are considered, since that one is known to work
+// with the stream builders; a matching channel count wins over a different one.
+fn select_stream_config(
+    kind: &str,
+    default: cpal::SupportedStreamConfig,
+    ranges: impl IntoIterator<Item = cpal::SupportedStreamConfigRange>,
+    prefer_48k: bool,
+) -> cpal::SupportedStreamConfig {
+    if !prefer_48k || default.sample_rate().0 == PREFERRED_DEVICE_SAMPLE_RATE {
+        return default;
+    }
+    let preferred = ranges
+        .into_iter()
+        .filter(|range| {
+            range.sample_format() == default.sample_format()
+                && (range.min_sample_rate().0..=range.max_sample_rate().0)
+                    .contains(&PREFERRED_DEVICE_SAMPLE_RATE)
+        })
+        .min_by_key(|range| range.channels() != default.channels())
+        .map(|range| range.with_sample_rate(cpal::SampleRate(PREFERRED_DEVICE_SAMPLE_RATE)));
+    match preferred {
+        Some(config) => {
+            log::info!(
+                "{kind} opened at 48 kHz instead of the default {} Hz",
+                default.sample_rate().0
+            );
+            config
+        }
+        None => {
+            log::info!(
+                "{kind} does not offer 48 kHz; using the default {} Hz",
+                default.sample_rate().0
+            );
+            default
+        }
+    }
+}
+
+pub fn list_input_devices(host_id: Option<&str>) -> Vec<AudioDevice> {
+    audio_in::list_input_devices(host_id)
+}
+
+pub fn list_output_devices(host_id: Option<&str>) -> Vec<AudioDevice> {
+    audio_out::list_output_devices(host_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audio_host_ids_match_case_insensitively() {
+        let hosts = list_audio_hosts();
+        assert_eq!(hosts.iter().filter(|host| host.default).count(), 1);
+        for host in &hosts {
+            let shouted = format!(" {} ", host.id.to_ascii_uppercase());
+            assert_eq!(canonical_audio_host_id(&shouted), Some(host.id.clone()));
+        }
+        assert_eq!(canonical_audio_host_id("no-such-backend"), None);
+    }
+
+    #[test]
+    fn device_id_allocator_suffixes_duplicate_names() {
+        let mut ids = DeviceIdAllocator::default();
+        assert_eq!(ids.next_id("USB Audio Device"), "USB Audio Device");
+        assert_eq!(ids.next_id("Speakers"), "Speakers");
+        assert_eq!(ids.next_id("USB Audio Device"), "USB Audio Device#2");
+        assert_eq!(ids.next_id("USB Audio Device"), "USB Audio Device#3");
+    }
+
+    #[test]
+    fn select_stream_config_prefers_48k_in_the_default_format() {
+        use cpal::SupportedStreamConfigRange as Range;
+        use cpal::{SampleFormat, SampleRate, SupportedBufferSize, SupportedStreamConfig};
+
+        let buffer = SupportedBufferSize::Unknown;
+        let default = SupportedStreamConfig::new(2, SampleRate(44_100), buffer, SampleFormat::F32);
+        let ranges = vec![
+            Range::new(
+                2,
+                SampleRate(48_000),
+                SampleRate(48_000),
+                buffer,
+                SampleFormat::I16,
+            ),
+            Range::new(
+                1,
+                SampleRate(8_000),
+                SampleRate(96_000),
+                buffer,
+                SampleFormat::F32,
+            ),
+            Range::new(
+                2,
+                SampleRate(44_100),
+                SampleRate(48_000),
+                buffer,
+                SampleFormat::F32,
+            ),
+        ];
+
+        let chosen = select_stream_config("output", default.clone(), ranges.clone(), true);
+        assert_eq!(chosen.sample_rate().0, 48_000);
+        assert_eq!(chosen.channels(), 2);
+        assert_eq!(chosen.sample_format(), SampleFormat::F32);
+
+        assert_eq!(
+            select_stream_config("output", default.clone(), ranges.clone(), false),
+            default
+        );
+        // 48 kHz is only offered in another sample format.
+        assert_eq!(
+            select_stream_config("output", default.clone(), ranges[..1].to_vec(), true),
+            default
+        );
+    }
+
+    #[test]
+    fn match_device_reports_missing_saved_device() {
+        let names = vec!["Headset".to_string(), "Headset".to_string()];
+        assert_eq!(match_device(&names, None), DeviceMatch::Default);
+        assert_eq!(
+            match_device(&names, Some("Headset#2")),
+            DeviceMatch::Found(1)
+        );
+        assert_eq!(
+            match_device(&names, Some("Studio Mic")),
+            DeviceMatch::SavedMissing
+        );
+        assert_eq!(
+            match_device(&[], Some("Headset")),
+            DeviceMatch::SavedMissing
+        );
+    }
 }
@@ -14,6 +14,23 @@ pub fn soft_limiter(sample: f32) -> f32 {
     }
 }
 
+pub fn rms_level(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+    let mut sum = 0.0_f32;
+    for &sample in frame {
+        sum += sample * sample;
+    }
+    (sum / frame.len() as f32).sqrt()
+}
+
+pub fn peak_level(frame: &[f32]) -> f32 {
+    frame
+        .iter()
+        .fold(0.0_f32, |peak, &sample| peak.max(sample.abs()))
+}
+
 pub fn should_conceal_gap(
     buffered_len: usize,
     gap_frames: u64,
@@ -51,6 +68,12 @@ pub fn mix_mono_frames(
     }
 
     let norm = (active_frames as f32).sqrt().max(1.0);
+    let headroom_gain = mix_headroom_gain(
+        active_frames,
+        peak_level(output),
+        headroom_gain,
+        limiter_drive,
+    );
     let mut clip_samples = 0_u64;
     let mut nan_samples = 0_u64;
     for sample in output.iter_mut() {
@@ -72,3 +95,86 @@ pub fn mix_mono_frames(
         nan_samples,
     }
 }
+
+// Headroom only guards against speakers summing past full scale, so a lone speaker that stays
+// under the limiter knee is left at unity. Above the knee the gain eases down to the usual
+// headroom instead of switching, so peaks hovering near it don't pump between frames.
+fn mix_headroom_gain(
+    active_frames: usize,
+    bus_peak: f32,
+    headroom_gain: f32,
+    limiter_drive: f32,
+) -> f32 {
+    if active_frames == 1 {
+        (1.0 / (bus_peak * limiter_drive)).clamp(headroom_gain, 1.0)
+    } else {
+        headroom_gain
+    }
+}
+
+// Stable pan in [-spread, spread] so a speaker keeps the same position across sessions.
+pub fn stereo_pan_for_session(session: u32, spread: f32) -> f32 {
+    let hashed = session.wrapping_mul(0x9E37_79B1) >> 16;
+    let unit = hashed as f32 / u16::MAX as f32;
+    (unit * 2.0 - 1.0) * spread.clamp(0.0, 1.0)
+}
+
+// Constant-power pan law: -1.0 is hard left, 0.0 center, 1.0 hard right.
+pub fn pan_gains(pan: f32) -> (f32, f32) {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+    (angle.cos(), angle.sin())
+}
+
+// Each source is (frame, pan, gain).
+pub fn mix_stereo_frames(
+    frames: &[(&[f32], f32, f32)],
+    left: &mut [f32],
+    right: &mut [f32],
+    headroom_gain: f32,
+    limiter_drive: f32,
+) -> MixMonoResult {
+    left.fill(0.0);
+    right.fill(0.0);
+
+    let mut active_frames = 0_usize;
+    for (frame, pan, gain) in frames {
+        if frame.is_empty() {
+            continue;
+        }
+        active_frames = active_frames.saturating_add(1);
+        let (left_gain, right_gain) = pan_gains(*pan);
+        let (left_gain, right_gain) = (left_gain * gain, right_gain * gain);
+        for (idx, sample) in frame.iter().take(left.len().min(right.len())).enumerate() {
+            left[idx] += *sample * left_gain;
+            right[idx] += *sample * right_gain;
+        }
+    }
+
+    if active_frames == 0 {
+        return MixMonoResult::default();
+    }
+
+    let norm = (active_frames as f32).sqrt().max(1.0);
+    let bus_peak = peak_level(left).max(peak_level(right));
+    let headroom_gain = mix_headroom_gain(active_frames, bus_peak, headroom_gain, limiter_drive);
+    let mut clip_samples = 0_u64;
+    let mut nan_samples = 0_u64;
+    for sample in left.iter_mut().chain(right.iter_mut()) {
+        let pre = *sample * (headroom_gain / norm);
+        if pre.abs() >= 1.0 {
+            clip_samples = clip_samples.saturating_add(1);
+        }
+        let mut limited = soft_limiter(pre * limiter_drive);
+        if !limited.is_finite() {
+            nan_samples = nan_samples.saturating_add(1);
+            limited = 0.0;
+        }
+        *sample = limited;
+    }
+
+    MixMonoResult {
+        active_frames,
+        clip_samples,
+        nan_samples,
+    }
+}
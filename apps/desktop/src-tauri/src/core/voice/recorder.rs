@@ -0,0 +1,273 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::core::events::{self, RecordingEvent};
+
+const APP_DIR: &str = "Harmony";
+const RECORDINGS_DIR: &str = "recordings";
+const RECORDING_SAMPLE_RATE: u32 = 48_000;
+const RECORDING_MAX_SECONDS: u64 = 30 * 60;
+const RECORDING_MAX_SAMPLES: u64 = RECORDING_SAMPLE_RATE as u64 * RECORDING_MAX_SECONDS;
+const RECORDING_QUEUE_CHUNKS: usize = 512;
+// Flush inbound audio on its own if no local frames arrive for this long (e.g. no mic).
+const RECORDING_PLAYBACK_BACKLOG_SAMPLES: usize = RECORDING_SAMPLE_RATE as usize / 5;
+const WAV_HEADER_LEN: usize = 44;
+pub const MIC_SAMPLE_MAX_SECONDS: u32 = 30;
+
+enum RecorderChunk {
+    Playback(Vec<f32>),
+    Local(Vec<f32>),
+}
+
+// Returned by record_mic_sample; the device fields describe the capture before resampling.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct MicSampleInfo {
+    pub path: String,
+    pub seconds: u32,
+    pub sample_rate: u32,
+    pub device_name: Option<String>,
+    pub device_sample_rate: Option<u32>,
+}
+
+// Where a recording comes from; mic samples skip the call recording events.
+#[derive(Debug, Clone, Copy)]
+struct RecordingSpec {
+    file_prefix: &'static str,
+    include_self: bool,
+    max_samples: u64,
+    emit_events: bool,
+}
+
+// WAV encoding runs on its own thread so the media tick only pays for a channel send.
+pub struct VoiceRecorder {
+    tx: SyncSender<RecorderChunk>,
+    include_self: bool,
+    path: PathBuf,
+}
+
+impl VoiceRecorder {
+    pub fn start(app: AppHandle, include_self: bool) -> Result<Self, String> {
+        Self::spawn(
+            app,
+            RecordingSpec {
+                file_prefix: "harmony",
+                include_self,
+                max_samples: RECORDING_MAX_SAMPLES,
+                emit_events: true,
+            },
+        )
+    }
+
+    // Raw 48 kHz capture only, finishing on its own after `seconds`.
+    pub fn start_mic_sample(app: AppHandle, seconds: u32) -> Result<Self, String> {
+        Self::spawn(
+            app,
+            RecordingSpec {
+                file_prefix: "harmony-mic",
+                include_self: true,
+                max_samples: u64::from(RECORDING_SAMPLE_RATE) * u64::from(seconds),
+                emit_events: false,
+            },
+        )
+    }
+
+    fn spawn(app: AppHandle, spec: RecordingSpec) -> Result<Self, String> {
+        let dir = resolve_recordings_dir()?;
+        fs::create_dir_all(&dir)
+            .map_err(|err| format!("failed to create recordings directory: {err}"))?;
+        let path = dir.join(format!("{}-{}.wav", spec.file_prefix, epoch_millis()));
+        let file =
+            File::create(&path).map_err(|err| format!("failed to create recording file: {err}"))?;
+        let mut writer = BufWriter::new(file);
+        writer
+            .write_all(&wav_header(0))
+            .map_err(|err| format!("failed to write recording header: {err}"))?;
+
+        let (tx, rx) = mpsc::sync_channel(RECORDING_QUEUE_CHUNKS);
+        let worker_path = path.clone();
+        std::thread::Builder::new()
+            .name("harmony-recorder".to_string())
+            .spawn(move || run_recorder(app, rx, writer, spec, worker_path))
+            .map_err(|err| format!("failed to start recorder thread: {err}"))?;
+
+        log::info!("recording started: path=\"{}\"", path.display());
+        Ok(Self {
+            tx,
+            include_self: spec.include_self,
+            path,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Returns false once the writer has finished and the recorder should be dropped.
+    pub fn push_playback(&self, samples: &[f32]) -> bool {
+        self.push(RecorderChunk::Playback(samples.to_vec()))
+    }
+
+    pub fn push_local(&self, samples: &[f32]) -> bool {
+        if !self.include_self {
+            return true;
+        }
+        self.push(RecorderChunk::Local(samples.to_vec()))
+    }
+
+    fn push(&self, chunk: RecorderChunk) -> bool {
+        match self.tx.try_send(chunk) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                log::debug!("recording queue full; dropping audio chunk");
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+fn run_recorder(
+    app: AppHandle,
+    rx: Receiver<RecorderChunk>,
+    mut writer: BufWriter<File>,
+    spec: RecordingSpec,
+    path: PathBuf,
+) {
+    let include_self = spec.include_self;
+    let mut playback = VecDeque::new();
+    let mut local = VecDeque::new();
+    let mut samples_written = 0_u64;
+    let mut reason = "stopped".to_string();
+
+    while let Ok(chunk) = rx.recv() {
+        match chunk {
+            RecorderChunk::Playback(samples) => playback.extend(samples),
+            RecorderChunk::Local(samples) => local.extend(samples),
+        }
+
+        let mut ready = if include_self {
+            local.len()
+        } else {
+            playback.len()
+        };
+        if include_self && local.is_empty() && playback.len() >= RECORDING_PLAYBACK_BACKLOG_SAMPLES
+        {
+            ready = playback.len();
+        }
+        let remaining = spec.max_samples.saturating_sub(samples_written);
+        let ready = ready.min(usize::try_from(remaining).unwrap_or(usize::MAX));
+
+        let mut write_result = Ok(());
+        for _ in 0..ready {
+            let sample = local.pop_front().unwrap_or(0.0) + playback.pop_front().unwrap_or(0.0);
+            let pcm = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            write_result = writer.write_all(&pcm.to_le_bytes());
+            if write_result.is_err() {
+                break;
+            }
+            samples_written += 1;
+        }
+        if let Err(err) = write_result {
+            reason = format!("write failed: {err}");
+            break;
+        }
+        if samples_written >= spec.max_samples {
+            reason = "duration limit reached".to_string();
+            break;
+        }
+    }
+
+    if let Err(err) = finalize_wav(writer, samples_written) {
+        reason = format!("write failed: {err}");
+    }
+    log::info!(
+        "recording finished: path=\"{}\" samples={samples_written} reason={reason}",
+        path.display()
+    );
+    if !spec.emit_events {
+        return;
+    }
+    let payload = RecordingEvent {
+        active: false,
+        path: Some(path.display().to_string()),
+        reason: Some(reason),
+    };
+    let _ = events::emit_recording(&app, &payload);
+}
+
+fn finalize_wav(mut writer: BufWriter<File>, samples_written: u64) -> io::Result<()> {
+    let data_len = u32::try_from(samples_written * 2).unwrap_or(u32::MAX);
+    writer.seek(SeekFrom::Start(0))?;
+    writer.write_all(&wav_header(data_len))?;
+    writer.flush()
+}
+
+fn wav_header(data_len: u32) -> [u8; WAV_HEADER_LEN] {
+    let channels = 1_u16;
+    let bits_per_sample = 16_u16;
+    let block_align = channels * bits_per_sample / 8;
+    let byte_rate = RECORDING_SAMPLE_RATE * u32::from(block_align);
+
+    let mut header = [0_u8; WAV_HEADER_LEN];
+    header[0..4].copy_from_slice(b"RIFF");
+    header[4..8].copy_from_slice(&data_len.saturating_add(36).to_le_bytes());
+    header[8..12].copy_from_slice(b"WAVE");
+    header[12..16].copy_from_slice(b"fmt ");
+    header[16..20].copy_from_slice(&16_u32.to_le_bytes());
+    header[20..22].copy_from_slice(&1_u16.to_le_bytes());
+    header[22..24].copy_from_slice(&channels.to_le_bytes());
+    header[24..28].copy_from_slice(&RECORDING_SAMPLE_RATE.to_le_bytes());
+    header[28..32].copy_from_slice(&byte_rate.to_le_bytes());
+    header[32..34].copy_from_slice(&block_align.to_le_bytes());
+    header[34..36].copy_from_slice(&bits_per_sample.to_le_bytes());
+    header[36..40].copy_from_slice(b"data");
+    header[40..44].copy_from_slice(&data_len.to_le_bytes());
+    header
+}
+
+fn resolve_recordings_dir() -> Result<PathBuf, String> {
+    let base = dirs::data_local_dir()
+        .or_else(dirs::data_dir)
+        .ok_or_else(|| "failed to resolve local data directory".to_string())?;
+    Ok(base.join(APP_DIR).join(RECORDINGS_DIR))
+}
+
+fn epoch_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wav_header_describes_48k_mono_pcm16() {
+        let header = wav_header(9_600);
+        assert_eq!(&header[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(header[4..8].try_into().unwrap()), 9_636);
+        assert_eq!(u16::from_le_bytes(header[22..24].try_into().unwrap()), 1);
+        assert_eq!(
+            u32::from_le_bytes(header[24..28].try_into().unwrap()),
+            48_000
+        );
+        assert_eq!(
+            u32::from_le_bytes(header[28..32].try_into().unwrap()),
+            96_000
+        );
+        assert_eq!(&header[36..40], b"data");
+        assert_eq!(
+            u32::from_le_bytes(header[40..44].try_into().unwrap()),
+            9_600
+        );
+    }
+}
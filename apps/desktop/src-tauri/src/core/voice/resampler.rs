@@ -3,6 +3,23 @@ use rubato::{Fft, FixedSync, Resampler};
 
 const RESAMPLER_CHUNK_FRAMES: usize = 960;
 
+// Fewer FFT sub-chunks lengthen the filter, trading latency for a steeper anti-aliasing cutoff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResamplerQuality {
+    #[default]
+    Standard,
+    High,
+}
+
+impl ResamplerQuality {
+    fn sub_chunks(self) -> usize {
+        match self {
+            Self::Standard => 2,
+            Self::High => 1,
+        }
+    }
+}
+
 pub struct MonoResampler {
     passthrough: bool,
     engine: Option<Fft<f32>>,
@@ -11,7 +28,11 @@ pub struct MonoResampler {
 }
 
 impl MonoResampler {
-    pub fn new(input_rate: u32, output_rate: u32) -> Result<Self, String> {
+    pub fn new(
+        input_rate: u32,
+        output_rate: u32,
+        quality: ResamplerQuality,
+    ) -> Result<Self, String> {
         let safe_input = input_rate.max(1);
         let safe_output = output_rate.max(1);
         let passthrough = safe_input == safe_output;
@@ -23,7 +44,7 @@ impl MonoResampler {
                     safe_input as usize,
                     safe_output as usize,
                     RESAMPLER_CHUNK_FRAMES,
-                    2,
+                    quality.sub_chunks(),
                     1,
                     FixedSync::Input,
                 )
@@ -79,7 +100,7 @@ impl MonoResampler {
         if self.output_pending.is_empty() {
             return;
         }
-        output.extend(self.output_pending.drain(..));
+        output.append(&mut self.output_pending);
     }
 }
 
@@ -89,7 +110,8 @@ mod tests {
 
     #[test]
     fn passthrough_when_rates_match() {
-        let mut resampler = MonoResampler::new(48_000, 48_000).expect("creates passthrough");
+        let mut resampler = MonoResampler::new(48_000, 48_000, ResamplerQuality::Standard)
+            .expect("creates passthrough");
         let input = vec![0.1_f32, -0.2, 0.3, -0.4];
         let mut output = Vec::new();
         resampler
@@ -100,7 +122,8 @@ mod tests {
 
     #[test]
     fn resamples_44100_to_48000_without_nans() {
-        let mut resampler = MonoResampler::new(44_100, 48_000).expect("creates resampler");
+        let mut resampler = MonoResampler::new(44_100, 48_000, ResamplerQuality::Standard)
+            .expect("creates resampler");
         let input = (0..4_410)
             .map(|idx| ((idx as f32 / 40.0).sin()) * 0.7)
             .collect::<Vec<_>>();
@@ -1,27 +1,135 @@
 #![allow(dead_code)]
 
+use crate::core::config::VoiceQualityConfig;
+
+pub const VAD_THRESHOLD_MIN: f32 = 0.001;
+pub const VAD_THRESHOLD_MAX: f32 = 0.5;
+pub const VAD_OFF_RATIO: f32 = 0.7;
+pub const VAD_HOLD_FRAMES_MAX: u32 = 50;
+pub const VAD_ATTACK_FRAMES_MAX: u32 = 10;
+pub const VAD_SMOOTHING_MAX: f32 = 0.95;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VadSettings {
+    pub threshold: f32,
+    pub off_threshold: Option<f32>,
+    pub hold_frames: u32,
+    pub attack_frames: u32,
+    pub smoothing: f32,
+}
+
+impl VadSettings {
+    pub fn new(quality: &VoiceQualityConfig) -> Self {
+        Self {
+            threshold: quality.vad_threshold,
+            off_threshold: quality.vad_off_threshold,
+            hold_frames: quality.vad_hold_frames,
+            attack_frames: quality.vad_attack_frames,
+            smoothing: quality.vad_smoothing,
+        }
+    }
+}
+
+// Opens after `attack_frames` consecutive frames at or above the on threshold and closes after
+// `hold_frames` frames below the off threshold; both compare against a smoothed level.
 #[derive(Debug, Clone)]
 pub struct VoiceActivityDetector {
     on_threshold: f32,
     off_threshold: f32,
     hold_frames: u32,
     hold_remaining: u32,
+    attack_frames: u32,
+    attack_count: u32,
+    smoothing: f32,
+    smoothed_level: f32,
     speaking: bool,
 }
 
 impl VoiceActivityDetector {
     pub const fn new(threshold: f32) -> Self {
-        let off_threshold = threshold * 0.7;
+        let off_threshold = threshold * VAD_OFF_RATIO;
         Self {
             on_threshold: threshold,
             off_threshold,
             hold_frames: 3,
             hold_remaining: 0,
+            attack_frames: 1,
+            attack_count: 0,
+            smoothing: 0.0,
+            smoothed_level: 0.0,
             speaking: false,
         }
     }
 
+    pub fn with_settings(settings: VadSettings) -> Self {
+        let mut vad = Self::new(settings.threshold);
+        vad.apply(settings);
+        vad
+    }
+
+    pub fn apply(&mut self, settings: VadSettings) {
+        self.set_on_threshold(settings.threshold);
+        self.set_off_threshold(
+            settings
+                .off_threshold
+                .unwrap_or(self.on_threshold * VAD_OFF_RATIO),
+        );
+        self.set_hold_frames(settings.hold_frames);
+        self.set_attack_frames(settings.attack_frames);
+        self.set_smoothing(settings.smoothing);
+    }
+
+    pub fn on_threshold(&self) -> f32 {
+        self.on_threshold
+    }
+
+    pub fn off_threshold(&self) -> f32 {
+        self.off_threshold
+    }
+
+    pub fn hold_frames(&self) -> u32 {
+        self.hold_frames
+    }
+
+    pub fn attack_frames(&self) -> u32 {
+        self.attack_frames
+    }
+
+    pub fn smoothing(&self) -> f32 {
+        self.smoothing
+    }
+
+    pub fn set_on_threshold(&mut self, threshold: f32) {
+        self.on_threshold = threshold.clamp(VAD_THRESHOLD_MIN, VAD_THRESHOLD_MAX);
+    }
+
+    pub fn set_off_threshold(&mut self, threshold: f32) {
+        self.off_threshold = threshold.clamp(VAD_THRESHOLD_MIN, self.on_threshold);
+    }
+
+    pub fn set_hold_frames(&mut self, hold_frames: u32) {
+        self.hold_frames = hold_frames.min(VAD_HOLD_FRAMES_MAX);
+        self.hold_remaining = self.hold_remaining.min(self.hold_frames);
+    }
+
+    pub fn set_attack_frames(&mut self, attack_frames: u32) {
+        self.attack_frames = attack_frames.clamp(1, VAD_ATTACK_FRAMES_MAX);
+        self.attack_count = self.attack_count.min(self.attack_frames);
+    }
+
+    // 0 uses the raw level; higher values weight the previous smoothed level more.
+    pub fn set_smoothing(&mut self, smoothing: f32) {
+        self.smoothing = if smoothing.is_finite() {
+            smoothing.clamp(0.0, VAD_SMOOTHING_MAX)
+        } else {
+            0.0
+        };
+    }
+
     pub fn is_speaking(&mut self, level: f32) -> bool {
+        self.smoothed_level += (level - self.smoothed_level) * (1.0 - self.smoothing);
+        let level = self.smoothed_level;
+
         if self.speaking {
             if level >= self.off_threshold {
                 self.hold_remaining = self.hold_frames;
@@ -32,10 +140,16 @@ impl VoiceActivityDetector {
                 return true;
             }
             self.speaking = false;
+            self.attack_count = 0;
             return false;
         }
 
-        if level >= self.on_threshold {
+        if level < self.on_threshold {
+            self.attack_count = 0;
+            return false;
+        }
+        self.attack_count += 1;
+        if self.attack_count >= self.attack_frames {
             self.speaking = true;
             self.hold_remaining = self.hold_frames;
             return true;
@@ -50,3 +164,50 @@ impl Default for VoiceActivityDetector {
         Self::new(0.25)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(attack_frames: u32, hold_frames: u32, smoothing: f32) -> VadSettings {
+        VadSettings {
+            threshold: 0.1,
+            off_threshold: Some(0.05),
+            hold_frames,
+            attack_frames,
+            smoothing,
+        }
+    }
+
+    #[test]
+    fn attack_requires_consecutive_frames_above_threshold() {
+        let mut vad = VoiceActivityDetector::with_settings(settings(3, 0, 0.0));
+        assert!(!vad.is_speaking(0.2));
+        assert!(!vad.is_speaking(0.2));
+        // A dip resets the attack counter.
+        assert!(!vad.is_speaking(0.0));
+        assert!(!vad.is_speaking(0.2));
+        assert!(!vad.is_speaking(0.2));
+        assert!(vad.is_speaking(0.2));
+    }
+
+    #[test]
+    fn release_holds_gate_open_below_off_threshold() {
+        let mut vad = VoiceActivityDetector::with_settings(settings(1, 2, 0.0));
+        assert!(vad.is_speaking(0.2));
+        assert!(vad.is_speaking(0.07));
+        assert!(vad.is_speaking(0.0));
+        assert!(vad.is_speaking(0.0));
+        assert!(!vad.is_speaking(0.0));
+    }
+
+    #[test]
+    fn smoothing_ignores_single_frame_bursts() {
+        let mut vad = VoiceActivityDetector::with_settings(settings(1, 0, 0.8));
+        assert!(!vad.is_speaking(0.3));
+        assert!(!vad.is_speaking(0.0));
+
+        let mut raw = VoiceActivityDetector::with_settings(settings(1, 0, 0.0));
+        assert!(raw.is_speaking(0.3));
+    }
+}
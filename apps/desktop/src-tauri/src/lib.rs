@@ -1,5 +1,5 @@
 mod core;
-use tauri::Manager;
+use tauri::{Manager, RunEvent};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -28,30 +28,89 @@ pub fn run() {
                     log::warn!("failed to emit initial state events: {err}");
                 }
             });
+            if let Some(path) = app.state::<core::AppCore>().config_watch_path() {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(core::config_watcher::watch(handle, path));
+            }
+            if let Some(port) = core::debug_server::debug_port_from_env() {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let sources = handle.state::<core::AppCore>().debug_sources().await;
+                    core::debug_server::serve(port, sources).await;
+                });
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             core::bootstrap,
             core::connect,
             core::disconnect,
+            core::reconnect_now,
             core::set_mute,
             core::set_deafen,
+            core::set_all_users_muted,
             core::set_ptt,
+            core::set_ptt_mode,
+            core::set_ptt_key_held,
             core::set_ptt_hotkey,
+            core::set_mute_hotkey,
+            core::set_deafen_hotkey,
+            core::hotkey_pressed,
+            core::set_jitter_buffer,
+            core::set_adaptive_enabled,
+            core::set_opus_complexity,
+            core::set_vad,
+            core::set_soundboard_mix,
+            core::set_loss_injection,
+            core::get_codec_capabilities,
+            core::get_voice_quality,
+            core::set_voice_quality,
+            core::set_self_position,
             core::set_input_device,
             core::set_output_device,
+            core::list_audio_hosts,
+            core::set_audio_host,
             core::set_server_endpoint,
             core::refresh_devices,
             core::get_audio_quality_metrics,
+            core::get_per_speaker_stats,
+            core::get_connection_summary,
+            core::start_input_monitor,
+            core::stop_input_monitor,
             core::send_message,
+            core::send_private_message,
+            core::kick_user,
+            core::ban_user,
+            core::get_message_history,
+            core::get_server_info,
+            core::start_recording,
+            core::stop_recording,
+            core::record_mic_sample,
+            core::join_channel_with_password,
+            core::listen_to_channel,
+            core::stop_listening_to_channel,
+            core::add_access_token,
+            core::create_channel,
+            core::export_config,
+            core::import_config,
             core::list_soundboard_clips,
             core::import_soundboard_clip,
+            core::soundboard_usage,
             core::delete_soundboard_clip,
+            core::set_soundboard_clip_category,
+            core::list_soundboard_categories,
             core::play_soundboard_clip
         ])
-        .run(tauri::generate_context!());
+        .build(tauri::generate_context!());
 
-    if let Err(err) = run_result {
-        eprintln!("error while running tauri application: {err}");
+    match run_result {
+        Ok(app) => app.run(|handle, event| {
+            // Play counts are batched, so write out whatever is still pending.
+            if let RunEvent::Exit = event {
+                let state = handle.state::<core::AppCore>();
+                tauri::async_runtime::block_on(state.flush_soundboard_usage());
+            }
+        }),
+        Err(err) => eprintln!("error while running tauri application: {err}"),
     }
 }
@@ -21,6 +21,40 @@ fn mixer_blends_two_speakers_with_headroom() {
     assert!(approx_eq(out[0], 0.171, 0.01));
 }
 
+#[test]
+fn mixer_leaves_a_lone_speaker_without_headroom_attenuation() {
+    let frame_len = 960;
+    let voice = vec![0.4_f32; frame_len];
+    let mut out = vec![0.0_f32; frame_len];
+
+    let mixed = quality::mix_mono_frames(&[voice.as_slice()], &mut out, 0.90, 1.35);
+    assert_eq!(mixed.active_frames, 1);
+    assert_eq!(mixed.clip_samples, 0);
+    assert!(approx_eq(out[0], 0.4 * 1.35, 0.0001));
+
+    let mut left = vec![0.0_f32; frame_len];
+    let mut right = vec![0.0_f32; frame_len];
+    quality::mix_stereo_frames(
+        &[(voice.as_slice(), 0.0, 1.0)],
+        &mut left,
+        &mut right,
+        0.90,
+        1.35,
+    );
+    let (center_gain, _) = quality::pan_gains(0.0);
+    assert!(approx_eq(left[0], 0.4 * center_gain * 1.35, 0.0001));
+
+    // Frames either side of the limiter knee come out at nearly the same level.
+    let knee = 1.0 / 1.35;
+    let levels = [knee - 0.005, knee + 0.005].map(|peak| {
+        let frame = vec![peak; frame_len];
+        let mut out = vec![0.0_f32; frame_len];
+        quality::mix_mono_frames(&[frame.as_slice()], &mut out, 0.90, 1.35);
+        out[0]
+    });
+    assert!(approx_eq(levels[0], levels[1], 0.01));
+}
+
 #[test]
 fn limiter_prevents_runaway_mix_levels() {
     let frame_len = 960;
@@ -34,6 +68,65 @@ fn limiter_prevents_runaway_mix_levels() {
     assert!(out.iter().all(|sample| sample.abs() < 1.8));
 }
 
+#[test]
+fn stereo_mixer_pans_speakers_apart() {
+    let frame_len = 960;
+    let voice = vec![0.4_f32; frame_len];
+    let mut left = vec![0.0_f32; frame_len];
+    let mut right = vec![0.0_f32; frame_len];
+
+    let mixed = quality::mix_stereo_frames(
+        &[(voice.as_slice(), -1.0, 1.0), (voice.as_slice(), 0.5, 0.5)],
+        &mut left,
+        &mut right,
+        0.90,
+        1.35,
+    );
+    assert_eq!(mixed.active_frames, 2);
+    assert_eq!(mixed.nan_samples, 0);
+    assert!(left[0] > right[0]);
+
+    let (center_left, center_right) = quality::pan_gains(0.0);
+    assert!(approx_eq(center_left, center_right, 0.0001));
+    assert!(approx_eq(
+        center_left * center_left + center_right * center_right,
+        1.0,
+        0.0001
+    ));
+}
+
+#[test]
+fn stereo_pan_is_stable_and_within_spread() {
+    for session in [0_u32, 1, 2, 42, 9_999, u32::MAX] {
+        let pan = quality::stereo_pan_for_session(session, 0.6);
+        assert_eq!(pan, quality::stereo_pan_for_session(session, 0.6));
+        assert!((-0.6..=0.6).contains(&pan));
+    }
+    assert_ne!(
+        quality::stereo_pan_for_session(1, 0.6),
+        quality::stereo_pan_for_session(2, 0.6)
+    );
+}
+
+#[test]
+fn rms_level_reports_signal_energy() {
+    assert_eq!(quality::rms_level(&[]), 0.0);
+    assert_eq!(quality::rms_level(&[0.0_f32; 960]), 0.0);
+    assert!(approx_eq(quality::rms_level(&[0.5_f32; 960]), 0.5, 0.0001));
+    assert!(approx_eq(
+        quality::rms_level(&[0.5, -0.5, 0.5, -0.5]),
+        0.5,
+        0.0001
+    ));
+}
+
+#[test]
+fn peak_level_reports_largest_magnitude() {
+    assert_eq!(quality::peak_level(&[]), 0.0);
+    assert_eq!(quality::peak_level(&[0.25, -0.75, 0.5]), 0.75);
+    assert!(quality::peak_level(&[0.1, f32::NAN, 0.2]) >= 0.2);
+}
+
 #[test]
 fn jitter_concealment_decision_matches_policy() {
     assert!(quality::should_conceal_gap(10, 1, false, 4, 10, 2));
@@ -44,7 +137,9 @@ fn jitter_concealment_decision_matches_policy() {
 
 #[test]
 fn resampler_generates_finite_audio_for_common_rates() {
-    let mut upsampler = resampler::MonoResampler::new(44_100, 48_000).expect("upsampler");
+    let mut upsampler =
+        resampler::MonoResampler::new(44_100, 48_000, resampler::ResamplerQuality::Standard)
+            .expect("upsampler");
     let input = (0..44_100)
         .map(|idx| ((idx as f32 / 35.0).sin()) * 0.6)
         .collect::<Vec<_>>();
@@ -55,7 +150,9 @@ fn resampler_generates_finite_audio_for_common_rates() {
     assert!(upsampled.len() > 1_000);
     assert!(upsampled.iter().all(|sample| sample.is_finite()));
 
-    let mut downsampler = resampler::MonoResampler::new(48_000, 44_100).expect("downsampler");
+    let mut downsampler =
+        resampler::MonoResampler::new(48_000, 44_100, resampler::ResamplerQuality::Standard)
+            .expect("downsampler");
     let mut downsampled = Vec::new();
     downsampler
         .process(&upsampled, &mut downsampled)
@@ -63,3 +160,24 @@ fn resampler_generates_finite_audio_for_common_rates() {
     assert!(downsampled.len() > 1_000);
     assert!(downsampled.iter().all(|sample| sample.is_finite()));
 }
+
+#[test]
+fn high_quality_resampler_downsamples_to_44100() {
+    let mut downsampler =
+        resampler::MonoResampler::new(48_000, 44_100, resampler::ResamplerQuality::High)
+            .expect("downsampler");
+    let input = (0..48_000)
+        .map(|idx| ((idx as f32 / 30.0).sin()) * 0.6)
+        .collect::<Vec<_>>();
+    let mut output = Vec::new();
+    downsampler
+        .process(&input, &mut output)
+        .expect("downsample succeeds");
+    assert!(output.iter().all(|sample| sample.is_finite()));
+    // One second in should come out as roughly one second at 44.1k, less the filter delay.
+    assert!(
+        (42_000..=44_100).contains(&output.len()),
+        "len {}",
+        output.len()
+    );
+}